@@ -174,6 +174,8 @@ class TerminalBridge:
             elif method == "agent.query":
                 # Agent query needs writer for streaming
                 result = await self.handle_agent_query(params, request_id, writer)
+            elif method == "agent.continue":
+                result = await self.handle_agent_continue(params, request_id, writer)
             elif method == "agent.cancel":
                 result = await self.handle_agent_cancel(params)
             elif method == "tool.approve":
@@ -182,10 +184,22 @@ class TerminalBridge:
                 result = await self.handle_session_list(params)
             elif method == "session.load":
                 result = await self.handle_session_load(params)
+            elif method == "session.search":
+                result = await self.handle_session_search(params)
             elif method == "session.export":
                 result = await self.handle_session_export(params)
             elif method == "session.delete":
                 result = await self.handle_session_delete(params)
+            elif method == "session.new":
+                result = await self.handle_session_new(params)
+            elif method == "session.fork":
+                result = await self.handle_session_fork(params)
+            elif method == "session.update":
+                result = await self.handle_session_update(params)
+            elif method == "session.rename":
+                result = await self.handle_session_rename(params)
+            elif method == "models.list":
+                result = await self.handle_models_list(params)
             else:
                 return self.create_error_response(
                     request_id, -32601, f"Method not found: {method}"
@@ -295,11 +309,77 @@ class TerminalBridge:
             "status": "streaming",
         }
 
+    async def handle_agent_continue(self, params: dict, request_id: Any, writer: asyncio.StreamWriter) -> dict:
+        """
+        Handle agent.continue request: resume a cancelled or cut-off response.
+        
+        Streams like agent.query under a new query ID, but adds no user message
+        to the session; the continuation is saved as another assistant message
+        tagged with the query it continues. A continuation can itself be
+        continued: its ID resolves back to the original query.
+        """
+        writer_id = id(writer)
+        client_id = self.connection_client_ids.get(writer_id, "unknown")
+        
+        if not self._check_rate_limit(client_id):
+            logger.warning(f"Rate limit exceeded for client {client_id}")
+            return {
+                "error": f"Rate limit exceeded. Maximum {self.max_queries_per_minute} queries per minute.",
+                "query_id": None,
+                "status": "rate_limited"
+            }
+        
+        previous_id = params.get("query_id")
+        if not previous_id:
+            raise ValueError("query_id required")
+        
+        # The interrupted query must be part of the active session
+        messages = self.current_session.messages if self.current_session else []
+        root_id = previous_id
+        for m in messages:
+            if m.role == MessageRole.ASSISTANT and m.metadata.get("query_id") == previous_id:
+                root_id = m.metadata.get("continues", previous_id)
+                break
+        asked = [m for m in messages if m.role == MessageRole.USER and m.metadata.get("query_id") == root_id]
+        if not asked:
+            raise ValueError(f"Unknown query {previous_id}")
+        answered = "".join(
+            m.content for m in messages
+            if m.role == MessageRole.ASSISTANT
+            and root_id in (m.metadata.get("query_id"), m.metadata.get("continues"))
+        )
+        
+        env_context = await self.context_manager.get_context(os.getcwd())
+        context = {
+            "environment": env_context.to_dict(),
+            "continues": root_id,
+            "partial_response": answered,
+        }
+        
+        query_id = str(uuid.uuid4())
+        logger.info(f"🤖 Continuing query {root_id} as {query_id}")
+        
+        task = asyncio.create_task(
+            self._stream_agent_response(
+                query_id, asked[-1].content, context, writer,
+                metadata={"continues": root_id},
+            )
+        )
+        self.active_streams[query_id] = task
+        
+        return {
+            "query_id": query_id,
+            "status": "streaming",
+        }
+
     async def _stream_agent_response(
-        self, query_id: str, message: str, context: dict, writer: asyncio.StreamWriter
+        self, query_id: str, message: str, context: dict, writer: asyncio.StreamWriter,
+        metadata: Optional[dict] = None,
     ):
         """
         Background task that streams agent response tokens.
+        
+        `metadata` is added to the saved assistant message.
         """
         from datetime import datetime
         assistant_response_content = []  # Collect response for session
@@ -402,7 +482,7 @@ class TerminalBridge:
                     content=full_response,
                     timestamp=datetime.now(),
                     token_count=len(full_response.split()),  # Simple word count
-                    metadata={"query_id": query_id}
+                    metadata={"query_id": query_id, **(metadata or {})}
                 )
                 self.current_session.add_message(assistant_msg)
                 self.session_manager.save_session(self.current_session)
@@ -472,9 +552,11 @@ class TerminalBridge:
     async def handle_session_list(self, params: dict) -> dict:
         """Handle session.list request."""
         limit = params.get("limit", 10)
+        # "updated_at" orders purely by update time, e.g. to find the latest session
+        pinned_first = params.get("sort") != "updated_at"
         
         try:
-            sessions = self.session_manager.list_sessions(limit=limit)
+            sessions = self.session_manager.list_sessions(limit=limit, pinned_first=pinned_first)
             return {
                 "status": "success",
                 "sessions": [
@@ -485,6 +567,7 @@ class TerminalBridge:
                         "updated_at": s.updated_at.isoformat(),
                         "message_count": s.message_count,
                         "total_tokens": s.total_tokens,
+                        "pinned": s.pinned,
                     }
                     for s in sessions
                 ]
@@ -493,6 +576,35 @@ class TerminalBridge:
             logger.error(f"Error listing sessions: {e}")
             return {"status": "error", "error": str(e)}
     
+    async def handle_session_search(self, params: dict) -> dict:
+        """Handle session.search request: match titles and message content."""
+        query = params.get("query")
+        if not query:
+            return {"status": "error", "error": "query required"}
+        limit = params.get("limit")
+        
+        try:
+            hits = self.session_manager.search_sessions(query, limit=limit)
+            return {
+                "status": "success",
+                "sessions": [
+                    {
+                        "session_id": s.session_id,
+                        "title": s.title,
+                        "created_at": s.created_at.isoformat(),
+                        "updated_at": s.updated_at.isoformat(),
+                        "message_count": s.message_count,
+                        "total_tokens": s.total_tokens,
+                        "pinned": s.pinned,
+                        "snippet": snippet,
+                    }
+                    for s, snippet in hits
+                ]
+            }
+        except Exception as e:
+            logger.error(f"Error searching sessions: {e}")
+            return {"status": "error", "error": str(e)}
+    
     async def handle_session_load(self, params: dict) -> dict:
         """Handle session.load request."""
         session_id = params.get("session_id")
@@ -525,6 +637,18 @@ class TerminalBridge:
             logger.error(f"Error loading session: {e}")
             return {"status": "error", "error": str(e)}
     
+    async def handle_models_list(self, params: dict) -> dict:
+        """Handle models.list request."""
+        # Only the built-in demo agent is available for now
+        return {
+            "models": [
+                {
+                    "name": "mock",
+                    "description": "Built-in demo agent (no network model)",
+                },
+            ]
+        }
+    
     async def handle_session_export(self, params: dict) -> dict:
         """Handle session.export request."""
         session_id = params.get("session_id")
@@ -544,7 +668,9 @@ class TerminalBridge:
                 return {
                     "status": "success",
                     "format": "markdown",
-                    "content": content
+                    "content": content,
+                    "byte_count": len(content.encode("utf-8")),
+                    "message_count": len(session.messages)
                 }
             else:
                 return {"status": "error", "error": f"Unsupported format: {format_type}"}
@@ -570,6 +696,117 @@ class TerminalBridge:
             logger.error(f"Error deleting session: {e}")
             return {"status": "error", "error": str(e)}
 
+    async def handle_session_new(self, params: dict) -> dict:
+        """Handle session.new request: start an empty session and switch to it."""
+        try:
+            session = self.session_manager.create_session(title=params.get("title"))
+            self.session_manager.save_session(session)
+            self.current_session = session
+            
+            logger.info(f"📝 Created new session: {session.metadata.session_id}")
+            return {
+                "status": "success",
+                "session_id": session.metadata.session_id,
+                "title": session.metadata.title,
+            }
+        except Exception as e:
+            logger.error(f"Error creating session: {e}")
+            return {"status": "error", "error": str(e)}
+
+    async def handle_session_fork(self, params: dict) -> dict:
+        """Handle session.fork request: copy a session and switch to the copy."""
+        session_id = params.get("session_id")
+        
+        try:
+            # Fork the current session if no session_id specified
+            if session_id:
+                source = self.session_manager.load_session(session_id)
+                if not source:
+                    return {"status": "error", "error": f"Session {session_id} not found"}
+            else:
+                source = self.current_session
+                if source is None:
+                    return {"status": "error", "error": "No active session to fork"}
+            
+            title = f"{source.metadata.title or source.metadata.session_id} (fork)"
+            fork = self.session_manager.create_session(title=title)
+            for msg in source.messages:
+                fork.add_message(msg)
+            self.session_manager.save_session(fork)
+            self.current_session = fork
+            
+            logger.info(f"🍴 Forked session {source.metadata.session_id} -> {fork.metadata.session_id}")
+            return {
+                "status": "success",
+                "session_id": fork.metadata.session_id,
+                "forked_from": source.metadata.session_id,
+                "title": fork.metadata.title,
+                "message_count": fork.metadata.message_count,
+            }
+        except Exception as e:
+            logger.error(f"Error forking session: {e}")
+            return {"status": "error", "error": str(e)}
+
+    async def handle_session_update(self, params: dict) -> dict:
+        """Handle session.update request: change session metadata (currently `pinned`)."""
+        session_id = params.get("session_id")
+        
+        if not session_id:
+            return {"status": "error", "error": "session_id required"}
+        
+        try:
+            session = self.session_manager.load_session(session_id)
+            if not session:
+                return {"status": "error", "error": f"Session {session_id} not found"}
+            
+            if "pinned" in params:
+                session.metadata.pinned = bool(params["pinned"])
+            self.session_manager.save_session(session)
+            
+            # Keep the in-memory active session in sync so its next save keeps the change
+            if self.current_session and self.current_session.metadata.session_id == session_id:
+                self.current_session.metadata.pinned = session.metadata.pinned
+            
+            return {
+                "status": "success",
+                "session_id": session_id,
+                "pinned": session.metadata.pinned,
+            }
+        except Exception as e:
+            logger.error(f"Error updating session: {e}")
+            return {"status": "error", "error": str(e)}
+
+    async def handle_session_rename(self, params: dict) -> dict:
+        """Handle session.rename request: change a session's title."""
+        session_id = params.get("session_id")
+        title = (params.get("title") or "").strip()
+        
+        if not session_id:
+            return {"status": "error", "error": "session_id required"}
+        if not title:
+            return {"status": "error", "error": "title required"}
+        
+        try:
+            session = self.session_manager.load_session(session_id)
+            if not session:
+                return {"status": "error", "error": f"Session {session_id} not found"}
+            
+            session.metadata.title = title
+            self.session_manager.save_session(session)
+            
+            # Keep the in-memory active session in sync so its next save keeps the change
+            if self.current_session and self.current_session.metadata.session_id == session_id:
+                self.current_session.metadata.title = title
+            
+            return {
+                "status": "success",
+                "session_id": session_id,
+                "title": title,
+            }
+        except Exception as e:
+            logger.error(f"Error renaming session: {e}")
+            return {"status": "error", "error": str(e)}
+
     def create_response(self, request_id: Any, result: Any) -> dict:
         """Create a JSON-RPC success response."""
         return {"jsonrpc": "2.0", "id": request_id, "result": result}
@@ -663,6 +900,11 @@ def main():
         action="store_true",
         help="Enable debug logging"
     )
+    parser.add_argument(
+        "--test",
+        action="store_true",
+        help="Listen on openagent-terminal-test.sock (pair with the terminal's --test)"
+    )
     parser.add_argument(
         "--execute",
         action="store_true",
@@ -698,10 +940,14 @@ def main():
     # Determine socket path - check env var, then arg, then default
     socket_path = args.socket or os.environ.get("OPENAGENT_SOCKET")
     if socket_path is None:
-        # Use test socket for now
+        # Must match the terminal's default (see DEFAULT_SOCKET_NAME in src/cli.rs)
         runtime_dir = os.environ.get("XDG_RUNTIME_DIR", "/tmp")
-        socket_path = f"{runtime_dir}/openagent-terminal-test.sock"
-        print(f"📦 Using test socket: {socket_path}")
+        if args.test:
+            socket_path = f"{runtime_dir}/openagent-terminal-test.sock"
+            print(f"📦 Using test socket: {socket_path}")
+        else:
+            socket_path = f"{runtime_dir}/openagent-terminal.sock"
+            print(f"📦 Using socket: {socket_path}")
     
     bridge = TerminalBridge(socket_path, demo_mode=demo_mode)
     
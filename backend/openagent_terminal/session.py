@@ -13,7 +13,7 @@ from datetime import datetime
 from enum import Enum
 from pathlib import Path
 from threading import Lock
-from typing import Any, Dict, List, Optional
+from typing import Any, Dict, List, Optional, Tuple
 
 
 class MessageRole(Enum):
@@ -68,6 +68,7 @@ class SessionMetadata:
     total_tokens: int
     title: Optional[str] = None
     tags: List[str] = field(default_factory=list)
+    pinned: bool = False
     
     def to_dict(self) -> Dict[str, Any]:
         """Serialize to dictionary."""
@@ -78,7 +79,8 @@ class SessionMetadata:
             "message_count": self.message_count,
             "total_tokens": self.total_tokens,
             "title": self.title,
-            "tags": self.tags
+            "tags": self.tags,
+            "pinned": self.pinned
         }
     
     @classmethod
@@ -91,7 +93,8 @@ class SessionMetadata:
             message_count=data["message_count"],
             total_tokens=data["total_tokens"],
             title=data.get("title"),
-            tags=data.get("tags", [])
+            tags=data.get("tags", []),
+            pinned=data.get("pinned", False)
         )
 
 
@@ -338,6 +341,13 @@ class SessionManager:
         Returns:
             Loaded session or None if not found/error
         """
+        session = self._read_session(session_id)
+        if session is not None:
+            self.current_session = session
+        return session
+    
+    def _read_session(self, session_id: str) -> Optional[Session]:
+        """Read a session from disk without making it the current one."""
         # Validate session_id to prevent path traversal
         if ".." in session_id or "/" in session_id or "\\" in session_id:
             return None
@@ -351,25 +361,69 @@ class SessionManager:
             with open(session_file, 'r', encoding='utf-8') as f:
                 data = json.load(f)
             
-            session = Session.from_dict(data)
-            self.current_session = session
-            return session
+            return Session.from_dict(data)
         except (json.JSONDecodeError, IOError, KeyError, ValueError) as e:
             print(f"Error loading session: {e}")
             return None
     
-    def list_sessions(self, limit: Optional[int] = None) -> List[SessionMetadata]:
+    def search_sessions(
+        self, query: str, limit: Optional[int] = None
+    ) -> List[Tuple[SessionMetadata, Optional[str]]]:
+        """Search session titles and message content.
+        
+        Args:
+            query: Text to look for, ignoring case
+            limit: Maximum number of sessions to return
+            
+        Returns:
+            Matching sessions in list_sessions order, each with an excerpt of
+            the first message containing the query (None for title-only matches)
+        """
+        needle = query.lower()
+        hits = []
+        for metadata in self.list_sessions():
+            if limit is not None and limit > 0 and len(hits) >= limit:
+                break
+            
+            snippet = None
+            session = self._read_session(metadata.session_id)
+            for message in session.messages if session else []:
+                at = message.content.lower().find(needle)
+                if at >= 0:
+                    snippet = self._snippet(message.content, at, len(query))
+                    break
+            
+            if snippet is not None or needle in (metadata.title or "").lower():
+                hits.append((metadata, snippet))
+        
+        return hits
+    
+    @staticmethod
+    def _snippet(content: str, at: int, length: int, context: int = 40) -> str:
+        """Cut the text around a match, marking elided ends."""
+        start = max(0, at - context)
+        end = min(len(content), at + length + context)
+        prefix = "…" if start > 0 else ""
+        suffix = "…" if end < len(content) else ""
+        return f"{prefix}{content[start:end].strip()}{suffix}"
+    
+    def list_sessions(self, limit: Optional[int] = None, pinned_first: bool = True) -> List[SessionMetadata]:
         """List all sessions.
         
         Args:
             limit: Maximum number of sessions to return
+            pinned_first: Put pinned sessions ahead of the others
             
         Returns:
-            List of session metadata, sorted by update time (newest first)
+            List of session metadata, pinned first (unless disabled), then by
+            update time (newest first)
         """
         try:
             sessions = [SessionMetadata.from_dict(s) for s in self.index["sessions"]]
-            sessions.sort(key=lambda s: s.updated_at, reverse=True)
+            if pinned_first:
+                sessions.sort(key=lambda s: (s.pinned, s.updated_at), reverse=True)
+            else:
+                sessions.sort(key=lambda s: s.updated_at, reverse=True)
             
             if limit is not None and limit > 0:
                 sessions = sessions[:limit]
@@ -9,6 +9,7 @@ import tempfile
 import os
 from pathlib import Path
 from openagent_terminal.bridge import TerminalBridge
+from openagent_terminal.session import MessageRole, SessionManager
 
 
 class TestTerminalBridge:
@@ -266,3 +267,94 @@ class TestBridgeIntegration:
 
 if __name__ == "__main__":
     pytest.main([__file__, "-v"])
+
+
+class _RecordingWriter:
+    """Minimal stand-in for an asyncio.StreamWriter."""
+    
+    def __init__(self):
+        self.lines = []
+    
+    def write(self, data: bytes):
+        self.lines.append(json.loads(data.decode("utf-8")))
+    
+    async def drain(self):
+        pass
+
+
+class TestAgentContinue:
+    """agent.continue resumes a cut-off answer, possibly more than once."""
+    
+    @pytest.fixture
+    def bridge(self, tmp_path):
+        bridge = TerminalBridge(str(tmp_path / "test.sock"), demo_mode=True)
+        bridge.session_manager = SessionManager(tmp_path / "sessions")
+        return bridge
+    
+    @pytest.mark.asyncio
+    async def test_continue_twice(self, bridge):
+        contexts = []
+        
+        async def fake_query(query_id, message, context=None):
+            contexts.append(context)
+            yield {"content": f"part{len(contexts)} "}
+        
+        bridge.agent_handler.query = fake_query
+        writer = _RecordingWriter()
+        
+        started = await bridge.handle_agent_query({"message": "long answer"}, 1, writer)
+        root_id = started["query_id"]
+        await bridge.active_streams[root_id]
+        
+        first = await bridge.handle_agent_continue({"query_id": root_id}, 2, writer)
+        await bridge.active_streams[first["query_id"]]
+        
+        # The client only knows the latest query ID after another cut-off
+        second = await bridge.handle_agent_continue({"query_id": first["query_id"]}, 3, writer)
+        await bridge.active_streams[second["query_id"]]
+        
+        assert contexts[1]["continues"] == root_id
+        assert contexts[1]["partial_response"] == "part1 "
+        assert contexts[2]["continues"] == root_id
+        assert contexts[2]["partial_response"] == "part1 part2 "
+        
+        assistant = [m for m in bridge.current_session.messages if m.role == MessageRole.ASSISTANT]
+        assert [m.content for m in assistant] == ["part1 ", "part2 ", "part3 "]
+        assert all(m.metadata.get("continues") == root_id for m in assistant[1:])
+    
+    @pytest.mark.asyncio
+    async def test_continue_unknown_query(self, bridge):
+        with pytest.raises(ValueError, match="Unknown query"):
+            await bridge.handle_agent_continue({"query_id": "nope"}, 1, _RecordingWriter())
+
+
+class TestSessionSearch:
+    """session.search matches titles and message content."""
+    
+    @pytest.mark.asyncio
+    async def test_handle_session_search(self, tmp_path):
+        from datetime import datetime
+        from openagent_terminal.session import Message
+        
+        bridge = TerminalBridge(str(tmp_path / "test.sock"), demo_mode=True)
+        bridge.session_manager = SessionManager(tmp_path / "sessions")
+        session = bridge.session_manager.create_session(title="Misc")
+        session.add_message(Message(
+            role=MessageRole.USER,
+            content="why does the tokenizer split emoji?",
+            timestamp=datetime.now(),
+        ))
+        bridge.session_manager.save_session(session)
+        
+        result = await bridge.handle_session_search({"query": "Tokenizer"})
+        
+        assert result["status"] == "success"
+        assert len(result["sessions"]) == 1
+        hit = result["sessions"][0]
+        assert hit["session_id"] == session.metadata.session_id
+        assert hit["snippet"] == "why does the tokenizer split emoji?"
+        
+        missing = await bridge.handle_session_search({"query": "nothing like this"})
+        assert missing["sessions"] == []
+        
+        assert (await bridge.handle_session_search({}))["status"] == "error"
@@ -464,6 +464,47 @@ class TestSessionManager:
         
         assert len(sessions) == 2
     
+    def test_search_sessions(self, temp_sessions_dir):
+        """Test searching titles and message content, ignoring case."""
+        manager = SessionManager(sessions_dir=temp_sessions_dir)
+        
+        titled = manager.create_session(title="Parser rewrite")
+        manager.save_session(titled)
+        talked = manager.create_session(title="Misc")
+        talked.add_message(Message(
+            role=MessageRole.ASSISTANT,
+            content="The PARSER drops comments after a string literal.",
+            timestamp=datetime.now()
+        ))
+        manager.save_session(talked)
+        other = manager.create_session(title="Unrelated")
+        manager.save_session(other)
+        manager.current_session = None
+        
+        hits = {meta.title: snippet for meta, snippet in manager.search_sessions("parser")}
+        
+        assert hits == {
+            "Parser rewrite": None,
+            "Misc": "The PARSER drops comments after a string literal.",
+        }
+        assert manager.current_session is None
+        assert len(manager.search_sessions("parser", limit=1)) == 1
+    
+    def test_list_sessions_by_update_time_ignores_pins(self, temp_sessions_dir):
+        """Test that pinned sessions only come first when asked to."""
+        manager = SessionManager(sessions_dir=temp_sessions_dir)
+        
+        old = manager.create_session(title="Old but pinned")
+        old.metadata.pinned = True
+        old.metadata.updated_at = datetime(2024, 1, 1)
+        manager.save_session(old)
+        new = manager.create_session(title="Latest")
+        new.metadata.updated_at = datetime(2024, 6, 1)
+        manager.save_session(new)
+        
+        assert manager.list_sessions(limit=1)[0].title == "Old but pinned"
+        assert manager.list_sessions(limit=1, pinned_first=False)[0].title == "Latest"
+    
     def test_delete_session(self, temp_sessions_dir):
         """Test deleting a session."""
         manager = SessionManager(sessions_dir=temp_sessions_dir)
@@ -30,7 +30,7 @@ cd ..
 
 # Wait for socket to be ready
 echo "⏳ Waiting for backend socket..."
-SOCKET_PATH="/run/user/1000/openagent-terminal-test.sock"
+SOCKET_PATH="/run/user/1000/openagent-terminal.sock"
 MAX_WAIT=10
 COUNT=0
 
@@ -1,19 +1,40 @@
 // ANSI Color Utilities for Terminal Output
 //
-// Provides simple syntax highlighting using ANSI escape codes.
-// This is Phase 3 - later we'll use GPU rendering with syntect.
+// Provides syntax highlighting using ANSI escape codes.
+// With the `highlight-advanced` feature, code blocks go through syntect when
+// the terminal supports truecolor; otherwise (and for languages syntect
+// doesn't know) the simple keyword highlighter below is used.
 
 use crossterm::terminal;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
-/// Get the current terminal width, clamped to reasonable bounds
-fn get_terminal_width() -> usize {
-    match terminal::size() {
-        Ok((cols, _rows)) => {
-            // Clamp between 40 (minimum) and 200 (maximum)
-            // Subtract 2 for border characters and padding
-            (cols as usize).clamp(40, 200).saturating_sub(2)
-        }
-        Err(_) => 78, // Default to 78 if terminal size detection fails
+/// Narrowest `max_content_width` honored, so borders never collapse
+const MIN_CONTENT_WIDTH: usize = 20;
+
+/// Narrowest box `boxed` draws: borders, padding and a few columns of text
+const MIN_BOX_WIDTH: usize = 8;
+
+/// Width for formatted blocks, capped at `max_content_width` when set
+///
+/// The cap only affects content blocks; the status line always uses the
+/// full terminal width.
+pub fn content_width(max_content_width: Option<usize>) -> usize {
+    effective_width(terminal::size().ok().map(|(cols, _rows)| cols), max_content_width)
+}
+
+/// Compute the content width from the terminal columns (if known) and an optional cap
+fn effective_width(cols: Option<u16>, max_content_width: Option<usize>) -> usize {
+    let width = match cols {
+        // Clamp between 40 (minimum) and 200 (maximum)
+        // Subtract 2 for border characters and padding
+        Some(cols) => (cols as usize).clamp(40, 200).saturating_sub(2),
+        None => 78, // Default to 78 if terminal size detection fails
+    };
+    match max_content_width {
+        Some(cap) => width.min(cap.max(MIN_CONTENT_WIDTH)),
+        None => width,
     }
 }
 
@@ -49,188 +70,337 @@ pub mod colors {
     pub const BG_BLUE: &str = "\x1b[44m";
 }
 
-/// Simple syntax highlighter using regex and ANSI colors
+/// Escape codes for each kind of highlighted token
+#[derive(Debug, PartialEq, Eq)]
+pub struct Palette {
+    pub keyword: &'static str,
+    pub string: &'static str,
+    pub comment: &'static str,
+    /// Booleans and null
+    pub literal: &'static str,
+    /// Added (`+`) diff lines
+    pub diff_add: &'static str,
+    /// Removed (`-`) diff lines
+    pub diff_remove: &'static str,
+}
+
+/// A highlighting theme: 24-bit colors plus a 16-color fallback
+#[derive(Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub name: &'static str,
+    /// Other names `from_name` accepts
+    pub aliases: &'static [&'static str],
+    /// Used when the terminal doesn't advertise truecolor
+    pub ansi: Palette,
+    /// `\x1b[38;2;R;G;Bm` colors, used when `COLORTERM` allows
+    pub truecolor: Palette,
+}
+
+/// Built-in highlighting themes; the first is the default
+pub const THEMES: &[Theme] = &[
+    Theme {
+        name: "monokai",
+        aliases: &[],
+        ansi: Palette { keyword: colors::MAGENTA, string: colors::GREEN, comment: colors::BRIGHT_BLACK, literal: colors::CYAN, diff_add: colors::GREEN, diff_remove: colors::RED },
+        truecolor: Palette {
+            keyword: "\x1b[38;2;249;38;114m",
+            string: "\x1b[38;2;230;219;116m",
+            comment: "\x1b[38;2;117;113;94m",
+            literal: "\x1b[38;2;174;129;255m",
+            diff_add: "\x1b[38;2;166;226;46m",
+            diff_remove: "\x1b[38;2;249;38;114m",
+        },
+    },
+    Theme {
+        name: "solarized",
+        aliases: &["solarized-dark"],
+        ansi: Palette { keyword: colors::GREEN, string: colors::CYAN, comment: colors::BRIGHT_BLACK, literal: colors::MAGENTA, diff_add: colors::GREEN, diff_remove: colors::RED },
+        truecolor: Palette {
+            keyword: "\x1b[38;2;133;153;0m",
+            string: "\x1b[38;2;42;161;152m",
+            comment: "\x1b[38;2;88;110;117m",
+            literal: "\x1b[38;2;211;54;130m",
+            diff_add: "\x1b[38;2;133;153;0m",
+            diff_remove: "\x1b[38;2;220;50;47m",
+        },
+    },
+    Theme {
+        name: "gruvbox",
+        aliases: &[],
+        ansi: Palette { keyword: colors::RED, string: colors::BRIGHT_GREEN, comment: colors::BRIGHT_BLACK, literal: colors::BRIGHT_MAGENTA, diff_add: colors::GREEN, diff_remove: colors::RED },
+        truecolor: Palette {
+            keyword: "\x1b[38;2;251;73;52m",
+            string: "\x1b[38;2;184;187;38m",
+            comment: "\x1b[38;2;146;131;116m",
+            literal: "\x1b[38;2;211;134;155m",
+            diff_add: "\x1b[38;2;184;187;38m",
+            diff_remove: "\x1b[38;2;251;73;52m",
+        },
+    },
+    Theme {
+        name: "dracula",
+        aliases: &[],
+        ansi: Palette { keyword: colors::BRIGHT_MAGENTA, string: colors::BRIGHT_YELLOW, comment: colors::BRIGHT_BLUE, literal: colors::BRIGHT_CYAN, diff_add: colors::GREEN, diff_remove: colors::RED },
+        truecolor: Palette {
+            keyword: "\x1b[38;2;255;121;198m",
+            string: "\x1b[38;2;241;250;140m",
+            comment: "\x1b[38;2;98;114;164m",
+            literal: "\x1b[38;2;189;147;249m",
+            diff_add: "\x1b[38;2;80;250;123m",
+            diff_remove: "\x1b[38;2;255;85;85m",
+        },
+    },
+    // No syntax colors, but diffs keep their meaning
+    Theme {
+        name: "plain",
+        aliases: &[],
+        ansi: Palette { keyword: "", string: "", comment: "", literal: "", diff_add: colors::GREEN, diff_remove: colors::RED },
+        truecolor: Palette { keyword: "", string: "", comment: "", literal: "", diff_add: colors::GREEN, diff_remove: colors::RED },
+    },
+];
+
+impl Theme {
+    /// Look up a built-in theme by name or alias (case-insensitive)
+    pub fn from_name(name: &str) -> Option<&'static Theme> {
+        THEMES.iter().find(|t| {
+            t.name.eq_ignore_ascii_case(name) || t.aliases.iter().any(|a| a.eq_ignore_ascii_case(name))
+        })
+    }
+
+    /// Palette for the current terminal: truecolor if supported, else the 16 basic colors
+    pub fn palette(&self) -> &Palette {
+        if truecolor_enabled() { &self.truecolor } else { &self.ansi }
+    }
+}
+
+/// Index into `THEMES` of the theme used for code blocks
+static ACTIVE_THEME: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether themes render with 24-bit colors (set once at startup)
+static TRUECOLOR: AtomicBool = AtomicBool::new(false);
+
+/// Whether a `COLORTERM` value advertises 24-bit color support
+pub fn detect_truecolor(colorterm: Option<&str>) -> bool {
+    colorterm.is_some_and(|v| v.eq_ignore_ascii_case("truecolor") || v.eq_ignore_ascii_case("24bit"))
+}
+
+/// Render themes with 24-bit colors (true) or the 16-color fallback (false)
+pub fn set_truecolor(enabled: bool) {
+    TRUECOLOR.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether themes currently render with 24-bit colors
+pub fn truecolor_enabled() -> bool {
+    TRUECOLOR.load(Ordering::Relaxed)
+}
+
+/// Switch the theme for code blocks rendered from now on; false if `name` is unknown
+pub fn set_theme(name: &str) -> bool {
+    let Some(theme) = Theme::from_name(name) else {
+        return false;
+    };
+    let index = THEMES.iter().position(|t| t == theme).unwrap_or(0);
+    ACTIVE_THEME.store(index, Ordering::Relaxed);
+    true
+}
+
+/// Theme currently used for code blocks
+pub fn active_theme() -> &'static Theme {
+    &THEMES[ACTIVE_THEME.load(Ordering::Relaxed)]
+}
+
+/// What the line highlighter needs to know about a language
+struct LineSyntax {
+    keywords: &'static [&'static str],
+    /// Characters that open (and close) a string
+    quotes: &'static [char],
+    /// Marker that starts a comment running to the end of the line
+    comment: &'static str,
+    /// The marker only counts at the start of a word (so bash's `$#` isn't a comment)
+    comment_at_word_start: bool,
+}
+
+const RUST: LineSyntax = LineSyntax {
+    keywords: &["fn", "let", "mut", "impl", "struct", "enum", "pub", "use",
+                "async", "await", "match", "if", "else", "for", "while", "return"],
+    // Single quotes are chars and lifetimes, not strings
+    quotes: &['"'],
+    comment: "//",
+    comment_at_word_start: false,
+};
+
+const PYTHON: LineSyntax = LineSyntax {
+    keywords: &["def", "class", "import", "from", "return", "if", "else",
+                "elif", "for", "while", "async", "await", "with", "as"],
+    quotes: &['"', '\''],
+    comment: "#",
+    comment_at_word_start: false,
+};
+
+const JAVASCRIPT: LineSyntax = LineSyntax {
+    keywords: &["function", "const", "let", "var", "return", "if", "else",
+                "for", "while", "async", "await", "class", "import", "export"],
+    quotes: &['"', '\'', '`'],
+    comment: "//",
+    comment_at_word_start: false,
+};
+
+const BASH: LineSyntax = LineSyntax {
+    keywords: &["if", "then", "else", "fi", "for", "do", "done", "while",
+                "case", "esac", "function"],
+    quotes: &['"', '\''],
+    comment: "#",
+    comment_at_word_start: true,
+};
+
+/// Append `text` in `color`, reset afterwards (unstyled if `color` is empty)
+fn paint(out: &mut String, color: &str, text: &str) {
+    if color.is_empty() {
+        out.push_str(text);
+    } else {
+        out.push_str(color);
+        out.push_str(text);
+        out.push_str(colors::RESET);
+    }
+}
+
+/// Simple syntax highlighter using ANSI colors
 pub struct SyntaxHighlighter;
 
 impl SyntaxHighlighter {
-    /// Highlight code based on language
-    pub fn highlight(code: &str, language: &str) -> String {
+    /// Highlight code based on language, preferring syntect when it's built in
+    pub fn highlight(code: &str, language: &str, theme: &Theme) -> String {
+        #[cfg(feature = "highlight-advanced")]
+        if truecolor_enabled() {
+            if let Some(highlighted) = Self::highlight_with_syntect(code, language, theme.name) {
+                return highlighted;
+            }
+        }
+
+        let colors = theme.palette();
         match language.to_lowercase().as_str() {
-            "rust" => Self::highlight_rust(code),
-            "python" => Self::highlight_python(code),
-            "javascript" | "typescript" | "js" | "ts" => Self::highlight_javascript(code),
-            "bash" | "sh" => Self::highlight_bash(code),
-            "json" => Self::highlight_json(code),
+            "rust" => Self::highlight_lines(code, &RUST, colors),
+            "python" => Self::highlight_lines(code, &PYTHON, colors),
+            "javascript" | "typescript" | "js" | "ts" => Self::highlight_lines(code, &JAVASCRIPT, colors),
+            "bash" | "sh" => Self::highlight_lines(code, &BASH, colors),
+            "json" => Self::highlight_json(code, colors),
             _ => code.to_string(), // No highlighting for unknown languages
         }
     }
     
-    fn highlight_rust(code: &str) -> String {
+    /// Highlight with syntect's bundled grammars and themes as 24-bit escapes
+    ///
+    /// `theme_name` is one of ours (mapped to the closest bundled theme) or a
+    /// syntect theme name. `None` for unknown languages or themes, and for
+    /// `plain`, so the caller can fall back.
+    #[cfg(feature = "highlight-advanced")]
+    pub fn highlight_with_syntect(code: &str, language: &str, theme_name: &str) -> Option<String> {
+        use std::sync::OnceLock;
+        use syntect::easy::HighlightLines;
+        use syntect::highlighting::ThemeSet;
+        use syntect::parsing::SyntaxSet;
+        use syntect::util::{as_24_bit_terminal_escaped, LinesWithEndings};
+
+        // Loading the bundled sets takes a while; do it once
+        static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+        static THEME_SET: OnceLock<ThemeSet> = OnceLock::new();
+        let syntaxes = SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines);
+        let themes = THEME_SET.get_or_init(ThemeSet::load_defaults);
+
+        let bundled = match theme_name.to_lowercase().as_str() {
+            "plain" => return None,
+            "monokai" => "base16-mocha.dark",
+            "solarized" | "solarized-dark" => "Solarized (dark)",
+            "gruvbox" => "base16-eighties.dark",
+            "dracula" => "base16-ocean.dark",
+            _ => theme_name,
+        };
+        let theme = themes.themes.get(bundled)?;
+        let syntax = syntaxes.find_syntax_by_token(language)?;
+
+        let mut highlighter = HighlightLines::new(syntax, theme);
         let mut result = String::new();
-        
-        for line in code.lines() {
-            let mut highlighted_line = line.to_string();
-            
-            // Keywords
-            for keyword in &["fn", "let", "mut", "impl", "struct", "enum", "pub", "use", 
-                            "async", "await", "match", "if", "else", "for", "while", "return"] {
-                highlighted_line = highlighted_line.replace(
-                    &format!(" {} ", keyword),
-                    &format!(" {}{}{} ", colors::MAGENTA, keyword, colors::RESET)
-                );
-                // Handle keyword at start of line
-                if highlighted_line.starts_with(keyword) {
-                    highlighted_line = format!("{}{}{}", colors::MAGENTA, keyword, 
-                                              &highlighted_line[keyword.len()..]);
-                }
-            }
-            
-            // String literals
-            if highlighted_line.contains('"') {
-                highlighted_line = Self::highlight_strings(&highlighted_line);
-            }
-            
-            // Comments
-            if highlighted_line.contains("//") {
-                if let Some(pos) = highlighted_line.find("//") {
-                    let (code_part, comment_part) = highlighted_line.split_at(pos);
-                    highlighted_line = format!("{}{}{}{}", 
-                                              code_part, 
-                                              colors::BRIGHT_BLACK, 
-                                              comment_part, 
-                                              colors::RESET);
-                }
-            }
-            
-            result.push_str(&highlighted_line);
+        for line in LinesWithEndings::from(code) {
+            let ranges = highlighter.highlight_line(line, syntaxes).ok()?;
+            // Same shape as the simple highlighter: each line reset and newline-terminated
+            result.push_str(as_24_bit_terminal_escaped(&ranges, false).trim_end_matches('\n'));
+            result.push_str(colors::RESET);
             result.push('\n');
         }
-        
-        result
+        Some(result)
     }
-    
-    fn highlight_python(code: &str) -> String {
+
+    /// Highlight line by line: keywords in code, quoted strings, then a trailing comment
+    ///
+    /// Each line is scanned once, so comment markers inside strings stay part
+    /// of the string and every colored span is reset on its own.
+    fn highlight_lines(code: &str, syntax: &LineSyntax, colors: &Palette) -> String {
         let mut result = String::new();
-        
         for line in code.lines() {
-            let mut highlighted_line = line.to_string();
-            
-            // Keywords
-            for keyword in &["def", "class", "import", "from", "return", "if", "else", 
-                            "elif", "for", "while", "async", "await", "with", "as"] {
-                highlighted_line = highlighted_line.replace(
-                    &format!(" {} ", keyword),
-                    &format!(" {}{}{} ", colors::MAGENTA, keyword, colors::RESET)
-                );
-                if highlighted_line.starts_with(keyword) {
-                    highlighted_line = format!("{}{}{}", colors::MAGENTA, keyword, 
-                                              &highlighted_line[keyword.len()..]);
-                }
-            }
-            
-            // String literals
-            if highlighted_line.contains('"') || highlighted_line.contains('\'') {
-                highlighted_line = Self::highlight_strings(&highlighted_line);
-            }
-            
-            // Comments
-            if highlighted_line.contains('#') {
-                if let Some(pos) = highlighted_line.find('#') {
-                    let (code_part, comment_part) = highlighted_line.split_at(pos);
-                    highlighted_line = format!("{}{}{}{}", 
-                                              code_part, 
-                                              colors::BRIGHT_BLACK, 
-                                              comment_part, 
-                                              colors::RESET);
-                }
-            }
-            
-            result.push_str(&highlighted_line);
+            Self::highlight_line(line, syntax, colors, &mut result);
             result.push('\n');
         }
-        
         result
     }
-    
-    fn highlight_javascript(code: &str) -> String {
-        let mut result = String::new();
-        
-        for line in code.lines() {
-            let mut highlighted_line = line.to_string();
-            
-            // Keywords
-            for keyword in &["function", "const", "let", "var", "return", "if", "else", 
-                            "for", "while", "async", "await", "class", "import", "export"] {
-                highlighted_line = highlighted_line.replace(
-                    &format!(" {} ", keyword),
-                    &format!(" {}{}{} ", colors::MAGENTA, keyword, colors::RESET)
-                );
-            }
-            
-            // String literals
-            if highlighted_line.contains('"') || highlighted_line.contains('\'') {
-                highlighted_line = Self::highlight_strings(&highlighted_line);
-            }
-            
-            // Comments
-            if highlighted_line.contains("//") {
-                if let Some(pos) = highlighted_line.find("//") {
-                    let (code_part, comment_part) = highlighted_line.split_at(pos);
-                    highlighted_line = format!("{}{}{}{}", 
-                                              code_part, 
-                                              colors::BRIGHT_BLACK, 
-                                              comment_part, 
-                                              colors::RESET);
+
+    fn highlight_line(line: &str, syntax: &LineSyntax, colors: &Palette, out: &mut String) {
+        let mut word_start = None;
+        let mut string_start = None;
+        let mut quote = '"';
+        let mut escaped = false;
+
+        for (i, ch) in line.char_indices() {
+            if let Some(start) = string_start {
+                if escaped {
+                    escaped = false;
+                } else if ch == '\\' {
+                    escaped = true;
+                } else if ch == quote {
+                    paint(out, colors.string, &line[start..i + ch.len_utf8()]);
+                    string_start = None;
                 }
+                continue;
             }
-            
-            result.push_str(&highlighted_line);
-            result.push('\n');
-        }
-        
-        result
-    }
-    
-    fn highlight_bash(code: &str) -> String {
-        let mut result = String::new();
-        
-        for line in code.lines() {
-            let mut highlighted_line = line.to_string();
-            
-            // Keywords
-            for keyword in &["if", "then", "else", "fi", "for", "do", "done", "while", 
-                            "case", "esac", "function"] {
-                highlighted_line = highlighted_line.replace(
-                    &format!(" {} ", keyword),
-                    &format!(" {}{}{} ", colors::MAGENTA, keyword, colors::RESET)
-                );
+
+            if ch.is_alphanumeric() || ch == '_' {
+                word_start.get_or_insert(i);
+                continue;
             }
-            
-            // String literals
-            if highlighted_line.contains('"') || highlighted_line.contains('\'') {
-                highlighted_line = Self::highlight_strings(&highlighted_line);
+            if let Some(start) = word_start.take() {
+                Self::push_word(&line[start..i], syntax, colors, out);
             }
-            
-            // Comments
-            if highlighted_line.contains('#') {
-                if let Some(pos) = highlighted_line.find('#') {
-                    let (code_part, comment_part) = highlighted_line.split_at(pos);
-                    highlighted_line = format!("{}{}{}{}", 
-                                              code_part, 
-                                              colors::BRIGHT_BLACK, 
-                                              comment_part, 
-                                              colors::RESET);
-                }
+
+            let at_word_start = !syntax.comment_at_word_start || line[..i].chars().last().is_none_or(char::is_whitespace);
+            if at_word_start && line[i..].starts_with(syntax.comment) {
+                paint(out, colors.comment, &line[i..]);
+                return;
             }
-            
-            result.push_str(&highlighted_line);
-            result.push('\n');
+            if syntax.quotes.contains(&ch) {
+                string_start = Some(i);
+                quote = ch;
+                continue;
+            }
+            out.push(ch);
+        }
+
+        // An unterminated string runs to the end of the line
+        if let Some(start) = string_start {
+            paint(out, colors.string, &line[start..]);
+        }
+        if let Some(start) = word_start {
+            Self::push_word(&line[start..], syntax, colors, out);
+        }
+    }
+
+    fn push_word(word: &str, syntax: &LineSyntax, colors: &Palette, out: &mut String) {
+        if syntax.keywords.contains(&word) {
+            paint(out, colors.keyword, word);
+        } else {
+            out.push_str(word);
         }
-        
-        result
     }
     
-    fn highlight_json(code: &str) -> String {
+    fn highlight_json(code: &str, colors: &Palette) -> String {
         let mut result = String::new();
         
         for line in code.lines() {
@@ -240,15 +410,15 @@ impl SyntaxHighlighter {
             // Numbers
             // Booleans
             highlighted_line = highlighted_line.replace("true", 
-                &format!("{}true{}", colors::CYAN, colors::RESET));
+                &format!("{}true{}", colors.literal, colors::RESET));
             highlighted_line = highlighted_line.replace("false", 
-                &format!("{}false{}", colors::CYAN, colors::RESET));
+                &format!("{}false{}", colors.literal, colors::RESET));
             highlighted_line = highlighted_line.replace("null", 
-                &format!("{}null{}", colors::CYAN, colors::RESET));
+                &format!("{}null{}", colors.literal, colors::RESET));
             
             // String literals
             if highlighted_line.contains('"') {
-                highlighted_line = Self::highlight_strings(&highlighted_line);
+                highlighted_line = Self::highlight_strings(&highlighted_line, colors);
             }
             
             result.push_str(&highlighted_line);
@@ -258,16 +428,15 @@ impl SyntaxHighlighter {
         result
     }
     
-    fn highlight_strings(line: &str) -> String {
+    fn highlight_strings(line: &str, colors: &Palette) -> String {
         // Simple string highlighting - just color the whole string
         let mut result = String::new();
         let mut in_string = false;
-        let mut chars = line.chars().peekable();
         
-        while let Some(ch) = chars.next() {
+        for ch in line.chars() {
             if ch == '"' {
                 if !in_string {
-                    result.push_str(colors::GREEN);
+                    result.push_str(colors.string);
                     result.push(ch);
                     in_string = true;
                 } else {
@@ -288,10 +457,9 @@ impl SyntaxHighlighter {
     }
 }
 
-/// Format a code block with header and highlighting
-pub fn format_code_block(language: &str, code: &str) -> String {
-    let highlighted = SyntaxHighlighter::highlight(code, language);
-    let width = get_terminal_width();
+/// Format a code block with header and highlighting, `width` columns wide
+pub fn format_code_block(language: &str, code: &str, width: usize, theme: &Theme) -> String {
+    let highlighted = SyntaxHighlighter::highlight(code, language, theme);
     
     // Calculate header: "┌─ language ─" + remaining dashes
     let header_prefix = format!("┌─ {} ─", language);
@@ -320,10 +488,10 @@ pub fn format_code_block(language: &str, code: &str) -> String {
     )
 }
 
-/// Highlight diff content
-pub fn format_diff(content: &str) -> String {
+/// Highlight diff content, with borders `width` columns wide
+pub fn format_diff(content: &str, width: usize, theme: &Theme) -> String {
+    let palette = theme.palette();
     let mut result = String::new();
-    let width = get_terminal_width();
     
     // Calculate header: "┌─ Diff ─" + remaining dashes
     let header_prefix = "┌─ Diff ─";
@@ -341,9 +509,9 @@ pub fn format_diff(content: &str) -> String {
     
     for line in content.lines() {
         if line.starts_with('+') {
-            result.push_str(&format!("{}{}{}\n", colors::GREEN, line, colors::RESET));
+            result.push_str(&format!("{}{}{}\n", palette.diff_add, line, colors::RESET));
         } else if line.starts_with('-') {
-            result.push_str(&format!("{}{}{}\n", colors::RED, line, colors::RESET));
+            result.push_str(&format!("{}{}{}\n", palette.diff_remove, line, colors::RESET));
         } else {
             result.push_str(line);
             result.push('\n');
@@ -361,23 +529,330 @@ pub fn format_diff(content: &str) -> String {
     result
 }
 
+/// Truncate `s` to at most `max_cols` display columns, appending an ellipsis
+///
+/// Cuts only on grapheme boundaries, so emoji and combining sequences are never
+/// split. ANSI CSI sequences (e.g. colors) count as zero width and are kept.
+pub fn truncate_display(s: &str, max_cols: usize) -> String {
+    const ELLIPSIS: &str = "…";
+
+    if display_width(s) <= max_cols {
+        return s.to_string();
+    }
+    if max_cols == 0 {
+        return String::new();
+    }
+
+    let budget = max_cols - 1; // Leave room for the ellipsis
+    let mut result = String::new();
+    let mut used = 0;
+    let mut rest = s;
+    let mut saw_escape = false;
+
+    while !rest.is_empty() {
+        if let Some(len) = csi_len(rest) {
+            result.push_str(&rest[..len]);
+            rest = &rest[len..];
+            saw_escape = true;
+            continue;
+        }
+        let grapheme = rest.graphemes(true).next().unwrap_or(rest);
+        let width = grapheme.width();
+        if used + width > budget {
+            break;
+        }
+        result.push_str(grapheme);
+        used += width;
+        rest = &rest[grapheme.len()..];
+    }
+
+    result.push_str(ELLIPSIS);
+    if saw_escape {
+        result.push_str(colors::RESET);
+    }
+    result
+}
+
+/// Hard-wrap `s` so no line is wider than `max_cols` display columns
+///
+/// Existing newlines are kept. Breaks fall on grapheme boundaries and ANSI CSI
+/// sequences stay where they were, so colors carry across the break.
+pub fn wrap_display(s: &str, max_cols: usize) -> Vec<String> {
+    let max_cols = max_cols.max(1);
+    let mut lines = Vec::new();
+
+    for source in s.split('\n') {
+        let mut line = String::new();
+        let mut used = 0;
+        let mut rest = source;
+        while !rest.is_empty() {
+            if let Some(len) = csi_len(rest) {
+                line.push_str(&rest[..len]);
+                rest = &rest[len..];
+                continue;
+            }
+            let grapheme = rest.graphemes(true).next().unwrap_or(rest);
+            let width = grapheme.width();
+            if used + width > max_cols && used > 0 {
+                lines.push(std::mem::take(&mut line));
+                used = 0;
+            }
+            line.push_str(grapheme);
+            used += width;
+            rest = &rest[grapheme.len()..];
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Draw a double-line box `width` columns wide around `title` and `body`
+///
+/// The title is centered (and truncated if it doesn't fit); body lines are
+/// left-aligned and wrapped. With no body lines the box is just the title.
+/// Lines are joined with `\n` and have no trailing newline.
+pub fn boxed(title: &str, body: &[&str], width: usize) -> String {
+    let inner = width.max(MIN_BOX_WIDTH) - 2;
+    let horizontal = "═".repeat(inner);
+    let mut lines = vec![format!("╔{}╗", horizontal)];
+
+    let title = truncate_display(title, inner);
+    let spare = inner - display_width(&title);
+    lines.push(format!("║{}{}{}║", " ".repeat(spare / 2), title, " ".repeat(spare - spare / 2)));
+
+    if !body.is_empty() {
+        lines.push(format!("╟{}╢", "─".repeat(inner)));
+        for line in body.iter().flat_map(|line| wrap_display(line, inner - 2)) {
+            let pad = inner - 2 - display_width(&line);
+            lines.push(format!("║ {}{} ║", line, " ".repeat(pad)));
+        }
+    }
+
+    lines.push(format!("╚{}╝", horizontal));
+    lines.join("\n")
+}
+
+/// Display width of `s`, ignoring ANSI CSI sequences
+pub fn display_width(s: &str) -> usize {
+    let mut width = 0;
+    let mut rest = s;
+    while !rest.is_empty() {
+        if let Some(len) = csi_len(rest) {
+            rest = &rest[len..];
+            continue;
+        }
+        let grapheme = rest.graphemes(true).next().unwrap_or(rest);
+        width += grapheme.width();
+        rest = &rest[grapheme.len()..];
+    }
+    width
+}
+
+/// Remove ANSI CSI sequences (colors and other SGR codes), keeping the text
+pub fn strip_ansi(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(pos) = rest.find('\x1b') {
+        result.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        match csi_len(rest) {
+            Some(len) => rest = &rest[len..],
+            None => {
+                // A lone ESC is kept so nothing but escape sequences is lost
+                result.push('\x1b');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Length in bytes of a CSI escape sequence at the start of `s`, if any
+fn csi_len(s: &str) -> Option<usize> {
+    let body = s.strip_prefix("\x1b[")?;
+    let end = body.bytes().position(|b| (0x40..=0x7e).contains(&b))?;
+    Some(2 + end + 1)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn test_theme_from_name() {
+        assert_eq!(THEMES[0].name, "monokai");
+        assert_eq!(Theme::from_name("gruvbox").map(|t| t.name), Some("gruvbox"));
+        assert_eq!(Theme::from_name("Solarized").map(|t| t.name), Some("solarized"));
+        assert_eq!(Theme::from_name("solarized-dark").map(|t| t.name), Some("solarized"));
+        assert!(Theme::from_name("no-such-theme").is_none());
+        assert!(Theme::from_name("").is_none());
+        // Unknown names are rejected without touching the active theme
+        let before = active_theme().name;
+        assert!(!set_theme("no-such-theme"));
+        assert_eq!(active_theme().name, before);
+    }
+
+    #[test]
+    fn test_truecolor_palettes() {
+        assert!(detect_truecolor(Some("truecolor")));
+        assert!(detect_truecolor(Some("24bit")));
+        assert!(!detect_truecolor(Some("")));
+        assert!(!detect_truecolor(Some("yes")));
+        assert!(!detect_truecolor(None));
+
+        for theme in THEMES.iter().filter(|t| t.name != "plain") {
+            let p = &theme.truecolor;
+            for code in [p.keyword, p.string, p.comment, p.literal, p.diff_add, p.diff_remove] {
+                let rgb: Vec<u8> = code.strip_prefix("\x1b[38;2;")
+                    .and_then(|rest| rest.strip_suffix('m'))
+                    .map(|rgb| rgb.split(';').map(|n| n.parse().unwrap()).collect())
+                    .unwrap_or_default();
+                assert_eq!(rgb.len(), 3, "{}: {:?}", theme.name, code);
+            }
+            // The fallback only uses the basic 16 colors
+            assert!(!theme.ansi.keyword.contains("38;2"), "{}", theme.name);
+        }
+    }
+
+    #[test]
+    fn test_boxed_border_lengths() {
+        for width in [20, 40, 78, 120] {
+            let text = boxed("Help", &["first line", "second"], width);
+            let lines: Vec<&str> = text.lines().collect();
+            assert_eq!(lines.len(), 6);
+            for line in &lines {
+                assert_eq!(display_width(line), width, "width {}: {:?}", width, line);
+            }
+            assert_eq!(lines[0], format!("╔{}╗", "═".repeat(width - 2)));
+            assert_eq!(lines[5], format!("╚{}╝", "═".repeat(width - 2)));
+            assert!(lines[1].trim_matches(|c| c == '║' || c == ' ') == "Help");
+            assert!(lines[3].starts_with("║ first line "));
+        }
+    }
+
+    #[test]
+    fn test_boxed_narrow_and_title_only() {
+        // Title only: top, title, bottom
+        let text = boxed("Session History", &[], 30);
+        assert_eq!(text.lines().count(), 3);
+        assert!(text.lines().all(|l| display_width(l) == 30));
+
+        // Long titles are truncated and long body lines wrap
+        let text = boxed("A rather long title here", &["abcdefghijkl"], 10);
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines.iter().all(|l| display_width(l) == 10), "{:?}", lines);
+        assert!(lines[1].contains('…'));
+        assert_eq!(lines[3], "║ abcdef ║");
+        assert_eq!(lines[4], "║ ghijkl ║");
+
+        // Widths below the minimum are raised to it
+        assert!(boxed("x", &[], 2).lines().all(|l| display_width(l) == MIN_BOX_WIDTH));
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(strip_ansi("plain text"), "plain text");
+        assert_eq!(strip_ansi(""), "");
+        let colored = format!("{}fn{} main() {}{{}}{}", colors::BLUE, colors::RESET, colors::BOLD, colors::RESET);
+        assert_eq!(strip_ansi(&colored), "fn main() {}");
+        assert_eq!(strip_ansi("\x1b[38;5;208m🤖 ok\x1b[0m\n"), "🤖 ok\n");
+
+        // Rendered blocks come back as their source text plus the box drawing
+        let block = format_code_block("rust", "let x = 1;", 40, &THEMES[0]);
+        let stripped = strip_ansi(&block);
+        assert!(!stripped.contains('\x1b'));
+        assert!(stripped.contains("let x = 1;"));
+        assert_eq!(display_width(&block), display_width(&stripped));
+    }
+
+    #[test]
+    fn test_wrap_display() {
+        assert_eq!(wrap_display("abcdef", 4), ["abcd", "ef"]);
+        assert_eq!(wrap_display("ab\ncd", 4), ["ab", "cd"]);
+        assert_eq!(wrap_display("", 4), [""]);
+        // Wide characters move to the next line instead of being split
+        assert_eq!(wrap_display("abc🤖d", 4), ["abc", "🤖d"]);
+
+        // Escapes take no columns and are kept
+        let colored = format!("{}abcdef{}", colors::GREEN, colors::RESET);
+        let lines = wrap_display(&colored, 3);
+        assert_eq!(lines, [format!("{}abc", colors::GREEN), format!("def{}", colors::RESET)]);
+    }
+
+    #[test]
+    fn test_truncate_display_emoji() {
+        let status = "● Connected  │  🤖 gpt-4  │  📝 abcd1234";
+        // Cutting at 17 columns would land inside the 🤖 bytes with byte slicing
+        let truncated = truncate_display(status, 17);
+        assert!(display_width(&truncated) <= 17);
+        assert!(truncated.ends_with('…'));
+        assert!(truncated.starts_with("● Connected  │"));
+
+        // A wide emoji that doesn't fit is dropped, not split
+        assert_eq!(truncate_display("ab🤖cd", 4), "ab…");
+        assert_eq!(truncate_display("ab🤖cd", 5), "ab🤖…");
+    }
+
+    #[test]
+    fn test_truncate_display_short_input_unchanged() {
+        assert_eq!(truncate_display("hello", 5), "hello");
+        assert_eq!(truncate_display("hello", 0), "");
+    }
+
+    #[test]
+    fn test_truncate_display_keeps_ansi() {
+        let colored = format!("{}hello world{}", colors::GREEN, colors::RESET);
+        assert_eq!(display_width(&colored), 11);
+        let truncated = truncate_display(&colored, 6);
+        assert_eq!(truncated, format!("{}hello…{}", colors::GREEN, colors::RESET));
+    }
     
     #[test]
     fn test_highlight_rust() {
         let code = "fn main() { println!(\"Hello\"); }";
-        let highlighted = SyntaxHighlighter::highlight(code, "rust");
+        let highlighted = SyntaxHighlighter::highlight(code, "rust", &THEMES[0]);
         assert!(highlighted.contains("fn"));
         // Should contain ANSI escape codes for coloring
         assert!(highlighted.contains("\x1b["));
     }
     
+    #[test]
+    fn test_comment_marker_inside_string() {
+        let c = THEMES[0].palette();
+        let r = colors::RESET;
+        let line = "let url = \"http://x\"; // real comment";
+        let highlighted = SyntaxHighlighter::highlight(line, "rust", &THEMES[0]);
+        assert_eq!(highlighted, format!(
+            "{}let{r} url = {}\"http://x\"{r}; {}// real comment{r}\n",
+            c.keyword, c.string, c.comment,
+        ));
+        assert_eq!(strip_ansi(&highlighted), format!("{}\n", line));
+
+        // Quotes inside a comment don't start a string
+        let highlighted = SyntaxHighlighter::highlight("x(); // say \"hi\"", "js", &THEMES[0]);
+        assert!(highlighted.ends_with(&format!("{}// say \"hi\"{r}\n", c.comment)));
+
+        // Escaped quotes and keywords inside strings stay in the string
+        let highlighted = SyntaxHighlighter::highlight("s = 'if \\' # not' # note", "python", &THEMES[0]);
+        assert_eq!(highlighted, format!("s = {}'if \\' # not'{r} {}# note{r}\n", c.string, c.comment));
+
+        // `$#` is a variable in bash, not a comment
+        let highlighted = SyntaxHighlighter::highlight("echo $# # count", "bash", &THEMES[0]);
+        assert_eq!(highlighted, format!("echo $# {}# count{r}\n", c.comment));
+    }
+
+    #[test]
+    fn test_plain_theme_adds_no_escapes() {
+        let plain = Theme::from_name("plain").unwrap();
+        let code = "fn main() { let s = \"x\"; } // done";
+        assert_eq!(SyntaxHighlighter::highlight(code, "rust", plain), format!("{}\n", code));
+    }
+
     #[test]
     fn test_highlight_python() {
         let code = "def hello():\n    print('world')";
-        let highlighted = SyntaxHighlighter::highlight(code, "python");
+        let highlighted = SyntaxHighlighter::highlight(code, "python", &THEMES[0]);
         assert!(highlighted.contains("def"));
         assert!(highlighted.contains("\x1b["));
     }
@@ -385,21 +860,34 @@ mod tests {
     #[test]
     fn test_highlight_javascript() {
         let code = "function test() { return true; }";
-        let highlighted = SyntaxHighlighter::highlight(code, "javascript");
+        let highlighted = SyntaxHighlighter::highlight(code, "javascript", &THEMES[0]);
         assert!(highlighted.contains("function"));
     }
     
     #[test]
     fn test_highlight_bash() {
         let code = "if [ -f file ]; then echo 'exists'; fi";
-        let highlighted = SyntaxHighlighter::highlight(code, "bash");
+        let highlighted = SyntaxHighlighter::highlight(code, "bash", &THEMES[0]);
         assert!(highlighted.contains("if"));
     }
     
+    #[cfg(feature = "highlight-advanced")]
+    #[test]
+    fn test_highlight_with_syntect() {
+        let code = "fn main() {\n    let s = \"fn\";\n}";
+        let highlighted = SyntaxHighlighter::highlight_with_syntect(code, "rust", "monokai").unwrap();
+        assert!(highlighted.contains("\x1b[38;2;"));
+        assert_eq!(strip_ansi(&highlighted), format!("{}\n", code));
+        assert_eq!(highlighted.lines().count(), 3);
+
+        assert!(SyntaxHighlighter::highlight_with_syntect(code, "rust", "plain").is_none());
+        assert!(SyntaxHighlighter::highlight_with_syntect(code, "no-such-language", "monokai").is_none());
+    }
+
     #[test]
     fn test_unknown_language() {
         let code = "some code";
-        let highlighted = SyntaxHighlighter::highlight(code, "unknown");
+        let highlighted = SyntaxHighlighter::highlight(code, "unknown", &THEMES[0]);
         // Should return unchanged for unknown language
         assert_eq!(highlighted, code);
     }
@@ -407,7 +895,7 @@ mod tests {
     #[test]
     fn test_format_code_block() {
         let code = "fn test() {}";
-        let formatted = format_code_block("rust", code);
+        let formatted = format_code_block("rust", code, 78, &THEMES[0]);
         // Should have border characters
         assert!(formatted.contains("┌"));
         assert!(formatted.contains("└"));
@@ -417,13 +905,37 @@ mod tests {
     #[test]
     fn test_format_diff() {
         let diff = "+added line\n-removed line\n unchanged";
-        let formatted = format_diff(diff);
+        let formatted = format_diff(diff, 78, &THEMES[0]);
         // Should contain diff markers
         assert!(formatted.contains("Diff"));
         assert!(formatted.contains("+added"));
         assert!(formatted.contains("-removed"));
     }
     
+    #[test]
+    fn test_effective_width() {
+        // Terminal size alone: clamped to 40..=200, minus padding
+        assert_eq!(effective_width(Some(120), None), 118);
+        assert_eq!(effective_width(Some(20), None), 38);
+        assert_eq!(effective_width(Some(400), None), 198);
+        assert_eq!(effective_width(None, None), 78);
+        
+        // The cap only ever narrows
+        assert_eq!(effective_width(Some(300), Some(100)), 100);
+        assert_eq!(effective_width(Some(80), Some(100)), 78);
+        assert_eq!(effective_width(None, Some(60)), 60);
+        
+        // Absurdly small caps are raised to a usable minimum
+        assert_eq!(effective_width(Some(120), Some(0)), MIN_CONTENT_WIDTH);
+    }
+    
+    #[test]
+    fn test_code_block_border_matches_width() {
+        let formatted = format_code_block("rust", "fn main() {}", 50, &THEMES[0]);
+        let footer = formatted.lines().last().unwrap();
+        assert_eq!(footer.matches('─').count(), 49);
+    }
+    
     #[test]
     fn test_ansi_colors() {
         // Test that color constants are defined
@@ -5,18 +5,24 @@
 use clap::{Parser, ValueEnum};
 use std::path::PathBuf;
 
+/// Socket file name the backend listens on by default
+pub const DEFAULT_SOCKET_NAME: &str = "openagent-terminal.sock";
+
+/// Socket file name used with `--test` (matches the backend's `--test`)
+pub const TEST_SOCKET_NAME: &str = "openagent-terminal-test.sock";
+
 /// OpenAgent-Terminal: AI-Native Terminal Emulator
 ///
 /// An intelligent terminal that combines traditional shell interaction with
 /// AI-powered assistance for enhanced productivity.
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Default)]
 #[command(name = "openagent-terminal")]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
     /// Path to Unix socket for IPC with Python backend
     ///
     /// Overrides OPENAGENT_SOCKET environment variable and default path.
-    /// Default: $XDG_RUNTIME_DIR/openagent-terminal-test.sock
+    /// Default: $XDG_RUNTIME_DIR/openagent-terminal.sock (openagent-terminal-test.sock with --test)
     #[arg(short, long, value_name = "PATH")]
     pub socket: Option<PathBuf>,
 
@@ -40,6 +46,12 @@ pub struct Cli {
     #[arg(long)]
     pub generate_config: bool,
 
+    /// Build sample protocol messages, check they round-trip, print them and exit
+    ///
+    /// Needs no backend; catches serialization regressions (e.g. in CI).
+    #[arg(long)]
+    pub dry_connect: bool,
+
     /// AI model to use for queries
     ///
     /// Overrides model setting from config file.
@@ -54,6 +66,63 @@ pub struct Cli {
     /// Suppress all output except errors (equivalent to --log-level error)
     #[arg(short, long)]
     pub quiet: bool,
+
+    /// Disable color in notices (info, warning and error lines)
+    ///
+    /// The prompt, status line and highlighted code keep their colors.
+    /// Also honored via the NO_COLOR environment variable.
+    #[arg(long)]
+    pub no_color: bool,
+
+    /// Offer to remove a stale socket file left behind by a crashed backend
+    #[arg(long)]
+    pub remove_stale_socket: bool,
+
+    /// Seconds to wait for a response to each agent query
+    ///
+    /// Overrides agent.query_timeout_secs from the config file. Control
+    /// requests (initialize, session.*) keep their shorter timeout.
+    #[arg(long, value_name = "SECS")]
+    pub timeout_per_query: Option<u64>,
+
+    /// Run in safe mode
+    ///
+    /// Forces demo tool execution, requires approval, denies high-risk tools
+    /// automatically, and (unless safe_mode.mock_model = false) uses the mock
+    /// model. Overrides conflicting config and CLI values.
+    #[arg(long)]
+    pub safe: bool,
+
+    /// Fail immediately if the backend isn't reachable (no connect retries)
+    #[arg(long)]
+    pub no_retry: bool,
+
+    /// Connect to an abstract-namespace Unix socket by name (Linux only)
+    ///
+    /// Abstract sockets have no filesystem entry, so they can't go stale or
+    /// hit permission issues in $XDG_RUNTIME_DIR.
+    #[arg(long, value_name = "NAME", conflicts_with = "socket")]
+    pub abstract_socket: Option<String>,
+
+    /// Append every query and streamed response to a markdown file
+    ///
+    /// Overrides terminal.transcript_file from the config file.
+    #[arg(long, value_name = "FILE")]
+    pub transcript: Option<PathBuf>,
+
+    /// Use the test socket (openagent-terminal-test.sock) by default
+    ///
+    /// Pairs with a backend started with --test, so a test backend can run
+    /// alongside the regular one.
+    #[arg(long)]
+    pub test: bool,
+
+    /// Print every IPC frame to stderr, prefixed with >> (sent) or << (received)
+    ///
+    /// Independent of --log-level. Frames are printed until the interactive
+    /// prompt takes over the terminal (connect and initialize).
+    #[arg(long)]
+    pub verbose_ipc: bool,
 }
 
 /// Log level for the application
@@ -86,6 +155,23 @@ impl LogLevel {
         }
     }
 
+    /// Level currently let through by `log::max_level()`
+    pub fn current() -> Self {
+        match log::max_level() {
+            log::LevelFilter::Trace => LogLevel::Trace,
+            log::LevelFilter::Debug => LogLevel::Debug,
+            log::LevelFilter::Info => LogLevel::Info,
+            log::LevelFilter::Warn => LogLevel::Warn,
+            log::LevelFilter::Error => LogLevel::Error,
+            log::LevelFilter::Off => LogLevel::Off,
+        }
+    }
+
+    /// Make this the active log level for the rest of the run
+    pub fn apply(self) {
+        log::set_max_level(self.to_level_filter());
+    }
+
     /// Convert to string for env_logger filter
     pub fn to_filter_str(self) -> &'static str {
         match self {
@@ -119,8 +205,13 @@ impl Cli {
     }
 
     /// Get socket path with precedence: CLI > Environment > Default
+    ///
+    /// Abstract sockets are returned as `@name` (see `ABSTRACT_SOCKET_PREFIX`).
     pub fn effective_socket_path(&self) -> String {
         // CLI argument takes highest precedence
+        if let Some(ref name) = self.abstract_socket {
+            return format!("{}{}", crate::ipc::client::ABSTRACT_SOCKET_PREFIX, name);
+        }
         if let Some(ref socket) = self.socket {
             return socket.to_string_lossy().to_string();
         }
@@ -130,9 +221,7 @@ impl Cli {
             return socket;
         }
 
-        // Default path
-        let runtime_dir = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/tmp".to_string());
-        format!("{}/openagent-terminal-test.sock", runtime_dir)
+        default_socket_path(std::env::var("XDG_RUNTIME_DIR").ok().as_deref(), self.test)
     }
 
     /// Get config path with precedence: CLI > Default
@@ -140,12 +229,28 @@ impl Cli {
         self.config.clone()
     }
 
+    /// Whether notices should be colored
+    ///
+    /// Precedence: --no-color > NO_COLOR env > default (enabled)
+    pub fn color_enabled(&self) -> bool {
+        if self.no_color {
+            return false;
+        }
+        std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+    }
+
     /// Check if we should only generate config and exit
     pub fn should_generate_config(&self) -> bool {
         self.generate_config
     }
 }
 
+/// Default socket path in `runtime_dir` (falling back to /tmp)
+fn default_socket_path(runtime_dir: Option<&str>, test: bool) -> String {
+    let name = if test { TEST_SOCKET_NAME } else { DEFAULT_SOCKET_NAME };
+    format!("{}/{}", runtime_dir.unwrap_or("/tmp"), name)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -157,42 +262,101 @@ mod tests {
         assert_eq!(LogLevel::Error.to_filter_str(), "error");
     }
 
+    #[test]
+    fn test_apply_log_level() {
+        let before = LogLevel::current();
+        LogLevel::Trace.apply();
+        assert_eq!(log::max_level(), log::LevelFilter::Trace);
+        assert!(matches!(LogLevel::current(), LogLevel::Trace));
+        LogLevel::Warn.apply();
+        assert_eq!(log::max_level(), log::LevelFilter::Warn);
+        assert!(matches!(LogLevel::current(), LogLevel::Warn));
+        before.apply();
+    }
+
     #[test]
     fn test_effective_log_level() {
         // Test quiet flag takes precedence
         let cli = Cli {
-            socket: None,
-            config: None,
             log_level: Some(LogLevel::Debug),
-            generate_config: false,
-            model: None,
-            verbose: false,
             quiet: true,
+            ..Default::default()
         };
         assert!(matches!(cli.effective_log_level(), LogLevel::Error));
 
         // Test verbose flag
         let cli = Cli {
-            socket: None,
-            config: None,
-            log_level: None,
-            generate_config: false,
-            model: None,
             verbose: true,
-            quiet: false,
+            ..Default::default()
         };
         assert!(matches!(cli.effective_log_level(), LogLevel::Debug));
 
         // Test explicit log level
         let cli = Cli {
-            socket: None,
-            config: None,
             log_level: Some(LogLevel::Trace),
-            generate_config: false,
-            model: None,
-            verbose: false,
-            quiet: false,
+            ..Default::default()
         };
         assert!(matches!(cli.effective_log_level(), LogLevel::Trace));
     }
+
+    #[test]
+    fn test_no_color_flag() {
+        let cli = Cli::parse_from(["openagent-terminal", "--no-color"]);
+        assert!(cli.no_color);
+        assert!(!cli.color_enabled());
+    }
+
+    #[test]
+    fn test_safe_flag_overrides_config() {
+        let cli = Cli::parse_from(["openagent-terminal", "--safe", "--model", "gpt-4"]);
+        assert!(cli.safe);
+
+        let mut config = crate::config::Config::default();
+        config.tools.enable_real_execution = true;
+        config.agent.model = cli.model.clone().unwrap();
+        config.safe_mode.enabled |= cli.safe;
+        config.apply_safe_mode();
+        assert!(!config.tools.enable_real_execution);
+        assert_eq!(config.agent.model, "mock");
+    }
+
+    #[test]
+    fn test_abstract_socket_path() {
+        let cli = Cli::parse_from(["openagent-terminal", "--abstract-socket", "openagent"]);
+        assert_eq!(cli.effective_socket_path(), "@openagent");
+
+        // Can't be combined with a filesystem socket
+        assert!(Cli::try_parse_from(["openagent-terminal", "--abstract-socket", "a", "--socket", "/tmp/b.sock"]).is_err());
+    }
+
+    #[test]
+    fn test_default_socket_path() {
+        assert_eq!(default_socket_path(Some("/run/user/1000"), false), "/run/user/1000/openagent-terminal.sock");
+        assert_eq!(default_socket_path(Some("/run/user/1000"), true), "/run/user/1000/openagent-terminal-test.sock");
+        assert_eq!(default_socket_path(None, false), "/tmp/openagent-terminal.sock");
+
+        assert!(!Cli::parse_from(["openagent-terminal"]).test);
+        assert!(Cli::parse_from(["openagent-terminal", "--test"]).test);
+    }
+
+    #[test]
+    fn test_verbose_ipc_flag() {
+        assert!(Cli::parse_from(["openagent-terminal", "--verbose-ipc"]).verbose_ipc);
+        assert!(!Cli::parse_from(["openagent-terminal", "--verbose"]).verbose_ipc);
+    }
+
+    #[test]
+    fn test_no_retry_flag() {
+        assert!(Cli::parse_from(["openagent-terminal", "--no-retry"]).no_retry);
+        assert!(!Cli::parse_from(["openagent-terminal"]).no_retry);
+    }
+
+    #[test]
+    fn test_timeout_per_query_flag() {
+        let cli = Cli::parse_from(["openagent-terminal", "--timeout-per-query", "300"]);
+        assert_eq!(cli.timeout_per_query, Some(300));
+
+        let cli = Cli::parse_from(["openagent-terminal"]);
+        assert_eq!(cli.timeout_per_query, None);
+    }
 }
@@ -0,0 +1,112 @@
+// Clock - Injectable source of "now"
+//
+// Code that needs the current time takes a `Clock` instead of calling
+// `Utc::now()` directly, so tests can pin timestamps with `MockClock`.
+
+use chrono::{DateTime, Utc};
+#[cfg(test)]
+use std::sync::Mutex;
+
+/// Source of the current time
+pub trait Clock: Send + Sync {
+    /// Current UTC time
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Real wall-clock time
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Fixed time that only moves when told to, for tests
+#[cfg(test)]
+#[derive(Debug)]
+pub struct MockClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+impl MockClock {
+    /// Create a clock pinned at `now`
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self { now: Mutex::new(now) }
+    }
+
+    /// Move the clock to `now`
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    /// Move the clock forward by `by`
+    pub fn advance(&self, by: chrono::Duration) {
+        let mut now = self.now.lock().unwrap();
+        *now += by;
+    }
+}
+
+#[cfg(test)]
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// Describe how long ago `then` was relative to `now` (e.g. "5m ago")
+///
+/// Future timestamps (clock skew) are reported as "just now".
+pub fn humanize_since(then: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let secs = (now - then).num_seconds();
+    match secs {
+        s if s < 60 => "just now".to_string(),
+        s if s < 3600 => format!("{}m ago", s / 60),
+        s if s < 86_400 => format!("{}h ago", s / 3600),
+        s if s < 30 * 86_400 => format!("{}d ago", s / 86_400),
+        _ => then.format("%Y-%m-%d").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn pinned() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 10, 4, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_mock_clock_is_pinned() {
+        let clock = MockClock::new(pinned());
+        assert_eq!(clock.now(), pinned());
+
+        clock.advance(Duration::minutes(5));
+        assert_eq!(clock.now(), pinned() + Duration::minutes(5));
+
+        clock.set(pinned());
+        assert_eq!(clock.now(), pinned());
+    }
+
+    #[test]
+    fn test_humanize_since() {
+        let clock = MockClock::new(pinned());
+        let then = pinned();
+
+        assert_eq!(humanize_since(then, clock.now()), "just now");
+        clock.advance(Duration::minutes(5));
+        assert_eq!(humanize_since(then, clock.now()), "5m ago");
+        clock.advance(Duration::hours(3));
+        assert_eq!(humanize_since(then, clock.now()), "3h ago");
+        clock.advance(Duration::days(2));
+        assert_eq!(humanize_since(then, clock.now()), "2d ago");
+        clock.advance(Duration::days(60));
+        assert_eq!(humanize_since(then, clock.now()), "2025-10-04");
+
+        // Timestamps slightly in the future don't produce negative output
+        assert_eq!(humanize_since(pinned() + Duration::seconds(30), pinned()), "just now");
+    }
+}
@@ -4,7 +4,22 @@
 // or a regular agent query, and executes the appropriate action.
 
 use crate::ansi;
-use crate::session::{SessionManager, SessionMetadata};
+use crate::cli::LogLevel;
+use crate::clock;
+use crate::config::{AgentConfig, Config};
+use crate::glyphs::Glyph;
+use crate::ipc::{ConnectionState, client::Diagnostics, message::{redact_sensitive, FinishReason, ModelInfo, ServerInfo}};
+use crate::notice::{self, Level};
+use crate::output::Output;
+use crate::session::{SessionManager, SessionMetadata, SessionSearchHit};
+use chrono::{DateTime, Utc};
+use std::path::Path;
+
+/// Number of entries `/history` shows when no limit is given
+pub const DEFAULT_HISTORY_LIMIT: usize = 20;
+
+/// Word the user must type to turn on real tool execution
+pub const EXEC_CONFIRMATION_WORD: &str = "EXECUTE";
 
 /// Represents a parsed command from user input
 #[derive(Debug, Clone)]
@@ -13,24 +28,214 @@ pub enum Command {
     Query(String),
     /// List all sessions (with optional limit)
     ListSessions(Option<usize>),
+    /// Search sessions by title and content
+    SearchSessions(String),
     /// Load a specific session by ID
     LoadSession(String),
+    /// Load the most recently updated session
+    ResumeLast,
     /// Export current or specified session
     ExportSession {
         session_id: Option<String>,
         format: String,
         output_file: Option<String>,
+        /// Print the export inline instead of writing a file
+        to_stdout: bool,
+        /// Overwrite an existing output file without asking
+        force: bool,
     },
+    /// Start a new, empty session (optionally titled) and switch to it
+    NewSession(Option<String>),
+    /// Copy a session (current by default) into a new one and switch to it
+    ForkSession(Option<String>),
     /// Delete a session
     DeleteSession(String),
+    /// Give a session a new title
+    RenameSession { session_id: String, title: String },
+    /// Pin a session so it is listed first
+    PinSession(String),
+    /// Remove a session's pin
+    UnpinSession(String),
+    /// List only pinned sessions
+    ListFavorites,
     /// Show current session info
     SessionInfo,
+    /// List the models the backend offers
+    ListModels,
+    /// List highlighting themes, marking the active one
+    ListThemes,
+    /// Switch the highlighting theme
+    SetTheme(String),
+    /// Show connection and backend diagnostics
+    Diagnostics,
+    /// Print the last raw message received from the backend
+    LastMessage,
+    /// Zero the protocol drift / orphan response counters
+    ResetDiagnostics,
+    /// Set (Some) or clear (None) the active system prompt
+    SetSystemPrompt(Option<String>),
+    /// Show the active system prompt
+    ShowSystemPrompt,
+    /// Resume the last response after it was cancelled or cut off
+    Continue,
+    /// Set the sampling temperature for following queries
+    SetTemperature(f32),
+    /// Show the sampling temperature
+    ShowTemperature,
+    /// Set the response token cap for following queries
+    SetMaxTokens(u32),
+    /// Show the response token cap
+    ShowMaxTokens,
+    /// Change the log level for the rest of the run
+    SetLogLevel(LogLevel),
+    /// Show the active log level
+    ShowLogLevel,
+    /// Turn real tool execution on (true) or off (false)
+    ToggleExec(bool),
+    /// Flip privacy mode (typed input shown as bullets)
+    TogglePrivate,
+    /// View or change the directories tools may operate in
+    SafeDir(SafeDirAction),
+    /// View or change configuration settings
+    Config(ConfigAction),
+    /// Show input history (last N entries, optionally filtered)
+    History {
+        limit: Option<usize>,
+        search: Option<String>,
+    },
+    /// Save input history to a file
+    ExportHistory(String),
+    /// Load input history from a file
+    ImportHistory(String),
     /// Show help
     Help,
+    /// Nothing to do (empty input, or an invalid command already reported)
+    Noop,
     /// Exit the application
     Exit,
 }
 
+/// What `/safedir` does with the tool directory allowlist
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SafeDirAction {
+    List,
+    Add(String),
+    Remove(String),
+}
+
+/// What `/config` does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigAction {
+    /// Print the whole configuration
+    Show,
+    /// Print one setting, by dotted path
+    Get(String),
+    /// Change a setting and save it to the config file
+    Set { key: String, value: String },
+}
+
+/// Options controlling how input is normalized before submission
+#[derive(Debug, Clone, Copy)]
+pub struct SubmitOptions {
+    /// Strip trailing spaces/tabs from every line and from the end of input
+    pub trim_trailing_whitespace: bool,
+    /// Collapse runs of blank lines into a single blank line
+    pub collapse_blank_lines: bool,
+}
+
+impl Default for SubmitOptions {
+    fn default() -> Self {
+        Self {
+            trim_trailing_whitespace: true,
+            collapse_blank_lines: false,
+        }
+    }
+}
+
+/// Normalize submitted input according to the given options
+///
+/// Slash commands are exempt from blank-line collapsing so their arguments
+/// are passed through untouched.
+pub fn normalize_submission(input: &str, opts: &SubmitOptions) -> String {
+    let is_command = input.trim_start().starts_with('/');
+    let collapse = opts.collapse_blank_lines && !is_command;
+
+    let mut lines: Vec<&str> = Vec::new();
+    let mut prev_blank = false;
+
+    for line in input.split('\n') {
+        let line = if opts.trim_trailing_whitespace { line.trim_end() } else { line };
+        let blank = line.trim().is_empty();
+
+        if collapse && blank && prev_blank {
+            continue;
+        }
+
+        prev_blank = blank;
+        lines.push(line);
+    }
+
+    let mut normalized = lines.join("\n");
+    if opts.trim_trailing_whitespace {
+        normalized.truncate(normalized.trim_end().len());
+    }
+    normalized
+}
+
+/// A slash command as offered by the command palette
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandSpec {
+    /// Name without the leading slash
+    pub name: &'static str,
+    /// Argument synopsis, empty if the command takes none
+    pub args: &'static str,
+    pub description: &'static str,
+}
+
+impl CommandSpec {
+    /// Text to put in the input buffer when the command is chosen
+    pub fn insert_text(&self) -> String {
+        if self.args.is_empty() {
+            format!("/{}", self.name)
+        } else {
+            format!("/{} ", self.name)
+        }
+    }
+}
+
+/// Every slash command understood by `parse_command` (aliases omitted)
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec { name: "list", args: "[limit]", description: "List recent sessions" },
+    CommandSpec { name: "search", args: "<query>", description: "Find sessions by title or content" },
+    CommandSpec { name: "load", args: "<session-id>", description: "Load a previous session" },
+    CommandSpec { name: "resume", args: "", description: "Load the most recently updated session" },
+    CommandSpec { name: "export", args: "[session-id] [--format=..] [--output=.. [--force] | --stdout]", description: "Export a session" },
+    CommandSpec { name: "new", args: "[title]", description: "Start a new, empty session" },
+    CommandSpec { name: "fork", args: "[session-id]", description: "Copy a session into a new one" },
+    CommandSpec { name: "delete", args: "<session-id>", description: "Delete a session" },
+    CommandSpec { name: "rename", args: "<session-id> <title>", description: "Change a session's title" },
+    CommandSpec { name: "pin", args: "<session-id>", description: "Pin a session to the top of /list" },
+    CommandSpec { name: "unpin", args: "<session-id>", description: "Unpin a session" },
+    CommandSpec { name: "fav", args: "", description: "List pinned sessions" },
+    CommandSpec { name: "info", args: "", description: "Show current session info" },
+    CommandSpec { name: "models", args: "", description: "List models the backend offers" },
+    CommandSpec { name: "theme", args: "[name]", description: "List or switch highlighting themes" },
+    CommandSpec { name: "system", args: "[text|clear]", description: "Show or set the system prompt" },
+    CommandSpec { name: "continue", args: "", description: "Resume a cancelled or cut-off response" },
+    CommandSpec { name: "temp", args: "[0.0-2.0]", description: "Show or set the sampling temperature" },
+    CommandSpec { name: "maxtokens", args: "[n]", description: "Show or set the response token cap" },
+    CommandSpec { name: "loglevel", args: "[level]", description: "Show or change the log level" },
+    CommandSpec { name: "exec", args: "on|off", description: "Toggle real tool execution" },
+    CommandSpec { name: "private", args: "", description: "Toggle privacy mode (mask typed input)" },
+    CommandSpec { name: "safedir", args: "[list] | add|remove <path>", description: "Show or edit the directories tools may use" },
+    CommandSpec { name: "config", args: "[get <key> | set <key> <value>]", description: "Show or change settings" },
+    CommandSpec { name: "history", args: "[N] | search <text> | export|import <file>", description: "Show, search, or save input history" },
+    CommandSpec { name: "diag", args: "[reset]", description: "Show connection and backend diagnostics" },
+    CommandSpec { name: "lastmsg", args: "", description: "Show the last raw message from the backend" },
+    CommandSpec { name: "help", args: "", description: "Show help" },
+    CommandSpec { name: "exit", args: "", description: "Exit the terminal" },
+];
+
 /// Parse user input into a command
 pub fn parse_command(input: &str) -> Command {
     let trimmed = input.trim();
@@ -39,8 +244,9 @@ pub fn parse_command(input: &str) -> Command {
     if let Some(cmd) = trimmed.strip_prefix('/') {
         let parts: Vec<&str> = cmd.split_whitespace().collect();
         
+        // A bare "/" is treated like empty input
         if parts.is_empty() {
-            return Command::Query(input.to_string());
+            return Command::Noop;
         }
 
         match parts[0] {
@@ -48,12 +254,21 @@ pub fn parse_command(input: &str) -> Command {
                 let limit = parts.get(1).and_then(|s| s.parse::<usize>().ok());
                 Command::ListSessions(limit)
             }
+            "search" | "find" => {
+                // The query is everything after the command, spaces included
+                let query = rest_after_words(cmd, 1);
+                if query.is_empty() {
+                    notice::error("/search requires text to look for");
+                    println!("Usage: /search <query>");
+                    return Command::Noop;
+                }
+                Command::SearchSessions(query.to_string())
+            }
             "load" => {
                 if parts.len() < 2 {
-                    println!("{}Error:{} /load requires a session ID", 
-                        ansi::colors::RED, ansi::colors::RESET);
+                    notice::error("/load requires a session ID");
                     println!("Usage: /load <session-id>");
-                    return Command::Help;
+                    return Command::Noop;
                 }
                 Command::LoadSession(parts[1].to_string())
             }
@@ -61,10 +276,16 @@ pub fn parse_command(input: &str) -> Command {
                 let mut session_id = None;
                 let mut format = "markdown".to_string();
                 let mut output_file = None;
+                let mut to_stdout = false;
+                let mut force = false;
 
-                // Parse arguments: /export [session-id] [--format=markdown] [--output=file.md]
+                // Parse arguments: /export [session-id] [--format=markdown] [--output=file.md [--force] | --stdout]
                 for part in &parts[1..] {
-                    if let Some(fmt) = part.strip_prefix("--format=") {
+                    if *part == "--stdout" {
+                        to_stdout = true;
+                    } else if *part == "--force" {
+                        force = true;
+                    } else if let Some(fmt) = part.strip_prefix("--format=") {
                         format = fmt.to_string();
                     } else if let Some(file) = part.strip_prefix("--output=") {
                         output_file = Some(file.to_string());
@@ -72,177 +293,911 @@ pub fn parse_command(input: &str) -> Command {
                         session_id = Some(part.to_string());
                     }
                 }
+                if to_stdout && output_file.is_some() {
+                    notice::error("/export takes either --stdout or --output, not both");
+                    println!("Usage: /export [session-id] [--format=..] [--output=<file> [--force] | --stdout]");
+                    return Command::Noop;
+                }
 
                 Command::ExportSession {
                     session_id,
                     format,
                     output_file,
+                    to_stdout,
+                    force,
                 }
             }
+            "resume" | "last" => Command::ResumeLast,
+            "new" => {
+                // The title is everything after the command, spaces included
+                let title = rest_after_words(cmd, 1);
+                Command::NewSession((!title.is_empty()).then(|| title.to_string()))
+            }
+            "fork" => Command::ForkSession(parts.get(1).map(|s| s.to_string())),
             "delete" | "rm" => {
                 if parts.len() < 2 {
-                    println!("{}Error:{} /delete requires a session ID", 
-                        ansi::colors::RED, ansi::colors::RESET);
+                    notice::error("/delete requires a session ID");
                     println!("Usage: /delete <session-id>");
-                    return Command::Help;
+                    return Command::Noop;
                 }
                 Command::DeleteSession(parts[1].to_string())
             }
+            "rename" => {
+                if parts.len() < 3 {
+                    notice::error("/rename requires a session ID and a new title");
+                    println!("Usage: /rename <session-id> <new title>");
+                    return Command::Noop;
+                }
+                // The title is everything after the ID, spaces included
+                let title = rest_after_words(cmd, 2);
+                Command::RenameSession {
+                    session_id: parts[1].to_string(),
+                    title: title.to_string(),
+                }
+            }
+            "pin" | "unpin" => {
+                let Some(id) = parts.get(1) else {
+                    notice::error(format!("/{} requires a session ID", parts[0]));
+                    println!("Usage: /{} <session-id>", parts[0]);
+                    return Command::Noop;
+                };
+                if parts[0] == "pin" {
+                    Command::PinSession(id.to_string())
+                } else {
+                    Command::UnpinSession(id.to_string())
+                }
+            }
+            "fav" | "favorites" => Command::ListFavorites,
             "info" | "current" => Command::SessionInfo,
+            "models" => Command::ListModels,
+            "theme" => match parts.get(1) {
+                Some(name) => Command::SetTheme(name.to_string()),
+                None => Command::ListThemes,
+            },
+            "lastmsg" => Command::LastMessage,
+            "diag" => match parts.get(1).copied() {
+                None => Command::Diagnostics,
+                Some("reset") => Command::ResetDiagnostics,
+                Some(_) => {
+                    notice::error("Unknown /diag option");
+                    println!("Usage: /diag [reset]");
+                    Command::Noop
+                }
+            },
+            "private" => Command::TogglePrivate,
+            "continue" => Command::Continue,
+            "temp" => match parts.get(1) {
+                None => Command::ShowTemperature,
+                Some(value) => {
+                    let parsed = value.parse::<f32>()
+                        .map_err(|_| format!("Not a number: {}", value))
+                        .and_then(AgentConfig::validate_temperature);
+                    match parsed {
+                        Ok(temperature) => Command::SetTemperature(temperature),
+                        Err(e) => {
+                            notice::error(e);
+                            println!("Usage: /temp <0.0-2.0>");
+                            Command::Noop
+                        }
+                    }
+                }
+            },
+            "maxtokens" => match parts.get(1) {
+                None => Command::ShowMaxTokens,
+                Some(value) => {
+                    let parsed = value.parse::<u32>()
+                        .map_err(|_| format!("Not a whole number: {}", value))
+                        .and_then(AgentConfig::validate_max_tokens);
+                    match parsed {
+                        Ok(max_tokens) => Command::SetMaxTokens(max_tokens),
+                        Err(e) => {
+                            notice::error(e);
+                            println!("Usage: /maxtokens <n>");
+                            Command::Noop
+                        }
+                    }
+                }
+            },
+            "loglevel" => match parts.get(1) {
+                None => Command::ShowLogLevel,
+                Some(name) => match <LogLevel as clap::ValueEnum>::from_str(name, true) {
+                    Ok(level) => Command::SetLogLevel(level),
+                    Err(_) => {
+                        notice::error(format!("Unknown log level: {}", name));
+                        println!("Usage: /loglevel trace|debug|info|warn|error|off");
+                        Command::Noop
+                    }
+                },
+            },
+            "exec" => match parts.get(1).copied() {
+                Some("on") => Command::ToggleExec(true),
+                Some("off") => Command::ToggleExec(false),
+                _ => {
+                    notice::error("/exec requires on or off");
+                    println!("Usage: /exec on|off");
+                    Command::Noop
+                }
+            },
+            "safedir" => match parts.get(1).copied() {
+                None | Some("list") => Command::SafeDir(SafeDirAction::List),
+                Some(sub @ ("add" | "remove" | "rm")) => {
+                    // The rest of the line, so paths may contain spaces
+                    let path = rest_after_words(cmd, 2);
+                    if path.is_empty() {
+                        notice::error(format!("/safedir {} requires a path", sub));
+                        println!("Usage: /safedir {} <path>", sub);
+                        return Command::Noop;
+                    }
+                    if sub == "add" {
+                        Command::SafeDir(SafeDirAction::Add(path.to_string()))
+                    } else {
+                        Command::SafeDir(SafeDirAction::Remove(path.to_string()))
+                    }
+                }
+                Some(_) => {
+                    notice::error("Unknown /safedir option");
+                    println!("Usage: /safedir [list] | /safedir add|remove <path>");
+                    Command::Noop
+                }
+            },
+            "config" => match parts.get(1).copied() {
+                None | Some("show") => Command::Config(ConfigAction::Show),
+                Some("get") => match parts.get(2) {
+                    Some(key) => Command::Config(ConfigAction::Get(key.to_string())),
+                    None => {
+                        notice::error("/config get requires a setting");
+                        println!("Usage: /config get <section.key>");
+                        Command::Noop
+                    }
+                },
+                Some("set") => {
+                    let (Some(key), true) = (parts.get(2), parts.len() > 3) else {
+                        notice::error("/config set requires a setting and a value");
+                        println!("Usage: /config set <section.key> <value>");
+                        return Command::Noop;
+                    };
+                    // The rest of the line, so string values may contain spaces
+                    let value = rest_after_words(cmd, 3);
+                    Command::Config(ConfigAction::Set { key: key.to_string(), value: value.to_string() })
+                }
+                Some(_) => {
+                    notice::error("Unknown /config option");
+                    println!("Usage: /config [get <section.key> | set <section.key> <value>]");
+                    Command::Noop
+                }
+            },
+            "history" => {
+                if let Some(&sub @ ("export" | "import")) = parts.get(1) {
+                    let Some(path) = parts.get(2) else {
+                        notice::error(format!("/history {} requires a file path", sub));
+                        println!("Usage: /history {} <file>", sub);
+                        return Command::Noop;
+                    };
+                    if sub == "export" {
+                        Command::ExportHistory(path.to_string())
+                    } else {
+                        Command::ImportHistory(path.to_string())
+                    }
+                } else if parts.get(1) == Some(&"search") {
+                    let text = rest_after_words(cmd, 2);
+                    if text.is_empty() {
+                        notice::error("/history search requires text to look for");
+                        println!("Usage: /history search <text>");
+                        return Command::Noop;
+                    }
+                    Command::History { limit: None, search: Some(text.to_string()) }
+                } else {
+                    let limit = match parts.get(1) {
+                        None => None,
+                        Some(n) => match n.parse::<usize>() {
+                            Ok(n) if n > 0 => Some(n),
+                            _ => {
+                                notice::error(format!("/history takes a positive number of entries, not '{}'", n));
+                                println!("Usage: /history [N] | search <text> | export|import <file>");
+                                return Command::Noop;
+                            }
+                        },
+                    };
+                    Command::History { limit, search: None }
+                }
+            }
+            "system" => {
+                // Keep the prompt text verbatim (including inner spacing)
+                let text = rest_after_words(cmd, 1);
+                match text {
+                    "" => Command::ShowSystemPrompt,
+                    "clear" => Command::SetSystemPrompt(None),
+                    _ => Command::SetSystemPrompt(Some(text.to_string())),
+                }
+            }
             "help" | "?" => Command::Help,
             "exit" | "quit" | "q" => Command::Exit,
             _ => {
-                println!("{}Unknown command:{} {}", 
-                    ansi::colors::YELLOW, ansi::colors::RESET, parts[0]);
+                notice::warn(format!("Unknown command: {}", parts[0]));
                 println!("Type /help for available commands");
-                Command::Help
+                Command::Noop
             }
         }
     } else if trimmed.is_empty() {
         // Empty input - do nothing
-        Command::Help
+        Command::Noop
     } else {
         // Regular agent query
         Command::Query(input.to_string())
     }
 }
 
+/// The rest of `cmd` after its first `words` words, inner spacing kept
+///
+/// Splits on the same whitespace as `split_whitespace`, so no byte offset
+/// is carried over from the parsed words.
+fn rest_after_words(cmd: &str, words: usize) -> &str {
+    let mut rest = cmd.trim();
+    for _ in 0..words {
+        rest = rest.split_once(char::is_whitespace).map_or("", |(_, tail)| tail.trim_start());
+    }
+    rest
+}
+
+/// `path` with a leading `~` replaced by `home` (unchanged without a home directory)
+pub fn expand_home(path: &str, home: Option<&Path>) -> String {
+    let rest = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => rest,
+        _ => return path.to_string(),
+    };
+    match home {
+        Some(home) => format!("{}{}", home.display(), rest),
+        None => path.to_string(),
+    }
+}
+
+/// Add `path` to the allowlist with `~` expanded; false if it is already listed
+pub fn add_safe_directory(dirs: &mut Vec<String>, path: &str, home: Option<&Path>) -> bool {
+    let expanded = expand_home(path, home);
+    if dirs.iter().any(|dir| Path::new(&expand_home(dir, home)) == Path::new(&expanded)) {
+        return false;
+    }
+    dirs.push(expanded);
+    true
+}
+
+/// Remove every allowlist entry naming `path` (`~` expanded on both sides); false if none did
+pub fn remove_safe_directory(dirs: &mut Vec<String>, path: &str, home: Option<&Path>) -> bool {
+    let expanded = expand_home(path, home);
+    let before = dirs.len();
+    dirs.retain(|dir| Path::new(&expand_home(dir, home)) != Path::new(&expanded));
+    dirs.len() != before
+}
+
+/// Print the configuration as TOML
+pub fn display_config(out: &mut dyn Output, config: &Config) {
+    match toml::to_string_pretty(config) {
+        Ok(text) => writeln!(out, "\n{}", text.trim_end()),
+        Err(e) => notice::write_notice(out, Level::Error, format!("Failed to serialize config: {}", e)),
+    }
+    writeln!(out);
+}
+
+/// Print one setting as `key = value`, or a whole table under its header
+pub fn display_setting(out: &mut dyn Output, key: &str, value: &toml::Value) {
+    match value {
+        toml::Value::Table(table) => {
+            writeln!(out, "{}[{}]{}", ansi::colors::BRIGHT_WHITE, key, ansi::colors::RESET);
+            write!(out, "{}", toml::to_string_pretty(table).unwrap_or_default());
+        }
+        value => writeln!(out, "{} = {}", key, value),
+    }
+}
+
+/// List the tool directory allowlist, showing what `~` entries expand to
+pub fn display_safe_directories(out: &mut dyn Output, dirs: &[String], home: Option<&Path>) {
+    if dirs.is_empty() {
+        notice::write_notice(out, Level::Warn, "No safe directories: tools can't operate anywhere");
+        writeln!(out, "Use /safedir add <path> to allow one");
+        return;
+    }
+    writeln!(out);
+    for dir in dirs {
+        let expanded = expand_home(dir, home);
+        if expanded == *dir {
+            writeln!(out, "  {}", dir);
+        } else {
+            writeln!(out, "  {}  {}→ {}{}", dir, ansi::colors::BRIGHT_BLACK, expanded, ansi::colors::RESET);
+        }
+    }
+    writeln!(out);
+}
+
+/// Order sessions with pinned ones first, otherwise keeping the backend's order
+pub fn pinned_first(sessions: &[SessionMetadata]) -> Vec<&SessionMetadata> {
+    let mut ordered: Vec<&SessionMetadata> = sessions.iter().collect();
+    ordered.sort_by_key(|s| !s.pinned);
+    ordered
+}
+
+/// Title box spanning the terminal width, for the top of a panel
+fn header_box(title: &str) -> String {
+    format!("{}{}{}", ansi::colors::CYAN, ansi::boxed(title, &[], ansi::content_width(None)), ansi::colors::RESET)
+}
+
 /// Display a formatted list of sessions
-pub fn display_sessions_list(sessions: &[SessionMetadata]) {
+pub fn display_sessions_list(out: &mut dyn Output, sessions: &[SessionMetadata], now: DateTime<Utc>) {
     if sessions.is_empty() {
-        println!("{}No sessions found.{}", ansi::colors::YELLOW, ansi::colors::RESET);
-        println!("Start a conversation to create your first session!");
+        notice::write_notice(out, Level::Warn, "No sessions found.");
+        writeln!(out, "Start a conversation to create your first session!");
         return;
     }
 
-    println!("\n{}╔═══════════════════════════════════════════════════════════════════╗{}", 
-        ansi::colors::CYAN, ansi::colors::RESET);
-    println!("{}║                        Session History                           ║{}", 
-        ansi::colors::CYAN, ansi::colors::RESET);
-    println!("{}╚═══════════════════════════════════════════════════════════════════╝{}", 
-        ansi::colors::CYAN, ansi::colors::RESET);
-    println!();
+    writeln!(out, "\n{}", header_box("Session History"));
+    writeln!(out);
 
-    for (idx, session) in sessions.iter().enumerate() {
-        let session_id_short = &session.session_id[..8.min(session.session_id.len())];
-        
-        println!("{}{}. {}{} {}{}", 
-            ansi::colors::BRIGHT_WHITE,
-            idx + 1,
-            ansi::colors::CYAN,
-            session_id_short,
-            session.title,
-            ansi::colors::RESET
-        );
-        
-        println!("   {}Created:{} {}  {}Messages:{} {}  {}Tokens:{} {}", 
-            ansi::colors::BRIGHT_BLACK,
-            ansi::colors::RESET,
-            session.created_at.format("%Y-%m-%d %H:%M"),
-            ansi::colors::BRIGHT_BLACK,
-            ansi::colors::RESET,
-            session.message_count,
-            ansi::colors::BRIGHT_BLACK,
-            ansi::colors::RESET,
-            session.total_tokens
-        );
-        println!();
+    for (idx, session) in pinned_first(sessions).into_iter().enumerate() {
+        write_session_entry(out, idx, session, now);
+        writeln!(out);
+    }
+
+    writeln!(out, "{}Tip:{} Use /load <session-id> to continue a previous session", 
+        ansi::colors::BRIGHT_BLACK, ansi::colors::RESET);
+}
+
+/// Display `/search` results in the backend's order, each with its matched snippet
+pub fn display_search_results(out: &mut dyn Output, query: &str, hits: &[SessionSearchHit], now: DateTime<Utc>) {
+    if hits.is_empty() {
+        notice::write_notice(out, Level::Warn, format!("No sessions match \"{}\".", query));
+        return;
     }
 
-    println!("{}Tip:{} Use /load <session-id> to continue a previous session", 
+    writeln!(out, "\n{}", header_box(&format!("Sessions matching \"{}\"", query)));
+    writeln!(out);
+
+    // Room for the indent and quotes
+    let max_cols = ansi::content_width(None).saturating_sub(5);
+    for (idx, hit) in hits.iter().enumerate() {
+        write_session_entry(out, idx, &hit.metadata, now);
+        if let Some(snippet) = &hit.snippet {
+            let flat = snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+            writeln!(out, "   {}“{}”{}",
+                ansi::colors::BRIGHT_BLACK,
+                ansi::truncate_display(&flat, max_cols),
+                ansi::colors::RESET
+            );
+        }
+        writeln!(out);
+    }
+
+    writeln!(out, "{}Tip:{} Use /load <session-id> to continue a previous session", 
         ansi::colors::BRIGHT_BLACK, ansi::colors::RESET);
 }
 
+/// Numbered title line and stats line of one session
+fn write_session_entry(out: &mut dyn Output, idx: usize, session: &SessionMetadata, now: DateTime<Utc>) {
+    let session_id_short = &session.session_id[..8.min(session.session_id.len())];
+    let pin = if session.pinned { format!("{} ", Glyph::Pin.get()) } else { String::new() };
+    
+    writeln!(out, "{}{}. {}{}{} {}{}", 
+        ansi::colors::BRIGHT_WHITE,
+        idx + 1,
+        ansi::colors::CYAN,
+        pin,
+        session_id_short,
+        session.title,
+        ansi::colors::RESET
+    );
+    
+    writeln!(out, "   {}Created:{} {}  {}Updated:{} {}  {}Messages:{} {}  {}Tokens:{} {}", 
+        ansi::colors::BRIGHT_BLACK,
+        ansi::colors::RESET,
+        session.created_at.format("%Y-%m-%d %H:%M"),
+        ansi::colors::BRIGHT_BLACK,
+        ansi::colors::RESET,
+        clock::humanize_since(session.updated_at, now),
+        ansi::colors::BRIGHT_BLACK,
+        ansi::colors::RESET,
+        session.message_count,
+        ansi::colors::BRIGHT_BLACK,
+        ansi::colors::RESET,
+        session.total_tokens
+    );
+}
+
+/// List the built-in highlighting themes, marking `current`
+pub fn display_themes(out: &mut dyn Output, current: &str) {
+    writeln!(out);
+    for theme in ansi::THEMES {
+        let palette = theme.palette();
+        let marker = if theme.name.eq_ignore_ascii_case(current) {
+            format!("{}●{}", ansi::colors::GREEN, ansi::colors::RESET)
+        } else {
+            " ".to_string()
+        };
+        writeln!(out, "  {} {}{}{}  {}fn{} {}\"text\"{} {}// comment{}",
+            marker,
+            ansi::colors::BRIGHT_WHITE, theme.name, ansi::colors::RESET,
+            palette.keyword, ansi::colors::RESET,
+            palette.string, ansi::colors::RESET,
+            palette.comment, ansi::colors::RESET,
+        );
+    }
+    writeln!(out);
+}
+
 /// Display current session info
-pub fn display_session_info(session_id: Option<&str>, manager: &SessionManager) {
-    println!("\n{}╔═══════════════════════════════════════════════════════════════════╗{}", 
-        ansi::colors::CYAN, ansi::colors::RESET);
-    println!("{}║                      Current Session Info                        ║{}", 
-        ansi::colors::CYAN, ansi::colors::RESET);
-    println!("{}╚═══════════════════════════════════════════════════════════════════╝{}", 
-        ansi::colors::CYAN, ansi::colors::RESET);
-    println!();
+pub fn display_session_info(out: &mut dyn Output, session_id: Option<&str>, manager: &SessionManager) {
+    writeln!(out, "\n{}", header_box("Current Session Info"));
+    writeln!(out);
 
     if let Some(id) = session_id {
-        println!("{}Session ID:{} {}", 
+        writeln!(out, "{}Session ID:{} {}", 
             ansi::colors::BRIGHT_WHITE, ansi::colors::RESET, id);
         
         if let Some(metadata) = manager.get_cached_metadata(id) {
-            println!("{}Title:{} {}", 
+            writeln!(out, "{}Title:{} {}", 
                 ansi::colors::BRIGHT_WHITE, ansi::colors::RESET, metadata.title);
-            println!("{}Created:{} {}", 
+            writeln!(out, "{}Created:{} {}", 
                 ansi::colors::BRIGHT_WHITE, ansi::colors::RESET, 
                 metadata.created_at.format("%Y-%m-%d %H:%M:%S"));
-            println!("{}Updated:{} {}", 
+            writeln!(out, "{}Updated:{} {}", 
                 ansi::colors::BRIGHT_WHITE, ansi::colors::RESET, 
                 metadata.updated_at.format("%Y-%m-%d %H:%M:%S"));
-            println!("{}Messages:{} {}", 
+            writeln!(out, "{}Messages:{} {}", 
                 ansi::colors::BRIGHT_WHITE, ansi::colors::RESET, metadata.message_count);
-            println!("{}Total Tokens:{} {}", 
+            writeln!(out, "{}Total Tokens:{} {}", 
                 ansi::colors::BRIGHT_WHITE, ansi::colors::RESET, metadata.total_tokens);
         }
     } else {
-        println!("{}No active session{}", ansi::colors::YELLOW, ansi::colors::RESET);
-        println!("Start a conversation to create a new session!");
+        notice::write_notice(out, Level::Warn, "No active session");
+        notice::write_notice(out, Level::Info, "Start a conversation to create a new session!");
+    }
+    writeln!(out);
+}
+
+/// How a tool approval request is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalStyle {
+    /// Panel with tool, description, risk and preview
+    Full,
+    /// A single `Approve ...? (y/N)` line
+    Compact,
+}
+
+/// Pick the approval style for `risk_level`
+///
+/// Only `low` and `medium` risk are ever compact; high or unrecognized risk
+/// always gets the full panel so the preview is seen.
+pub fn approval_style(risk_level: &str, compact_approvals: bool) -> ApprovalStyle {
+    match risk_level {
+        "low" | "medium" if compact_approvals => ApprovalStyle::Compact,
+        _ => ApprovalStyle::Full,
+    }
+}
+
+/// Decide whether exiting should ask for confirmation first
+///
+/// Only asks when enabled and there is something to lose: a response still
+/// streaming or a conversation with messages in it.
+pub fn should_confirm_exit(confirm_on_exit: bool, stream_active: bool, transcript_has_content: bool) -> bool {
+    confirm_on_exit && (stream_active || transcript_has_content)
+}
+
+/// Decide whether an `/exec` toggle may be applied
+///
+/// Turning execution off (or re-enabling while already on) is always allowed;
+/// turning it on requires the exact confirmation word.
+pub fn exec_toggle_allowed(enable: bool, currently_enabled: bool, confirmation: Option<&str>) -> bool {
+    if !enable || currently_enabled {
+        return true;
+    }
+    confirmation.map(str::trim) == Some(EXEC_CONFIRMATION_WORD)
+}
+
+/// Extract the answer text from a non-streaming `agent.query` result
+///
+/// Looks for `content`, `response`, or `text`; anything else is shown as JSON.
+pub fn inline_response_text(result: &serde_json::Value) -> String {
+    if let Some(text) = result.as_str() {
+        return text.to_string();
+    }
+    ["content", "response", "text"]
+        .iter()
+        .find_map(|key| result.get(key).and_then(|v| v.as_str()))
+        .map(str::to_string)
+        .unwrap_or_else(|| serde_json::to_string_pretty(result).unwrap_or_default())
+}
+
+/// Display available models, marking `current`
+///
+/// `models` is `None` when the backend can't list models; only the
+/// configured model is shown then.
+pub fn display_models(out: &mut dyn Output, models: Option<&[ModelInfo]>, current: &str) {
+    let Some(models) = models else {
+        notice::write_notice(out, Level::Warn, "Backend does not list models");
+        writeln!(out, "  {}●{} {} (configured)", ansi::colors::GREEN, ansi::colors::RESET, current);
+        return;
+    };
+    if models.is_empty() {
+        notice::write_notice(out, Level::Warn, "Backend reported no models");
+        return;
+    }
+    writeln!(out);
+    for model in models {
+        let marker = if model.name == current {
+            format!("{}●{}", ansi::colors::GREEN, ansi::colors::RESET)
+        } else {
+            " ".to_string()
+        };
+        write!(out, "  {} {}{}{}", marker, ansi::colors::BRIGHT_WHITE, model.name, ansi::colors::RESET);
+        if let Some(tokens) = model.context_window {
+            write!(out, "  {}{}k ctx{}", ansi::colors::DIM, tokens / 1000, ansi::colors::RESET);
+        }
+        if let Some(description) = &model.description {
+            write!(out, "  {}", description);
+        }
+        writeln!(out);
+    }
+    if !models.iter().any(|m| m.name == current) {
+        writeln!(out, "  {}(configured model {} is not in this list){}", ansi::colors::DIM, current, ansi::colors::RESET);
+    }
+    writeln!(out);
+}
+
+/// Display history entries as returned by `LineEditor::history_entries`
+pub fn display_history(out: &mut dyn Output, entries: &[(usize, &str)], search: Option<&str>, width: usize) {
+    if entries.is_empty() {
+        match search {
+            Some(text) => notice::write_notice(out, Level::Warn, format!("No history entries match \"{}\"", text)),
+            None => notice::write_notice(out, Level::Warn, "No history yet"),
+        }
+        return;
+    }
+
+    match search {
+        Some(text) => writeln!(out, "{}History matching \"{}\":{}", ansi::colors::CYAN, text, ansi::colors::RESET),
+        None => writeln!(out, "{}Recent commands:{}", ansi::colors::CYAN, ansi::colors::RESET),
+    }
+    let index_width = entries.iter().map(|(i, _)| i.to_string().len()).max().unwrap_or(1);
+    for (index, entry) in entries {
+        writeln!(out, "{}", format_history_entry(*index, index_width, entry, width));
+    }
+}
+
+/// One `/history` line: the index right-aligned in `index_width` columns, then
+/// the entry on a single line, cut with an ellipsis to fit `width` columns
+///
+/// Line breaks in multi-line entries are shown as `↵`.
+pub fn format_history_entry(index: usize, index_width: usize, entry: &str, width: usize) -> String {
+    // Two spaces of indent and two between the columns
+    let available = width.saturating_sub(index_width + 4);
+    let entry = entry.trim_end().replace("\r\n", "\n").replace('\n', "↵");
+    format!("  {}{:>index_width$}{}  {}",
+        ansi::colors::BRIGHT_BLACK, index, ansi::colors::RESET,
+        ansi::truncate_display(&entry, available),
+    )
+}
+
+/// Whether writing an export to `path` should ask before replacing it
+///
+/// Only an existing file needs confirmation, and `--force` skips it.
+pub fn export_needs_confirmation(path: &std::path::Path, force: bool) -> bool {
+    !force && path.exists()
+}
+
+/// Create the temp file `/export` writes to when it has no explicit destination
+///
+/// The name gets a random part and the file is created exclusively, so
+/// another user can't predict it or plant a symlink there. The file is kept
+/// after the handle is dropped.
+pub fn create_default_export_file(session_id: Option<&str>, format: &str) -> std::io::Result<(std::fs::File, std::path::PathBuf)> {
+    let extension = match format {
+        "markdown" | "md" => "md",
+        "json" => "json",
+        _ => "txt",
+    };
+    let name = session_id.unwrap_or("current");
+    tempfile::Builder::new()
+        .prefix(&format!("openagent-export-{}-", name))
+        .suffix(&format!(".{}", extension))
+        .tempfile()?
+        .keep()
+        .map_err(|e| e.error)
+}
+
+/// Format a duration as a compact human-readable uptime (e.g. "2h 05m")
+pub fn format_uptime(uptime: std::time::Duration) -> String {
+    let secs = uptime.as_secs();
+    let (days, hours, mins) = (secs / 86400, (secs % 86400) / 3600, (secs % 3600) / 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {:02}m", hours, mins)
+    } else if mins > 0 {
+        format!("{}m {:02}s", mins, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Display connection and backend diagnostics
+pub fn display_diagnostics(server_info: Option<&ServerInfo>, state: ConnectionState, counters: Diagnostics) {
+    println!("\n{}", header_box("Diagnostics"));
+    println!();
+
+    let client_version = env!("CARGO_PKG_VERSION");
+    println!("{}Frontend:{} openagent-terminal v{}", 
+        ansi::colors::BRIGHT_WHITE, ansi::colors::RESET, client_version);
+    println!("{}Connection:{} {:?}", 
+        ansi::colors::BRIGHT_WHITE, ansi::colors::RESET, state);
+
+    match server_info {
+        Some(info) => {
+            println!("{}Backend:{} {} v{}", 
+                ansi::colors::BRIGHT_WHITE, ansi::colors::RESET, info.name, info.version);
+            if let Some(uptime) = info.uptime(chrono::Utc::now()) {
+                println!("{}Backend uptime:{} {}", 
+                    ansi::colors::BRIGHT_WHITE, ansi::colors::RESET, format_uptime(uptime));
+            }
+            if info.major_version_mismatch(client_version) {
+                notice::warn(format!(
+                    "Backend major version ({}) differs from frontend ({})",
+                    info.version, client_version
+                ));
+            }
+        }
+        None => {
+            println!("{}Backend:{} unknown (not reported at initialize)", 
+                ansi::colors::BRIGHT_WHITE, ansi::colors::RESET);
+        }
     }
+    println!("{}Protocol drift:{} {} message(s) with unknown fields", 
+        ansi::colors::BRIGHT_WHITE, ansi::colors::RESET, counters.protocol_drift);
+    println!("{}Orphan responses:{} {}", 
+        ansi::colors::BRIGHT_WHITE, ansi::colors::RESET, counters.orphan_responses);
     println!();
 }
 
+/// Footer explaining why a response ended early, or `None` when it finished normally
+pub fn finish_footer(reason: &FinishReason, color: bool) -> Option<String> {
+    let text = match reason {
+        FinishReason::Stop | FinishReason::Other => return None,
+        FinishReason::Length => "response truncated (max tokens), /continue resumes it",
+        FinishReason::ContentFilter => "stopped by content filter",
+        FinishReason::Error => "stopped by an error while generating",
+    };
+    Some(if color {
+        format!("{}{} {}{}", ansi::colors::BRIGHT_BLACK, Glyph::Caution.get(), text, ansi::colors::RESET)
+    } else {
+        format!("{} {}", Glyph::Caution.get(), text)
+    })
+}
+
+/// Pretty-print a raw backend line with sensitive fields redacted
+///
+/// Lines that aren't valid JSON are returned unchanged so malformed messages
+/// can still be inspected.
+pub fn format_last_message(raw: &str) -> String {
+    match serde_json::from_str::<serde_json::Value>(raw) {
+        Ok(mut value) => {
+            redact_sensitive(&mut value);
+            serde_json::to_string_pretty(&value).unwrap_or_else(|_| raw.to_string())
+        }
+        Err(_) => raw.to_string(),
+    }
+}
+
 /// Display help message
-pub fn display_help() {
-    println!("\n{}╔═══════════════════════════════════════════════════════════════════╗{}", 
-        ansi::colors::CYAN, ansi::colors::RESET);
-    println!("{}║                      OpenAgent-Terminal Help                     ║{}", 
-        ansi::colors::CYAN, ansi::colors::RESET);
-    println!("{}╚═══════════════════════════════════════════════════════════════════╝{}", 
-        ansi::colors::CYAN, ansi::colors::RESET);
-    println!();
+pub fn display_help(out: &mut dyn Output) {
+    writeln!(out, "\n{}", header_box("OpenAgent-Terminal Help"));
+    writeln!(out);
     
-    println!("{}Session Commands:{}", ansi::colors::BRIGHT_WHITE, ansi::colors::RESET);
-    println!("  {}/list [limit]{}", ansi::colors::GREEN, ansi::colors::RESET);
-    println!("    List all sessions (or limit to N most recent)");
-    println!("    Aliases: /ls");
-    println!();
+    writeln!(out, "{}Session Commands:{}", ansi::colors::BRIGHT_WHITE, ansi::colors::RESET);
+    writeln!(out, "  {}/list [limit]{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    List all sessions (or limit to N most recent)");
+    writeln!(out, "    Aliases: /ls");
+    writeln!(out);
     
-    println!("  {}/load <session-id>{}", ansi::colors::GREEN, ansi::colors::RESET);
-    println!("    Load and continue a previous session");
-    println!();
+    writeln!(out, "  {}/search <query>{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Find sessions whose title or messages contain the query (ignoring case)");
+    writeln!(out, "    Aliases: /find");
+    writeln!(out);
     
-    println!("  {}/export [session-id] [--format=markdown] [--output=file.md]{}", 
+    writeln!(out, "  {}/load <session-id>{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Load and continue a previous session");
+    writeln!(out);
+    
+    writeln!(out, "  {}/resume{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Load the most recently updated session");
+    writeln!(out, "    Aliases: /last");
+    writeln!(out);
+    
+    writeln!(out, "  {}/export [session-id] [--format=markdown] [--output=file.md [--force] | --stdout]{}", 
         ansi::colors::GREEN, ansi::colors::RESET);
-    println!("    Export session to file (defaults to current session, markdown format)");
-    println!();
+    writeln!(out, "    Export session to file (defaults to current session, markdown format)");
+    writeln!(out, "    Without --output, writes to a temp file and prints its path; --stdout prints inline");
+    writeln!(out, "    Asks before overwriting an existing --output file unless --force is given");
+    writeln!(out);
     
-    println!("  {}/delete <session-id>{}", ansi::colors::GREEN, ansi::colors::RESET);
-    println!("    Delete a session permanently");
-    println!("    Aliases: /rm");
-    println!();
+    writeln!(out, "  {}/new [title]{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Start a new, empty session and switch to it");
+    writeln!(out);
     
-    println!("  {}/info{}", ansi::colors::GREEN, ansi::colors::RESET);
-    println!("    Show current session information");
-    println!("    Aliases: /current");
-    println!();
+    writeln!(out, "  {}/fork [session-id]{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Copy a session (default: current) into a new one and switch to it");
+    writeln!(out);
     
-    println!("  {}/help{}", ansi::colors::GREEN, ansi::colors::RESET);
-    println!("    Show this help message");
-    println!("    Aliases: /?");
-    println!();
+    writeln!(out, "  {}/delete <session-id>{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Delete a session permanently");
+    writeln!(out, "    Aliases: /rm");
+    writeln!(out);
     
-    println!("  {}/exit{}", ansi::colors::GREEN, ansi::colors::RESET);
-    println!("    Exit the application");
-    println!("    Aliases: /quit, /q");
-    println!();
+    writeln!(out, "  {}/rename <session-id> <new title>{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Change a session's title");
+    writeln!(out);
     
-    println!("{}Agent Queries:{}", ansi::colors::BRIGHT_WHITE, ansi::colors::RESET);
-    println!("  Type anything without a / prefix to send to the AI agent");
-    println!("  Example: \"Help me debug this Python code\"");
-    println!();
+    writeln!(out, "  {}/pin <session-id>{}, {}/unpin <session-id>{}", 
+        ansi::colors::GREEN, ansi::colors::RESET, ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Pin a session to the top of /list, or remove its pin");
+    writeln!(out);
+    
+    writeln!(out, "  {}/fav{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    List only pinned sessions");
+    writeln!(out, "    Aliases: /favorites");
+    writeln!(out);
+    
+    writeln!(out, "  {}/info{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Show current session information");
+    writeln!(out, "    Aliases: /current");
+    writeln!(out);
+    
+    writeln!(out, "  {}/models{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    List models the backend offers (● marks the configured one)");
+    writeln!(out);
+    
+    writeln!(out, "  {}/theme [name]{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    List highlighting themes, or switch to one for new code blocks");
+    writeln!(out);
+    
+    writeln!(out, "  {}/system [text|clear]{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Show, set, or clear the system prompt sent with queries");
+    writeln!(out);
+    
+    writeln!(out, "  {}/continue{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Resume the last response if it was cancelled or cut off at max tokens");
+    writeln!(out);
+    
+    writeln!(out, "  {}/temp [0.0-2.0]{}, {}/maxtokens [n]{}",
+        ansi::colors::GREEN, ansi::colors::RESET, ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Show or change the sampling temperature and response token cap for following queries");
+    writeln!(out);
+    
+    writeln!(out, "  {}/loglevel [level]{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Show the log level, or change it (trace, debug, info, warn, error, off)");
+    writeln!(out);
+    
+    writeln!(out, "  {}/exec on|off{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Toggle real tool execution (enabling asks you to type {})", EXEC_CONFIRMATION_WORD);
+    writeln!(out);
+    
+    writeln!(out, "  {}/private{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Toggle privacy mode: input is shown as bullets until submitted (Ctrl+X Ctrl+P)");
+    writeln!(out);
+    
+    writeln!(out, "  {}/safedir [list] | /safedir add|remove <path>{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Show or edit the directories tools may operate in (saved to the config file)");
+    writeln!(out);
+    
+    writeln!(out, "  {}/config [get <key> | set <key> <value>]{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Show all settings, one (e.g. agent.model), or change one and save it to the config file");
+    writeln!(out);
+    
+    writeln!(out, "  {}/history [N] | /history search <text>{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Show the last N inputs (default {}) or those containing text", DEFAULT_HISTORY_LIMIT);
+    writeln!(out);
+    
+    writeln!(out, "  {}/history export|import <file>{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Save input history to a file, or add entries from one");
+    writeln!(out);
+    
+    writeln!(out, "  {}/diag [reset]{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Show connection and backend diagnostics, or zero the drift counters");
+    writeln!(out);
+    
+    writeln!(out, "  {}/lastmsg{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Pretty-print the last raw message from the backend (secrets redacted)");
+    writeln!(out);
+    
+    writeln!(out, "  {}/help{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Show this help message");
+    writeln!(out, "    Aliases: /?");
+    writeln!(out);
+    
+    writeln!(out, "  {}/exit{}", ansi::colors::GREEN, ansi::colors::RESET);
+    writeln!(out, "    Exit the application");
+    writeln!(out, "    Aliases: /quit, /q");
+    writeln!(out);
+    
+    writeln!(out, "{}Agent Queries:{}", ansi::colors::BRIGHT_WHITE, ansi::colors::RESET);
+    writeln!(out, "  Type anything without a / prefix to send to the AI agent");
+    writeln!(out, "  Example: \"Help me debug this Python code\"");
+    writeln!(out);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_display_help_sections() {
+        let mut out = crate::output::BufferOutput::new();
+        display_help(&mut out);
+        let text = out.contents();
+
+        assert!(text.contains("OpenAgent-Terminal Help"));
+        assert!(text.contains("Session Commands:"));
+        assert!(text.contains("Agent Queries:"));
+        for command in ["/list", "/load", "/export", "/delete", "/info", "/help", "/exit"] {
+            assert!(text.contains(command), "help is missing {}", command);
+        }
+    }
+
+    #[test]
+    fn test_display_empty_sessions_list() {
+        let mut out = crate::output::BufferOutput::new();
+        display_sessions_list(&mut out, &[], Utc::now());
+        let text = out.contents();
+        assert!(text.contains("No sessions found."));
+        assert!(text.ends_with("Start a conversation to create your first session!\n"));
+    }
+
+    #[test]
+    fn test_display_sessions_list_relative_time() {
+        use chrono::TimeZone;
+
+        let clock = crate::clock::MockClock::new(Utc.with_ymd_and_hms(2025, 10, 4, 12, 0, 0).unwrap());
+        let session = SessionMetadata {
+            session_id: "a1b2c3d4e5".to_string(),
+            title: "Debugging".to_string(),
+            created_at: Utc.with_ymd_and_hms(2025, 10, 4, 9, 0, 0).unwrap(),
+            updated_at: Utc.with_ymd_and_hms(2025, 10, 4, 11, 15, 0).unwrap(),
+            message_count: 4,
+            total_tokens: 120,
+            pinned: false,
+        };
+
+        let mut out = crate::output::BufferOutput::new();
+        display_sessions_list(&mut out, &[session], crate::clock::Clock::now(&clock));
+        let text = out.contents();
+        assert!(text.contains("a1b2c3d4"));
+        assert!(text.contains("2025-10-04 09:00"));
+        assert!(text.contains("45m ago"));
+    }
+
+    #[test]
+    fn test_display_search_results_shows_snippet() {
+        use chrono::TimeZone;
+
+        let now = Utc.with_ymd_and_hms(2025, 10, 4, 12, 0, 0).unwrap();
+        let metadata = SessionMetadata {
+            session_id: "a1b2c3d4e5".to_string(),
+            title: "Debugging".to_string(),
+            created_at: Utc.with_ymd_and_hms(2025, 10, 4, 9, 0, 0).unwrap(),
+            updated_at: Utc.with_ymd_and_hms(2025, 10, 4, 11, 15, 0).unwrap(),
+            message_count: 4,
+            total_tokens: 120,
+            pinned: false,
+        };
+        let hits = [SessionSearchHit { metadata, snippet: Some("the parser\n  drops comments".to_string()) }];
+
+        let mut out = crate::output::BufferOutput::new();
+        display_search_results(&mut out, "parser", &hits, now);
+        let text = out.contents();
+        assert!(text.contains("Sessions matching \"parser\""));
+        assert!(text.contains("a1b2c3d4 Debugging"));
+        assert!(text.contains("“the parser drops comments”"));
+
+        let mut out = crate::output::BufferOutput::new();
+        display_search_results(&mut out, "lexer", &[], now);
+        assert!(out.contents().contains("No sessions match \"lexer\"."));
+    }
+
     #[test]
     fn test_parse_query() {
         match parse_command("Hello, world!") {
@@ -272,23 +1227,81 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_resume() {
+        assert!(matches!(parse_command("/resume"), Command::ResumeLast));
+        assert!(matches!(parse_command("/last"), Command::ResumeLast));
+    }
+
     #[test]
     fn test_parse_export_session() {
         match parse_command("/export") {
-            Command::ExportSession { session_id: None, format, output_file: None } => {
+            Command::ExportSession { session_id: None, format, output_file: None, to_stdout: false, force: false } => {
                 assert_eq!(format, "markdown");
             },
             _ => panic!("Expected ExportSession command"),
         }
 
         match parse_command("/export abc123 --format=json --output=out.json") {
-            Command::ExportSession { session_id, format, output_file } => {
+            Command::ExportSession { session_id, format, output_file, .. } => {
                 assert_eq!(session_id, Some("abc123".to_string()));
                 assert_eq!(format, "json");
                 assert_eq!(output_file, Some("out.json".to_string()));
             },
             _ => panic!("Expected ExportSession with args"),
         }
+
+        match parse_command("/export --stdout abc123") {
+            Command::ExportSession { session_id, output_file: None, to_stdout: true, .. } => {
+                assert_eq!(session_id, Some("abc123".to_string()));
+            },
+            other => panic!("Expected ExportSession with --stdout, got {:?}", other),
+        }
+
+        match parse_command("/export --output=out.md --force") {
+            Command::ExportSession { session_id: None, output_file, force: true, .. } => {
+                assert_eq!(output_file, Some("out.md".to_string()));
+            },
+            other => panic!("Expected ExportSession with --force, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_export_needs_confirmation() {
+        let dir = tempfile::tempdir().unwrap();
+        let existing = dir.path().join("existing.md");
+        std::fs::write(&existing, "old").unwrap();
+        let missing = dir.path().join("missing.md");
+
+        assert!(export_needs_confirmation(&existing, false));
+        assert!(!export_needs_confirmation(&existing, true));
+        assert!(!export_needs_confirmation(&missing, false));
+        assert!(!export_needs_confirmation(&missing, true));
+    }
+
+    #[test]
+    fn test_parse_export_rejects_stdout_with_output() {
+        assert!(matches!(parse_command("/export --stdout --output=out.md"), Command::Noop));
+        assert!(matches!(parse_command("/export --output=out.md abc123 --stdout"), Command::Noop));
+    }
+
+    #[test]
+    fn test_default_export_file() {
+        let (_, first) = create_default_export_file(Some("abc123"), "markdown").unwrap();
+        let (_, second) = create_default_export_file(Some("abc123"), "markdown").unwrap();
+        let (_, json) = create_default_export_file(None, "json").unwrap();
+        for path in [&first, &second, &json] {
+            assert!(path.starts_with(std::env::temp_dir()));
+            assert!(path.exists());
+        }
+        let name = first.file_name().unwrap().to_str().unwrap();
+        assert!(name.starts_with("openagent-export-abc123-") && name.ends_with(".md"), "{}", name);
+        assert!(json.file_name().unwrap().to_str().unwrap().starts_with("openagent-export-current-"));
+        // Every export gets its own file
+        assert_ne!(first, second);
+        for path in [first, second, json] {
+            std::fs::remove_file(path).unwrap();
+        }
     }
 
     #[test]
@@ -299,6 +1312,105 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_fork_session() {
+        assert!(matches!(parse_command("/new"), Command::NewSession(None)));
+        match parse_command("/new  Refactor the parser ") {
+            Command::NewSession(Some(title)) => assert_eq!(title, "Refactor the parser"),
+            other => panic!("Expected NewSession, got {:?}", other),
+        }
+        match parse_command("/ new Überblick") {
+            Command::NewSession(Some(title)) => assert_eq!(title, "Überblick"),
+            other => panic!("Expected NewSession, got {:?}", other),
+        }
+        assert!(matches!(parse_command("/fork"), Command::ForkSession(None)));
+        match parse_command("/fork abc123") {
+            Command::ForkSession(Some(id)) => assert_eq!(id, "abc123"),
+            other => panic!("Expected ForkSession command, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_search_command() {
+        match parse_command("/search  Parser Notes ") {
+            Command::SearchSessions(query) => assert_eq!(query, "Parser Notes"),
+            other => panic!("Expected SearchSessions command, got {:?}", other),
+        }
+        match parse_command("/ search Größe") {
+            Command::SearchSessions(query) => assert_eq!(query, "Größe"),
+            other => panic!("Expected SearchSessions command, got {:?}", other),
+        }
+        assert!(matches!(parse_command("/find todo"), Command::SearchSessions(_)));
+        assert!(matches!(parse_command("/search"), Command::Noop));
+    }
+
+    #[test]
+    fn test_parse_rename_command() {
+        match parse_command("/rename  abc123 Parser  refactor notes ") {
+            Command::RenameSession { session_id, title } => {
+                assert_eq!(session_id, "abc123");
+                assert_eq!(title, "Parser  refactor notes");
+            }
+            other => panic!("Expected RenameSession command, got {:?}", other),
+        }
+        match parse_command("/ rename 日 New title") {
+            Command::RenameSession { session_id, title } => {
+                assert_eq!(session_id, "日");
+                assert_eq!(title, "New title");
+            }
+            other => panic!("Expected RenameSession command, got {:?}", other),
+        }
+        assert!(matches!(parse_command("/rename"), Command::Noop));
+        assert!(matches!(parse_command("/rename abc123"), Command::Noop));
+    }
+
+    #[test]
+    fn test_parse_pin_commands() {
+        match parse_command("/pin abc123") {
+            Command::PinSession(id) => assert_eq!(id, "abc123"),
+            other => panic!("Expected PinSession command, got {:?}", other),
+        }
+        match parse_command("/unpin abc123") {
+            Command::UnpinSession(id) => assert_eq!(id, "abc123"),
+            other => panic!("Expected UnpinSession command, got {:?}", other),
+        }
+        assert!(matches!(parse_command("/pin"), Command::Noop));
+        assert!(matches!(parse_command("/unpin"), Command::Noop));
+        assert!(matches!(parse_command("/fav"), Command::ListFavorites));
+        assert!(matches!(parse_command("/favorites"), Command::ListFavorites));
+    }
+
+    #[test]
+    fn test_pinned_sessions_listed_first() {
+        use chrono::TimeZone;
+
+        let metadata = |id: &str, hour: u32, pinned: bool| SessionMetadata {
+            session_id: id.to_string(),
+            title: id.to_string(),
+            created_at: Utc.with_ymd_and_hms(2025, 10, 1, 0, 0, 0).unwrap(),
+            updated_at: Utc.with_ymd_and_hms(2025, 10, 4, hour, 0, 0).unwrap(),
+            message_count: 1,
+            total_tokens: 0,
+            pinned,
+        };
+
+        // Backend order is most recent first; the pinned sessions are the oldest
+        let sessions = vec![
+            metadata("newest", 17, false),
+            metadata("pinned-b", 10, true),
+            metadata("middle", 12, false),
+            metadata("pinned-a", 9, true),
+        ];
+        let ids: Vec<&str> = pinned_first(&sessions).iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, ["pinned-b", "pinned-a", "newest", "middle"]);
+
+        let mut out = crate::output::BufferOutput::new();
+        display_sessions_list(&mut out, &sessions, Utc.with_ymd_and_hms(2025, 10, 4, 18, 0, 0).unwrap());
+        let text = out.contents();
+        assert!(text.find("pinned-a").unwrap() < text.find("newest").unwrap());
+        assert!(text.contains("📌 pinned-b"));
+    }
+
     #[test]
     fn test_parse_info() {
         match parse_command("/info") {
@@ -307,6 +1419,374 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_registry_commands_parse() {
+        // Every palette entry must be a real command, not an "Unknown command"
+        for spec in COMMANDS {
+            let input = match spec.name {
+                "load" | "delete" | "pin" | "unpin" | "search" => format!("/{} abc", spec.name),
+                "exec" => "/exec off".to_string(),
+                "rename" => "/rename abc Title".to_string(),
+                _ => format!("/{}", spec.name),
+            };
+            assert!(!matches!(parse_command(&input), Command::Noop), "/{} did not parse", spec.name);
+        }
+        assert_eq!(COMMANDS[0].insert_text(), "/list ");
+        assert_eq!(COMMANDS.iter().find(|c| c.name == "help").unwrap().insert_text(), "/help");
+    }
+
+    #[test]
+    fn test_parse_theme() {
+        assert!(matches!(parse_command("/theme"), Command::ListThemes));
+        match parse_command("/theme gruvbox") {
+            Command::SetTheme(name) => assert_eq!(name, "gruvbox"),
+            other => panic!("Expected SetTheme command, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_display_themes_marks_current() {
+        let mut out = crate::output::BufferOutput::new();
+        display_themes(&mut out, "solarized");
+        let text = out.contents();
+        for theme in ansi::THEMES {
+            assert!(text.contains(theme.name), "missing {}", theme.name);
+        }
+        let marked: Vec<&str> = text.lines().filter(|l| l.contains('●')).collect();
+        assert_eq!(marked.len(), 1);
+        assert!(marked[0].contains("solarized"));
+    }
+
+    #[test]
+    fn test_parse_models() {
+        assert!(matches!(parse_command("/models"), Command::ListModels));
+        assert!(matches!(parse_command("  /models  "), Command::ListModels));
+    }
+
+    #[test]
+    fn test_display_models_marks_current() {
+        let models = [
+            ModelInfo { name: "gpt-4".into(), context_window: Some(128_000), description: Some("Capable".into()) },
+            ModelInfo { name: "mock".into(), context_window: None, description: None },
+        ];
+        let mut out = crate::output::BufferOutput::new();
+        display_models(&mut out, Some(&models), "mock");
+        let text = out.contents();
+        let mock_line = text.lines().find(|l| l.contains("mock")).unwrap();
+        assert!(mock_line.contains('●'));
+        assert!(!text.lines().find(|l| l.contains("gpt-4")).unwrap().contains('●'));
+        assert!(text.contains("128k ctx"));
+
+        // Unsupported backends still show the configured model
+        let mut out = crate::output::BufferOutput::new();
+        display_models(&mut out, None, "mock");
+        assert!(out.contents().contains("mock (configured)"));
+    }
+
+    #[test]
+    fn test_parse_diag() {
+        assert!(matches!(parse_command("/diag"), Command::Diagnostics));
+        assert!(matches!(parse_command("/diag reset"), Command::ResetDiagnostics));
+        assert!(matches!(parse_command("/diag bogus"), Command::Noop));
+    }
+
+    #[test]
+    fn test_parse_lastmsg() {
+        assert!(matches!(parse_command("/lastmsg"), Command::LastMessage));
+    }
+
+    #[test]
+    fn test_format_last_message_redacts() {
+        let raw = r#"{"jsonrpc":"2.0","method":"context.auth","params":{"password":"hunter2","prompt":"Password"}}"#;
+        let text = format_last_message(raw);
+        assert!(!text.contains("hunter2"));
+        assert!(text.contains("<redacted>"));
+        assert!(text.contains("\n  \"method\": \"context.auth\""));
+        assert_eq!(format_last_message("not json"), "not json");
+    }
+
+    #[test]
+    fn test_parse_system_prompt() {
+        match parse_command("/system You are  a pirate.") {
+            Command::SetSystemPrompt(Some(text)) => assert_eq!(text, "You are  a pirate."),
+            other => panic!("Expected SetSystemPrompt, got {:?}", other),
+        }
+        match parse_command("/ system  Réponds en français") {
+            Command::SetSystemPrompt(Some(text)) => assert_eq!(text, "Réponds en français"),
+            other => panic!("Expected SetSystemPrompt, got {:?}", other),
+        }
+        assert!(matches!(parse_command("/system clear"), Command::SetSystemPrompt(None)));
+        assert!(matches!(parse_command("/system"), Command::ShowSystemPrompt));
+    }
+
+    #[test]
+    fn test_parse_exec() {
+        assert!(matches!(parse_command("/exec on"), Command::ToggleExec(true)));
+        assert!(matches!(parse_command("/exec off"), Command::ToggleExec(false)));
+        assert!(matches!(parse_command("/exec"), Command::Noop));
+    }
+
+    #[test]
+    fn test_parse_continue() {
+        assert!(matches!(parse_command("/continue"), Command::Continue));
+        assert!(matches!(parse_command("  /continue  "), Command::Continue));
+    }
+
+    #[test]
+    fn test_parse_sampling() {
+        assert!(matches!(parse_command("/temp"), Command::ShowTemperature));
+        assert!(matches!(parse_command("/temp 0.2"), Command::SetTemperature(t) if t == 0.2));
+        assert!(matches!(parse_command("/temp 2"), Command::SetTemperature(t) if t == 2.0));
+        assert!(matches!(parse_command("/temp hot"), Command::Noop));
+        assert!(matches!(parse_command("/maxtokens"), Command::ShowMaxTokens));
+        assert!(matches!(parse_command("/maxtokens 4096"), Command::SetMaxTokens(4096)));
+        assert!(matches!(parse_command("/maxtokens -1"), Command::Noop));
+    }
+
+    #[test]
+    fn test_parse_sampling_out_of_range() {
+        // Rejected by the same check config loading uses
+        assert!(matches!(parse_command("/temp 2.5"), Command::Noop));
+        assert!(matches!(parse_command("/temp -0.5"), Command::Noop));
+        assert!(matches!(parse_command("/maxtokens 0"), Command::Noop));
+        assert_eq!(
+            AgentConfig::validate_temperature(2.5).unwrap_err(),
+            "Temperature must be between 0.0 and 2.0 (got 2.5)"
+        );
+    }
+
+    #[test]
+    fn test_parse_loglevel() {
+        assert!(matches!(parse_command("/loglevel"), Command::ShowLogLevel));
+        assert!(matches!(parse_command("/loglevel debug"), Command::SetLogLevel(LogLevel::Debug)));
+        assert!(matches!(parse_command("/loglevel WARN"), Command::SetLogLevel(LogLevel::Warn)));
+        assert!(matches!(parse_command("/loglevel off"), Command::SetLogLevel(LogLevel::Off)));
+        assert!(matches!(parse_command("/loglevel loud"), Command::Noop));
+    }
+
+    #[test]
+    fn test_parse_safedir() {
+        assert_eq!(parse_safedir("/safedir"), Some(SafeDirAction::List));
+        assert_eq!(parse_safedir("/safedir list"), Some(SafeDirAction::List));
+        assert_eq!(parse_safedir("/safedir add ~/projects"), Some(SafeDirAction::Add("~/projects".to_string())));
+        assert_eq!(parse_safedir("/safedir add /tmp/my dir"), Some(SafeDirAction::Add("/tmp/my dir".to_string())));
+        assert_eq!(parse_safedir("/ safedir add  ~/мои проекты"), Some(SafeDirAction::Add("~/мои проекты".to_string())));
+        assert_eq!(parse_safedir("/safedir remove ~"), Some(SafeDirAction::Remove("~".to_string())));
+        assert_eq!(parse_safedir("/safedir rm /tmp"), Some(SafeDirAction::Remove("/tmp".to_string())));
+        assert!(matches!(parse_command("/safedir add"), Command::Noop));
+        assert!(matches!(parse_command("/safedir remove"), Command::Noop));
+        assert!(matches!(parse_command("/safedir clear"), Command::Noop));
+    }
+
+    #[test]
+    fn test_parse_config() {
+        assert!(matches!(parse_command("/config"), Command::Config(ConfigAction::Show)));
+        match parse_command("/config get agent.model") {
+            Command::Config(action) => assert_eq!(action, ConfigAction::Get("agent.model".to_string())),
+            other => panic!("Expected Config command, got {:?}", other),
+        }
+        match parse_command("/config set agent.system_prompt  Be brief and precise ") {
+            Command::Config(action) => assert_eq!(action, ConfigAction::Set {
+                key: "agent.system_prompt".to_string(),
+                value: "Be brief and precise".to_string(),
+            }),
+            other => panic!("Expected Config command, got {:?}", other),
+        }
+        match parse_command("/ config set clé  valeur  à garder ") {
+            Command::Config(action) => assert_eq!(action, ConfigAction::Set {
+                key: "clé".to_string(),
+                value: "valeur  à garder".to_string(),
+            }),
+            other => panic!("Expected Config command, got {:?}", other),
+        }
+        assert!(matches!(parse_command("/config get"), Command::Noop));
+        assert!(matches!(parse_command("/config set agent.model"), Command::Noop));
+        assert!(matches!(parse_command("/config reset"), Command::Noop));
+    }
+
+    #[test]
+    fn test_display_setting() {
+        let config = Config::default();
+        let mut out = crate::output::BufferOutput::new();
+        display_setting(&mut out, "agent.model", &config.get_path("agent.model").unwrap());
+        display_setting(&mut out, "agent", &config.get_path("agent").unwrap());
+        let text = out.contents();
+        assert!(text.starts_with("agent.model = \"mock\"\n"));
+        assert!(text.contains("[agent]"));
+        assert!(text.contains("max_tokens = 2000"));
+    }
+
+    fn parse_safedir(input: &str) -> Option<SafeDirAction> {
+        match parse_command(input) {
+            Command::SafeDir(action) => Some(action),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_safe_directory_add_remove() {
+        let home = Path::new("/home/user");
+        assert_eq!(expand_home("~", Some(home)), "/home/user");
+        assert_eq!(expand_home("~/src", Some(home)), "/home/user/src");
+        assert_eq!(expand_home("~other/src", Some(home)), "~other/src");
+        assert_eq!(expand_home("~/src", None), "~/src");
+
+        let mut dirs = vec!["~".to_string(), ".".to_string()];
+        assert!(add_safe_directory(&mut dirs, "~/src", Some(home)));
+        assert_eq!(dirs, ["~", ".", "/home/user/src"]);
+        // Already listed, however it's spelled
+        assert!(!add_safe_directory(&mut dirs, "/home/user/src/", Some(home)));
+        assert!(!add_safe_directory(&mut dirs, "/home/user", Some(home)));
+
+        assert!(remove_safe_directory(&mut dirs, "/home/user", Some(home)));
+        assert_eq!(dirs, [".", "/home/user/src"]);
+        assert!(remove_safe_directory(&mut dirs, "~/src", Some(home)));
+        assert_eq!(dirs, ["."]);
+        assert!(!remove_safe_directory(&mut dirs, "~/src", Some(home)));
+    }
+
+    #[test]
+    fn test_parse_private() {
+        assert!(matches!(parse_command("/private"), Command::TogglePrivate));
+    }
+
+    #[test]
+    fn test_approval_style() {
+        assert_eq!(approval_style("low", true), ApprovalStyle::Compact);
+        assert_eq!(approval_style("medium", true), ApprovalStyle::Compact);
+        assert_eq!(approval_style("high", true), ApprovalStyle::Full);
+        assert_eq!(approval_style("unknown", true), ApprovalStyle::Full);
+        for risk in ["low", "medium", "high", "unknown"] {
+            assert_eq!(approval_style(risk, false), ApprovalStyle::Full);
+        }
+    }
+
+    #[test]
+    fn test_should_confirm_exit() {
+        // Disabled: never ask
+        assert!(!should_confirm_exit(false, true, true));
+        assert!(!should_confirm_exit(false, false, false));
+        // Enabled: ask only when there is something to lose
+        assert!(should_confirm_exit(true, true, false));
+        assert!(should_confirm_exit(true, false, true));
+        assert!(should_confirm_exit(true, true, true));
+        assert!(!should_confirm_exit(true, false, false));
+    }
+
+    #[test]
+    fn test_exec_toggle_requires_confirmation() {
+        // Enabling needs the exact word
+        assert!(exec_toggle_allowed(true, false, Some("EXECUTE")));
+        assert!(exec_toggle_allowed(true, false, Some(" EXECUTE ")));
+        assert!(!exec_toggle_allowed(true, false, Some("execute")));
+        assert!(!exec_toggle_allowed(true, false, Some("yes")));
+        assert!(!exec_toggle_allowed(true, false, None));
+
+        // Disabling, or enabling when already on, needs nothing
+        assert!(exec_toggle_allowed(false, true, None));
+        assert!(exec_toggle_allowed(true, true, None));
+    }
+
+    #[test]
+    fn test_parse_history() {
+        assert!(matches!(parse_command("/history"), Command::History { limit: None, search: None }));
+        assert!(matches!(parse_command("/history 50"), Command::History { limit: Some(50), search: None }));
+        // An invalid count is a usage error rather than the default
+        for bad in ["/history abc", "/history -5", "/history 0"] {
+            assert!(matches!(parse_command(bad), Command::Noop), "{}", bad);
+        }
+        match parse_command("/history search foo bar") {
+            Command::History { limit: None, search: Some(text) } => assert_eq!(text, "foo bar"),
+            other => panic!("Expected History search, got {:?}", other),
+        }
+        match parse_command("/ history  search  café  crème ") {
+            Command::History { limit: None, search: Some(text) } => assert_eq!(text, "café  crème"),
+            other => panic!("Expected History search, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_inline_response_text() {
+        use serde_json::json;
+        assert_eq!(inline_response_text(&json!({"content": "hello"})), "hello");
+        assert_eq!(inline_response_text(&json!({"response": "hi", "status": "ok"})), "hi");
+        assert_eq!(inline_response_text(&json!("plain")), "plain");
+        assert!(inline_response_text(&json!({"other": 1})).contains("\"other\": 1"));
+    }
+
+    #[test]
+    fn test_parse_history_export_import() {
+        match parse_command("/history export ~/history.json") {
+            Command::ExportHistory(path) => assert_eq!(path, "~/history.json"),
+            other => panic!("Expected ExportHistory, got {:?}", other),
+        }
+        match parse_command("/history import backup.json") {
+            Command::ImportHistory(path) => assert_eq!(path, "backup.json"),
+            other => panic!("Expected ImportHistory, got {:?}", other),
+        }
+        // A path is required
+        assert!(matches!(parse_command("/history export"), Command::Noop));
+        assert!(matches!(parse_command("/history import"), Command::Noop));
+    }
+
+    #[test]
+    fn test_display_history() {
+        let mut out = crate::output::BufferOutput::new();
+        display_history(&mut out, &[(9, "ls"), (10, "git log")], None, 80);
+        let text = out.contents();
+        assert!(text.contains("Recent commands:"));
+        assert!(text.contains(" 9"));
+        assert!(text.contains("10"));
+        assert!(text.contains("git log"));
+    }
+
+    #[test]
+    fn test_history_entry_truncates_wide_chars() {
+        // 2 indent + 1 index + 2 gap leaves 9 columns: four 2-column characters and the ellipsis
+        let line = ansi::strip_ansi(&format_history_entry(3, 1, "日本語のテキストです", 14));
+        assert_eq!(line, "  3  日本語の…");
+        assert_eq!(ansi::display_width(&line), 14);
+
+        // Short entries are kept, multi-line ones folded onto one line
+        assert_eq!(ansi::strip_ansi(&format_history_entry(3, 1, "ls", 14)), "  3  ls");
+        assert_eq!(ansi::strip_ansi(&format_history_entry(3, 1, "a\nb\n", 14)), "  3  a↵b");
+    }
+
+    #[test]
+    fn test_history_index_alignment() {
+        let mut out = crate::output::BufferOutput::new();
+        display_history(&mut out, &[(9, "ls"), (10, "git log")], None, 80);
+        let text = ansi::strip_ansi(&out.contents());
+        let lines: Vec<&str> = text.lines().skip(1).collect();
+        assert_eq!(lines, ["   9  ls", "  10  git log"]);
+    }
+
+    #[test]
+    fn test_finish_footer() {
+        assert_eq!(finish_footer(&FinishReason::Stop, false), None);
+        assert_eq!(finish_footer(&FinishReason::Other, false), None);
+
+        let truncated = finish_footer(&FinishReason::Length, false).unwrap();
+        assert!(truncated.ends_with("response truncated (max tokens), /continue resumes it"));
+        let filtered = finish_footer(&FinishReason::ContentFilter, false).unwrap();
+        assert!(filtered.ends_with("stopped by content filter"));
+        let errored = finish_footer(&FinishReason::Error, false).unwrap();
+        assert!(errored.ends_with("stopped by an error while generating"));
+        assert!(!errored.contains('\x1b'));
+
+        let colored = finish_footer(&FinishReason::ContentFilter, true).unwrap();
+        assert!(colored.starts_with(ansi::colors::BRIGHT_BLACK) && colored.ends_with(ansi::colors::RESET));
+    }
+
+    #[test]
+    fn test_format_uptime() {
+        use std::time::Duration;
+        assert_eq!(format_uptime(Duration::from_secs(42)), "42s");
+        assert_eq!(format_uptime(Duration::from_secs(125)), "2m 05s");
+        assert_eq!(format_uptime(Duration::from_secs(2 * 3600 + 5 * 60)), "2h 05m");
+        assert_eq!(format_uptime(Duration::from_secs(3 * 86400 + 4 * 3600)), "3d 4h");
+    }
+
     #[test]
     fn test_parse_help() {
         match parse_command("/help") {
@@ -315,6 +1795,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_empty_input_is_noop() {
+        assert!(matches!(parse_command(""), Command::Noop));
+        assert!(matches!(parse_command("   "), Command::Noop));
+        assert!(matches!(parse_command("/"), Command::Noop));
+        assert!(matches!(parse_command("  /  "), Command::Noop));
+        // Invalid commands report their own usage instead of the help screen
+        assert!(matches!(parse_command("/nosuchcommand"), Command::Noop));
+        assert!(matches!(parse_command("/load"), Command::Noop));
+    }
+
+    #[test]
+    fn test_normalize_trims_trailing_whitespace() {
+        let opts = SubmitOptions::default();
+        assert_eq!(normalize_submission("hello world  \t", &opts), "hello world");
+        assert_eq!(normalize_submission("line one  \nline two\t\n\n", &opts), "line one\nline two");
+    }
+
+    #[test]
+    fn test_normalize_keeps_whitespace_when_disabled() {
+        let opts = SubmitOptions { trim_trailing_whitespace: false, collapse_blank_lines: false };
+        assert_eq!(normalize_submission("hello  ", &opts), "hello  ");
+    }
+
+    #[test]
+    fn test_normalize_collapses_blank_lines() {
+        let opts = SubmitOptions { trim_trailing_whitespace: true, collapse_blank_lines: true };
+        assert_eq!(normalize_submission("a\n\n\n  \nb\n\nc", &opts), "a\n\nb\n\nc");
+
+        // Collapsing is off by default
+        let opts = SubmitOptions::default();
+        assert_eq!(normalize_submission("a\n\n\nb", &opts), "a\n\n\nb");
+    }
+
+    #[test]
+    fn test_normalize_slash_commands_not_collapsed() {
+        let opts = SubmitOptions { trim_trailing_whitespace: true, collapse_blank_lines: true };
+        assert_eq!(normalize_submission("/export  \n\n\nfoo", &opts), "/export\n\n\nfoo");
+    }
+
     #[test]
     fn test_parse_exit() {
         match parse_command("/exit") {
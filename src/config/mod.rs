@@ -3,11 +3,13 @@
 // Provides TOML-based configuration with sensible defaults.
 
 use anyhow::{Context, Result};
+use crate::error::TerminalError;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
 
 /// Complete configuration for OpenAgent-Terminal
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Config {
     /// Terminal-specific settings
     pub terminal: TerminalConfig,
@@ -20,6 +22,22 @@ pub struct Config {
     
     /// Tool execution settings
     pub tools: ToolsConfig,
+    
+    /// Shell hooks run on lifecycle events
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    
+    /// Guardrail that overrides execution-related settings
+    #[serde(default)]
+    pub safe_mode: SafeModeConfig,
+    
+    /// Session loading behavior
+    #[serde(default)]
+    pub session: SessionConfig,
+    
+    /// File this was loaded from, where settings changed at runtime are saved
+    #[serde(skip)]
+    file: Option<PathBuf>,
 }
 
 /// Terminal display and rendering settings
@@ -39,6 +57,89 @@ pub struct TerminalConfig {
     
     /// Enable syntax highlighting in blocks
     pub syntax_highlighting: bool,
+    
+    /// Trim trailing whitespace from submitted input
+    #[serde(default = "default_true")]
+    pub trim_trailing_whitespace: bool,
+    
+    /// Collapse runs of blank lines in multi-line input into one
+    #[serde(default)]
+    pub collapse_blank_lines: bool,
+    
+    /// Cap on the width of code and diff blocks (unset = terminal width)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_content_width: Option<usize>,
+    
+    /// Markdown file that every query and streamed response is appended to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcript_file: Option<PathBuf>,
+    
+    /// What counts as a word for Ctrl+←/→ and Ctrl+W
+    #[serde(default)]
+    pub word_boundary: WordBoundary,
+    
+    /// Ask before exiting while a response streams or the conversation has messages
+    #[serde(default)]
+    pub confirm_on_exit: bool,
+    
+    /// Icons in the UI: emoji, or plain ASCII markers for terminals without emoji fonts
+    #[serde(default)]
+    pub glyphs: GlyphMode,
+    
+    /// Cursor shape while the terminal is running (`default` leaves it alone)
+    #[serde(default)]
+    pub cursor_style: CursorStyle,
+    
+    /// Run in the alternate screen; when false output stays in the scrollback on exit
+    #[serde(default = "default_true")]
+    pub use_alternate_screen: bool,
+    
+    /// Show a short note instead of a streamed code block identical to the one just before it
+    #[serde(default)]
+    pub dedupe_code_blocks: bool,
+    
+    /// Sleep until input arrives at the prompt; when false, poll for it every 100ms
+    #[serde(default = "default_true")]
+    pub event_driven_input: bool,
+}
+
+/// Cursor shape and blink, set with DECSCUSR
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CursorStyle {
+    /// Keep whatever the terminal emulator uses
+    #[default]
+    Default,
+    Block,
+    BlinkingBlock,
+    Underline,
+    BlinkingUnderline,
+    Beam,
+    BlinkingBeam,
+}
+
+/// How the UI draws its icons
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GlyphMode {
+    /// Emoji (🤖, 🔒, ✅, ...)
+    #[default]
+    Emoji,
+    /// Bracketed ASCII markers (`AI:`, `[LOCK]`, `[OK]`, ...)
+    Ascii,
+}
+
+/// How the line editor splits input into words
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WordBoundary {
+    /// Unicode word segmentation (UAX #29)
+    #[default]
+    Unicode,
+    /// Anything between whitespace is one word
+    Whitespace,
+    /// Runs of letters and digits, also split at camelCase humps (for code)
+    Subword,
 }
 
 /// AI agent configuration
@@ -58,6 +159,39 @@ pub struct AgentConfig {
     
     /// Temperature for LLM sampling (0.0 - 2.0)
     pub temperature: f32,
+    
+    /// System prompt sent with every query (unset = backend default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    
+    /// Retry the initial query request on transient failures
+    #[serde(default)]
+    pub retry_queries: bool,
+    
+    /// Seconds to wait for a response to an agent query
+    #[serde(default = "default_query_timeout_secs")]
+    pub query_timeout_secs: u64,
+    
+    /// Seconds to wait for a response to control requests (initialize, session.*)
+    #[serde(default = "default_control_timeout_secs")]
+    pub control_timeout_secs: u64,
+    
+    /// Seconds to wait for a response to any other request
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+    
+    /// Reconnect and re-initialize when the backend connection drops
+    #[serde(default)]
+    pub auto_reconnect: bool,
+    
+    /// Most agent queries sent per minute (0 = unlimited); control RPCs are exempt
+    #[serde(default)]
+    pub max_queries_per_minute: u32,
+    
+    /// Send the previous turn's ID as `parent` with follow-up queries
+    /// (only to backends advertising the `threading` capability)
+    #[serde(default)]
+    pub thread_queries: bool,
 }
 
 /// Keyboard shortcut configuration
@@ -77,6 +211,22 @@ pub struct Keybindings {
     
     /// Show command history
     pub show_history: String,
+    
+    /// Load the next more recently updated session
+    #[serde(default = "default_previous_session_key")]
+    pub previous_session: String,
+    
+    /// Load the next older session
+    #[serde(default = "default_next_session_key")]
+    pub next_session: String,
+}
+
+fn default_previous_session_key() -> String {
+    "Alt+Up".to_string()
+}
+
+fn default_next_session_key() -> String {
+    "Alt+Down".to_string()
 }
 
 /// Tool execution configuration
@@ -90,9 +240,247 @@ pub struct ToolsConfig {
     
     /// Timeout for shell commands in seconds
     pub command_timeout: u64,
+    
+    /// Deny high-risk tool requests without prompting
+    #[serde(default)]
+    pub auto_deny_high_risk: bool,
+    
+    /// Ask about low and medium-risk tools on one line instead of the full panel
+    #[serde(default)]
+    pub compact_approvals: bool,
+}
+
+/// Shell hook configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HooksConfig {
+    /// Command run after each completed response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_response: Option<String>,
+}
+
+/// Safe mode configuration (also enabled by `--safe`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SafeModeConfig {
+    /// Force demo execution, approvals, and high-risk auto-deny
+    #[serde(default)]
+    pub enabled: bool,
+    
+    /// Also pin the model to "mock" so no network model is called
+    #[serde(default = "default_true")]
+    pub mock_model: bool,
+}
+
+/// Session configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionConfig {
+    /// What loading a session does to the current conversation context
+    #[serde(default)]
+    pub on_load: LoadMode,
+    
+    /// Create a session (`session.new`) before the first query if none is active
+    #[serde(default = "default_true")]
+    pub auto_create_session: bool,
+    
+    /// Show the first question and last answer when a session is loaded
+    #[serde(default = "default_true")]
+    pub preview_on_load: bool,
+}
+
+/// How a loaded session's messages combine with the current context
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoadMode {
+    /// Start over from the loaded session's messages
+    #[default]
+    Replace,
+    /// Keep the current conversation and add the loaded messages after it
+    Append,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            on_load: LoadMode::default(),
+            auto_create_session: true,
+            preview_on_load: true,
+        }
+    }
+}
+
+impl Default for SafeModeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mock_model: true,
+        }
+    }
+}
+
+/// Pick the config directory from the given environment
+///
+/// Follows the XDG spec: `xdg_config_home` only counts if it is absolute,
+/// otherwise `home/.config`. `None` when neither is known: there is no
+/// shared fallback, since a config there (hooks included) could be planted
+/// by another user.
+pub fn resolve_config_dir(xdg_config_home: Option<&Path>, home: Option<&Path>) -> Option<PathBuf> {
+    if let Some(xdg) = xdg_config_home.filter(|p| p.is_absolute()) {
+        return Some(xdg.to_path_buf());
+    }
+    home.filter(|p| !p.as_os_str().is_empty()).map(|home| home.join(".config"))
+}
+
+/// The config directory for this environment
+///
+/// Every config path is resolved through here.
+pub fn config_dir() -> Option<PathBuf> {
+    // macOS and Windows keep their native locations while they can be found
+    #[cfg(not(target_os = "linux"))]
+    if let Some(dir) = dirs::config_dir() {
+        return Some(dir);
+    }
+    
+    let xdg = std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from);
+    let home = std::env::var_os("HOME").map(PathBuf::from).or_else(dirs::home_dir);
+    resolve_config_dir(xdg.as_deref(), home.as_deref())
+}
+
+/// Pick the data directory from the given environment
+///
+/// `xdg_data_home` only counts if it is absolute, otherwise `home/.local/share`.
+/// `None` when neither is known.
+pub fn resolve_data_dir(xdg_data_home: Option<&Path>, home: Option<&Path>) -> Option<PathBuf> {
+    if let Some(xdg) = xdg_data_home.filter(|p| p.is_absolute()) {
+        return Some(xdg.to_path_buf());
+    }
+    home.filter(|p| !p.as_os_str().is_empty()).map(|home| home.join(".local").join("share"))
+}
+
+/// The data directory for state kept between runs, such as input history
+pub fn data_dir() -> Option<PathBuf> {
+    // macOS and Windows keep their native locations while they can be found
+    #[cfg(not(target_os = "linux"))]
+    if let Some(dir) = dirs::data_dir() {
+        return Some(dir);
+    }
+    
+    let xdg = std::env::var_os("XDG_DATA_HOME").map(PathBuf::from);
+    let home = std::env::var_os("HOME").map(PathBuf::from).or_else(dirs::home_dir);
+    resolve_data_dir(xdg.as_deref(), home.as_deref())
+}
+
+/// Where input history is kept between sessions, if there is a data directory
+pub fn history_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("openagent-terminal").join("history"))
 }
 
 impl Config {
+    /// Apply safe mode overrides, if enabled
+    ///
+    /// Must run after all other config and CLI overrides so that safe mode
+    /// wins over conflicting values.
+    pub fn apply_safe_mode(&mut self) {
+        if !self.safe_mode.enabled {
+            return;
+        }
+        self.tools.enable_real_execution = false;
+        self.tools.auto_deny_high_risk = true;
+        self.agent.require_approval = true;
+        if self.safe_mode.mock_model {
+            self.agent.model = "mock".to_string();
+        }
+    }
+    
+    /// Value at a dotted path such as `agent.model`
+    ///
+    /// Only the sections in `EDITABLE_SECTIONS` are reachable. Unset optional
+    /// settings have no value and report as not set.
+    pub fn get_path(&self, path: &str) -> Result<toml::Value, String> {
+        let (section, keys) = split_setting_path(path)?;
+        let mut value = toml::Value::try_from(self).map_err(|e| e.to_string())?;
+        for key in std::iter::once(section).chain(keys.iter().copied()) {
+            value = match value {
+                toml::Value::Table(mut table) => table.remove(key),
+                _ => None,
+            }
+            .ok_or_else(|| format!("{} is not set", path))?;
+        }
+        Ok(value)
+    }
+
+    /// Change the setting at a dotted path, parsing `raw` to match its type
+    ///
+    /// Strings are taken as typed; other values are read as TOML (`true`,
+    /// `42`, `["a", "b"]`). Unknown settings, type mismatches and invalid
+    /// `[agent]` values are rejected and leave the config unchanged, as are
+    /// the `PROTECTED_SETTINGS`.
+    pub fn set_path(&mut self, path: &str, raw: &str) -> Result<(), String> {
+        let (section, keys) = split_setting_path(path)?;
+        if let Some((_, instead)) = PROTECTED_SETTINGS.iter().find(|(protected, _)| *protected == path) {
+            return Err(format!("{} can't be changed with /config; {}", path, instead));
+        }
+        let Some((leaf, parents)) = keys.split_last() else {
+            return Err(format!("{} is a section; name a setting in it, e.g. {}.<key>", path, path));
+        };
+
+        let mut root = toml::Value::try_from(&*self).map_err(|e| e.to_string())?;
+        let mut table = root.as_table_mut().and_then(|t| t.get_mut(section)).and_then(toml::Value::as_table_mut)
+            .ok_or_else(|| format!("Unknown section: {}", section))?;
+        for key in parents {
+            table = table.get_mut(*key).and_then(toml::Value::as_table_mut)
+                .ok_or_else(|| format!("Unknown setting: {}", path))?;
+        }
+
+        let value = parse_setting_value(table.get(*leaf), raw)
+            .map_err(|e| format!("Invalid value for {}: {}", path, e))?;
+        table.insert(leaf.to_string(), value.clone());
+
+        let updated: Config = root.try_into().map_err(|e: toml::de::Error| format!("Invalid value for {}: {}", path, e.message()))?;
+        let problems = updated.invalid_settings();
+        if !problems.is_empty() {
+            return Err(problems.join("; "));
+        }
+        // Serde skips unknown keys, so a misspelled setting only shows up as missing here
+        if updated.get_path(path).ok() != Some(value) {
+            return Err(format!("Unknown setting: {}", path));
+        }
+        *self = Config { file: self.file.take(), ..updated };
+        Ok(())
+    }
+
+    /// Check value ranges, listing every invalid setting in the error
+    pub fn validate(&self) -> std::result::Result<(), TerminalError> {
+        let problems = self.invalid_settings();
+        if problems.is_empty() {
+            return Ok(());
+        }
+        let list: Vec<String> = problems.iter().map(|p| format!("  - {}", p)).collect();
+        Err(TerminalError::ConfigError(format!("invalid settings:\n{}", list.join("\n"))))
+    }
+
+    /// One `path: reason` entry per out-of-range setting
+    fn invalid_settings(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if let Err(e) = AgentConfig::validate_temperature(self.agent.temperature) {
+            problems.push(format!("agent.temperature: {}", e));
+        }
+        if let Err(e) = AgentConfig::validate_max_tokens(self.agent.max_tokens) {
+            problems.push(format!("agent.max_tokens: {}", e));
+        }
+        if !FONT_SIZE_RANGE.contains(&self.terminal.font_size) {
+            problems.push(format!(
+                "terminal.font_size: must be between {} and {} (got {})",
+                FONT_SIZE_RANGE.start(), FONT_SIZE_RANGE.end(), self.terminal.font_size,
+            ));
+        }
+        if self.terminal.scrollback_lines == 0 {
+            problems.push("terminal.scrollback_lines: must be at least 1".to_string());
+        }
+        if self.tools.command_timeout == 0 {
+            problems.push("tools.command_timeout: must be at least 1 second".to_string());
+        }
+        problems
+    }
+
     /// Load configuration from file, or use defaults if not found
     pub fn load() -> Result<Self> {
         let config_path = Self::config_path()?;
@@ -103,6 +491,7 @@ impl Config {
                 .context("Failed to read config file")?;
             let config: Config = toml::from_str(&contents)
                 .context("Failed to parse config file")?;
+            config.validate()?;
             Ok(config)
         } else {
             log::info!("No config file found, using defaults");
@@ -119,11 +508,61 @@ impl Config {
             .context("Failed to read config file")?;
         let config: Config = toml::from_str(&contents)
             .context("Failed to parse config file")?;
-        Ok(config)
+        config.validate()?;
+        Ok(config.with_file(path))
+    }
+    
+    /// Save settings changed at runtime to `path` rather than the default location
+    pub fn with_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.file = Some(path.into());
+        self
+    }
+    
+    /// The config file this run reads and saves settings to
+    pub fn file_path(&self) -> Result<PathBuf> {
+        self.file.clone().map_or_else(Self::config_path, Ok)
+    }
+    
+    /// Write the current value of one setting (a dotted path) to the config file
+    ///
+    /// Only that key changes on disk: comments, formatting and the other
+    /// settings are kept, so this run's CLI overrides aren't saved. A missing
+    /// file is created from the defaults, so it still loads on its own.
+    pub fn save_setting(&self, path: &str) -> Result<()> {
+        let value = self.get_path(path).map_err(anyhow::Error::msg)?;
+        let file = self.file_path()?;
+        let contents = match std::fs::read_to_string(&file) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                toml::to_string_pretty(&Self::default()).context("Failed to serialize config")?
+            }
+            Err(e) => return Err(e).context("Failed to read config file"),
+        };
+        let mut doc: toml_edit::DocumentMut = contents.parse().context("Failed to parse config file")?;
+        
+        let (section, keys) = split_setting_path(path).map_err(anyhow::Error::msg)?;
+        let mut item = &mut doc[section];
+        for key in keys {
+            item = &mut item[key];
+        }
+        let mut updated = edit_item(&value)?;
+        // Keep a trailing comment on the line
+        if let (Some(old), Some(new)) = (item.as_value(), updated.as_value_mut()) {
+            *new.decor_mut() = old.decor().clone();
+        }
+        *item = updated;
+        
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)
+                .context("Failed to create config directory")?;
+        }
+        std::fs::write(&file, doc.to_string())
+            .context("Failed to write config file")?;
+        log::info!("Saved {} to: {:?}", path, file);
+        Ok(())
     }
     
     /// Save configuration to file
-    #[allow(dead_code)] // Will be used for config generation
     pub fn save(&self) -> Result<()> {
         let config_path = Self::config_path()?;
         
@@ -144,8 +583,8 @@ impl Config {
     
     /// Get the path to the configuration file
     pub fn config_path() -> Result<PathBuf> {
-        let config_dir = dirs::config_dir()
-            .context("Could not determine config directory")?;
+        let config_dir = config_dir()
+            .context("Could not determine config directory (neither XDG_CONFIG_HOME nor HOME is set); pass --config <path>")?;
         Ok(config_dir.join("openagent-terminal").join("config.toml"))
     }
     
@@ -159,17 +598,87 @@ impl Config {
     }
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            terminal: TerminalConfig::default(),
-            agent: AgentConfig::default(),
-            keybindings: Keybindings::default(),
-            tools: ToolsConfig::default(),
+/// Font sizes `Config::validate` accepts, in points
+const FONT_SIZE_RANGE: RangeInclusive<u16> = 6..=72;
+
+/// Config sections `/config get` and `/config set` can reach
+pub const EDITABLE_SECTIONS: &[&str] = &["terminal", "agent", "keybindings", "tools"];
+
+/// Settings in those sections that guard tool execution, with where to change them
+///
+/// `/exec on` asks for confirmation and is refused in safe mode, which a
+/// plain `set_path` would skip.
+pub const PROTECTED_SETTINGS: &[(&str, &str)] = &[
+    ("tools.enable_real_execution", "use /exec on or /exec off"),
+    ("tools.auto_deny_high_risk", "edit the config file"),
+];
+
+/// A setting's value as a `toml_edit` item, by way of a one-key document
+fn edit_item(value: &toml::Value) -> Result<toml_edit::Item> {
+    let table = toml::Table::from_iter([("v".to_string(), value.clone())]);
+    let mut doc: toml_edit::DocumentMut = toml::to_string(&table)
+        .context("Failed to serialize setting")?
+        .parse()
+        .context("Failed to serialize setting")?;
+    Ok(doc.remove("v").unwrap_or_default())
+}
+
+/// Split `section.key...` into the section and the keys under it
+fn split_setting_path(path: &str) -> Result<(&str, Vec<&str>), String> {
+    let mut parts = path.split('.');
+    let section = parts.next().unwrap_or_default();
+    if !EDITABLE_SECTIONS.contains(&section) {
+        return Err(format!("Unknown section: {} (expected one of: {})", section, EDITABLE_SECTIONS.join(", ")));
+    }
+    let keys: Vec<&str> = parts.collect();
+    if keys.iter().any(|k| k.is_empty()) {
+        return Err(format!("Invalid setting path: {}", path));
+    }
+    Ok((section, keys))
+}
+
+/// Parse a typed-in value for a setting whose current value is `current`
+fn parse_setting_value(current: Option<&toml::Value>, raw: &str) -> Result<toml::Value, String> {
+    let raw = raw.trim();
+    let literal = || {
+        toml::from_str::<toml::Table>(&format!("v = {}", raw)).ok().and_then(|mut t| t.remove("v"))
+    };
+    match current {
+        // Quotes are optional for strings
+        Some(toml::Value::String(_)) => Ok(match literal() {
+            Some(toml::Value::String(s)) => toml::Value::String(s),
+            _ => toml::Value::String(raw.to_string()),
+        }),
+        Some(current) => {
+            let parsed = literal().ok_or_else(|| format!("expected a {}", current.type_str()))?;
+            match (current, parsed) {
+                (toml::Value::Float(_), toml::Value::Integer(n)) => Ok(toml::Value::Float(n as f64)),
+                (current, parsed) if current.same_type(&parsed) => Ok(parsed),
+                (current, _) => Err(format!("expected a {}", current.type_str())),
+            }
         }
+        // Unset optional settings: take TOML if it parses, else a plain string
+        None => Ok(literal().unwrap_or_else(|| toml::Value::String(raw.to_string()))),
     }
 }
 
+/// Serde default helper for boolean options that default to enabled
+fn default_true() -> bool {
+    true
+}
+
+fn default_query_timeout_secs() -> u64 {
+    120
+}
+
+fn default_control_timeout_secs() -> u64 {
+    10
+}
+
+fn default_request_timeout_secs() -> u64 {
+    crate::ipc::client::REQUEST_TIMEOUT_SECS
+}
+
 impl Default for TerminalConfig {
     fn default() -> Self {
         Self {
@@ -178,6 +687,37 @@ impl Default for TerminalConfig {
             theme: "monokai".to_string(),
             scrollback_lines: 10000,
             syntax_highlighting: true,
+            trim_trailing_whitespace: true,
+            collapse_blank_lines: false,
+            max_content_width: None,
+            transcript_file: None,
+            word_boundary: WordBoundary::default(),
+            glyphs: GlyphMode::default(),
+            cursor_style: CursorStyle::default(),
+            use_alternate_screen: true,
+            dedupe_code_blocks: false,
+            event_driven_input: true,
+            confirm_on_exit: false,
+        }
+    }
+}
+
+impl AgentConfig {
+    /// Check that `temperature` is in the supported 0.0 - 2.0 range
+    pub fn validate_temperature(temperature: f32) -> Result<f32, String> {
+        if (0.0..=2.0).contains(&temperature) {
+            Ok(temperature)
+        } else {
+            Err(format!("Temperature must be between 0.0 and 2.0 (got {})", temperature))
+        }
+    }
+    
+    /// Check that `max_tokens` allows at least one token
+    pub fn validate_max_tokens(max_tokens: u32) -> Result<u32, String> {
+        if max_tokens > 0 {
+            Ok(max_tokens)
+        } else {
+            Err("Max tokens must be at least 1".to_string())
         }
     }
 }
@@ -190,6 +730,14 @@ impl Default for AgentConfig {
             require_approval: true,
             max_tokens: 2000,
             temperature: 0.7,
+            system_prompt: None,
+            retry_queries: false,
+            query_timeout_secs: default_query_timeout_secs(),
+            control_timeout_secs: default_control_timeout_secs(),
+            request_timeout_secs: default_request_timeout_secs(),
+            auto_reconnect: false,
+            max_queries_per_minute: 0,
+            thread_queries: false,
         }
     }
 }
@@ -202,6 +750,8 @@ impl Default for Keybindings {
             cancel: "Ctrl+C".to_string(),
             clear_screen: "Ctrl+K".to_string(),
             show_history: "Ctrl+L".to_string(),
+            previous_session: default_previous_session_key(),
+            next_session: default_next_session_key(),
         }
     }
 }
@@ -215,6 +765,8 @@ impl Default for ToolsConfig {
                 ".".to_string(), // Current directory
             ],
             command_timeout: 10,
+            auto_deny_high_risk: false,
+            compact_approvals: false,
         }
     }
 }
@@ -239,10 +791,261 @@ mod tests {
         assert_eq!(parsed.terminal.font_family, config.terminal.font_family);
     }
     
+    #[test]
+    fn test_missing_new_fields_use_defaults() {
+        // Config files written before newer options existed must still load
+        let toml_str = r#"
+            [terminal]
+            font_family = "Fira Code"
+            font_size = 12
+            theme = "monokai"
+            scrollback_lines = 5000
+            syntax_highlighting = true
+
+            [agent]
+            model = "mock"
+            auto_suggest = true
+            require_approval = true
+            max_tokens = 2000
+            temperature = 0.7
+
+            [keybindings]
+            toggle_ai = "Ctrl+A"
+            send_query = "Enter"
+            cancel = "Ctrl+C"
+            clear_screen = "Ctrl+K"
+            show_history = "Ctrl+L"
+
+            [tools]
+            enable_real_execution = false
+            safe_directories = ["~"]
+            command_timeout = 10
+        "#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        assert!(parsed.terminal.trim_trailing_whitespace);
+        assert!(!parsed.terminal.collapse_blank_lines);
+        assert_eq!(parsed.agent.query_timeout_secs, 120);
+        assert_eq!(parsed.agent.control_timeout_secs, 10);
+        assert_eq!(parsed.agent.request_timeout_secs, 30);
+        assert!(parsed.hooks.on_response.is_none());
+        assert!(!parsed.tools.auto_deny_high_risk);
+        assert!(!parsed.safe_mode.enabled);
+        assert!(parsed.safe_mode.mock_model);
+        assert!(parsed.terminal.transcript_file.is_none());
+        assert!(parsed.terminal.max_content_width.is_none());
+        assert_eq!(parsed.session.on_load, LoadMode::Replace);
+        assert_eq!(parsed.terminal.cursor_style, CursorStyle::Default);
+        assert!(parsed.terminal.use_alternate_screen);
+        
+        let parsed: Config = toml::from_str(&format!("{}\n[session]\non_load = \"append\"\n", toml_str)).unwrap();
+        assert_eq!(parsed.session.on_load, LoadMode::Append);
+        
+        let with_cursor = toml_str.replace("syntax_highlighting = true", "syntax_highlighting = true\ncursor_style = \"blinking-beam\"");
+        let parsed: Config = toml::from_str(&with_cursor).unwrap();
+        assert_eq!(parsed.terminal.cursor_style, CursorStyle::BlinkingBeam);
+    }
+    
+    #[test]
+    fn test_safe_mode_overrides() {
+        let mut config = Config::default();
+        config.tools.enable_real_execution = true;
+        config.tools.auto_deny_high_risk = false;
+        config.agent.require_approval = false;
+        config.agent.model = "gpt-4".to_string();
+        
+        // Disabled safe mode changes nothing
+        let mut unchanged = config.clone();
+        unchanged.apply_safe_mode();
+        assert!(unchanged.tools.enable_real_execution);
+        assert_eq!(unchanged.agent.model, "gpt-4");
+        
+        let mut safe = config.clone();
+        safe.safe_mode.enabled = true;
+        safe.apply_safe_mode();
+        assert!(!safe.tools.enable_real_execution);
+        assert!(safe.tools.auto_deny_high_risk);
+        assert!(safe.agent.require_approval);
+        assert_eq!(safe.agent.model, "mock");
+        
+        // The model is kept when mock pinning is turned off
+        let mut keep_model = config;
+        keep_model.safe_mode = SafeModeConfig { enabled: true, mock_model: false };
+        keep_model.apply_safe_mode();
+        assert!(!keep_model.tools.enable_real_execution);
+        assert_eq!(keep_model.agent.model, "gpt-4");
+    }
+    
+    #[test]
+    fn test_get_and_set_paths() {
+        let mut config = Config::default();
+        assert_eq!(config.get_path("agent.model"), Ok(toml::Value::String("mock".into())));
+        assert_eq!(config.get_path("terminal.font_size"), Ok(toml::Value::Integer(14)));
+        assert!(config.get_path("agent").unwrap().is_table());
+        assert!(config.get_path("agent.system_prompt").unwrap_err().contains("not set"));
+        assert!(config.get_path("session.on_load").unwrap_err().contains("Unknown section"));
+
+        config.set_path("agent.model", "gpt-4").unwrap();
+        assert_eq!(config.agent.model, "gpt-4");
+        config.set_path("agent.model", "\"claude 3\"").unwrap();
+        assert_eq!(config.agent.model, "claude 3");
+        config.set_path("agent.temperature", "1").unwrap();
+        assert_eq!(config.agent.temperature, 1.0);
+        config.set_path("tools.safe_directories", "[\"~/src\"]").unwrap();
+        assert_eq!(config.tools.safe_directories, ["~/src"]);
+        config.set_path("terminal.max_content_width", "100").unwrap();
+        assert_eq!(config.terminal.max_content_width, Some(100));
+        config.set_path("agent.system_prompt", "Be brief").unwrap();
+        assert_eq!(config.agent.system_prompt.as_deref(), Some("Be brief"));
+        config.set_path("terminal.glyphs", "ascii").unwrap();
+        assert_eq!(config.terminal.glyphs, GlyphMode::Ascii);
+
+        // Rejected changes leave the config alone
+        for (path, raw) in [
+            ("terminal.font_size", "big"),
+            ("terminal.glyphs", "sparkly"),
+            ("agent.temperature", "9"),
+            ("agent.modle", "gpt-4"),
+            ("agent", "x"),
+            ("hooks.on_response", "echo"),
+            ("tools.enable_real_execution", "true"),
+            ("tools.auto_deny_high_risk", "false"),
+        ] {
+            assert!(config.set_path(path, raw).is_err(), "{} = {}", path, raw);
+        }
+        assert_eq!(config.terminal.font_size, 14);
+        assert_eq!(config.agent.temperature, 1.0);
+        assert!(!config.tools.enable_real_execution);
+        assert!(config.set_path("tools.enable_real_execution", "true").unwrap_err().contains("/exec on"));
+    }
+
     #[test]
     fn test_config_path() {
         let path = Config::config_path().unwrap();
         assert!(path.to_str().unwrap().contains("openagent-terminal"));
         assert!(path.to_str().unwrap().ends_with("config.toml"));
     }
+
+    #[test]
+    fn test_validate_sampling() {
+        assert_eq!(AgentConfig::validate_temperature(0.0), Ok(0.0));
+        assert_eq!(AgentConfig::validate_temperature(2.0), Ok(2.0));
+        assert_eq!(
+            AgentConfig::validate_temperature(2.5),
+            Err("Temperature must be between 0.0 and 2.0 (got 2.5)".to_string())
+        );
+        assert!(AgentConfig::validate_temperature(-0.1).is_err());
+        assert!(AgentConfig::validate_temperature(f32::NAN).is_err());
+        assert_eq!(AgentConfig::validate_max_tokens(1), Ok(1));
+        assert!(AgentConfig::validate_max_tokens(0).is_err());
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert!(Config::default().validate().is_ok());
+        assert!(Config::default().invalid_settings().is_empty());
+    }
+
+    #[test]
+    fn test_validate_lists_every_invalid_setting() {
+        let mut config = Config::default();
+        config.agent.temperature = 50.0;
+        config.agent.max_tokens = 0;
+        config.terminal.font_size = 0;
+        config.terminal.scrollback_lines = 0;
+        config.tools.command_timeout = 0;
+
+        let message = match config.validate() {
+            Err(TerminalError::ConfigError(message)) => message,
+            other => panic!("expected a config error, got {:?}", other),
+        };
+        for field in ["agent.temperature", "agent.max_tokens", "terminal.font_size", "terminal.scrollback_lines", "tools.command_timeout"] {
+            assert!(message.contains(&format!("  - {}:", field)), "{} missing from {}", field, message);
+        }
+
+        // The bounds themselves are fine
+        for size in [6, 72] {
+            config = Config::default();
+            config.terminal.font_size = size;
+            assert!(config.validate().is_ok(), "font size {}", size);
+        }
+        config.terminal.font_size = 73;
+        assert!(config.validate().unwrap_err().to_string().contains("between 6 and 72 (got 73)"));
+    }
+
+    #[test]
+    fn test_load_from_rejects_invalid_values() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let mut config = Config::default();
+        config.agent.temperature = 50.0;
+        config.terminal.font_size = 0;
+        std::fs::write(&path, toml::to_string(&config).unwrap()).unwrap();
+
+        let err = format!("{:#}", Config::load_from(&path).unwrap_err());
+        assert!(err.contains("agent.temperature"), "{}", err);
+        assert!(err.contains("terminal.font_size"), "{}", err);
+
+        config.agent.temperature = 1.0;
+        config.terminal.font_size = 12;
+        std::fs::write(&path, toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(Config::load_from(&path).unwrap().terminal.font_size, 12);
+    }
+
+    #[test]
+    fn test_save_setting_keeps_the_rest_of_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("custom.toml");
+        let original = toml::to_string(&Config::default()).unwrap()
+            .replacen("[agent]", "# My agent settings\n[agent]", 1)
+            .replacen("model = \"mock\"", "model = \"mock\" # keep it cheap", 1);
+        std::fs::write(&path, &original).unwrap();
+
+        // A CLI override that must not be written back
+        let mut config = Config::load_from(&path).unwrap();
+        config.terminal.font_size = 20;
+        config.set_path("agent.model", "gpt-4").unwrap();
+        config.save_setting("agent.model").unwrap();
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(saved.contains("# My agent settings"), "{}", saved);
+        assert!(saved.contains("model = \"gpt-4\" # keep it cheap"), "{}", saved);
+        let reloaded = Config::load_from(&path).unwrap();
+        assert_eq!(reloaded.agent.model, "gpt-4");
+        assert_eq!(reloaded.terminal.font_size, 14);
+    }
+
+    #[test]
+    fn test_save_setting_creates_a_loadable_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("config.toml");
+        let mut config = Config::default().with_file(&path);
+        config.set_path("tools.safe_directories", "[\"~/src\"]").unwrap();
+        config.save_setting("tools.safe_directories").unwrap();
+        assert_eq!(Config::load_from(&path).unwrap().tools.safe_directories, ["~/src"]);
+    }
+
+    #[test]
+    fn test_config_dir_fallback_chain() {
+        let xdg = Path::new("/home/user/.xdg");
+        let home = Path::new("/home/user");
+
+        assert_eq!(resolve_config_dir(Some(xdg), Some(home)), Some(xdg.to_path_buf()));
+        // XDG_CONFIG_HOME unset, or relative (ignored per the spec)
+        assert_eq!(resolve_config_dir(None, Some(home)), Some(home.join(".config")));
+        assert_eq!(resolve_config_dir(Some(Path::new("rel")), Some(home)), Some(home.join(".config")));
+        // Neither set (an empty HOME counts as unset): no shared directory like /tmp
+        assert_eq!(resolve_config_dir(None, None), None);
+        assert_eq!(resolve_config_dir(None, Some(Path::new(""))), None);
+        assert_eq!(resolve_config_dir(Some(xdg), None), Some(xdg.to_path_buf()));
+    }
+    
+    #[test]
+    fn test_data_dir_fallback_chain() {
+        let xdg = Path::new("/home/user/.xdg-data");
+        let home = Path::new("/home/user");
+        
+        assert_eq!(resolve_data_dir(Some(xdg), Some(home)), Some(xdg.to_path_buf()));
+        assert_eq!(resolve_data_dir(Some(Path::new("rel")), Some(home)), Some(home.join(".local/share")));
+        assert_eq!(resolve_data_dir(None, Some(Path::new(""))), None);
+    }
 }
@@ -4,6 +4,9 @@
 
 use thiserror::Error;
 
+/// Placeholder path for connection errors raised without a known socket
+const UNKNOWN_SOCKET_PATH: &str = "<unknown socket>";
+
 /// Main error type for OpenAgent-Terminal
 #[derive(Error, Debug)]
 #[allow(dead_code)]
@@ -105,6 +108,12 @@ pub enum TerminalError {
     Other(String),
 }
 
+impl From<crate::ipc::IpcError> for TerminalError {
+    fn from(err: crate::ipc::IpcError) -> Self {
+        Self::from_ipc(&err)
+    }
+}
+
 #[allow(dead_code)]
 impl TerminalError {
     /// Create a backend connection error
@@ -128,6 +137,44 @@ impl TerminalError {
         Self::Timeout { seconds }
     }
 
+    /// Map an IPC failure to its user-facing error (see `From<IpcError>`)
+    ///
+    /// Connection errors don't know the socket path; fill it in with
+    /// `with_socket_path` where it is known.
+    pub fn from_ipc(err: &crate::ipc::IpcError) -> Self {
+        use crate::ipc::IpcError;
+        use std::io::{Error, ErrorKind};
+        match err {
+            IpcError::Timeout(after) => Self::timeout(after.as_secs_f64().ceil() as u64),
+            IpcError::RpcError { message, .. } => Self::AgentQueryError(message.clone()),
+            IpcError::ConnectionError(msg) => Self::backend_connection(UNKNOWN_SOCKET_PATH, Error::other(msg.clone())),
+            IpcError::SocketNotFound(path) => {
+                Self::backend_connection(path.clone(), Error::new(ErrorKind::NotFound, err.to_string()))
+            }
+            IpcError::StaleSocket(path) => {
+                Self::backend_connection(path.clone(), Error::new(ErrorKind::ConnectionRefused, err.to_string()))
+            }
+            IpcError::SendFailed(msg)
+            | IpcError::ReceiveFailed(msg) => Self::BackendDisconnected(msg.clone()),
+            IpcError::NotConnected => Self::BackendDisconnected(err.to_string()),
+            IpcError::SerializationError(msg)
+            | IpcError::ParseError(msg)
+            | IpcError::ProtocolError(msg) => Self::ProtocolError(msg.clone()),
+            IpcError::IoError(e) => Self::IoError(Error::new(e.kind(), e.to_string())),
+            IpcError::InternalError(msg) => Self::Other(msg.clone()),
+        }
+    }
+
+    /// Fill in the socket path of a connection error that didn't know it
+    pub fn with_socket_path(self, socket_path: &str) -> Self {
+        match self {
+            Self::BackendConnectionError { path, source } if path == UNKNOWN_SOCKET_PATH => {
+                Self::backend_connection(socket_path, source)
+            }
+            other => other,
+        }
+    }
+
     /// Check if this is a connection error
     pub fn is_connection_error(&self) -> bool {
         matches!(self, Self::BackendConnectionError { .. } | Self::BackendDisconnected(_))
@@ -252,6 +299,7 @@ macro_rules! retry {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Duration;
 
     #[test]
     fn test_error_messages() {
@@ -275,6 +323,64 @@ mod tests {
         assert!(!config_err.is_recoverable());
     }
 
+    #[test]
+    fn test_ipc_errors_classified() {
+        use crate::ipc::IpcError;
+
+        assert!(TerminalError::from_ipc(&IpcError::Timeout(Duration::from_secs(30))).is_recoverable());
+        assert!(TerminalError::from_ipc(&IpcError::rpc(-32603, "overloaded")).is_recoverable());
+        assert!(!TerminalError::from_ipc(&IpcError::NotConnected).is_recoverable());
+        assert!(!TerminalError::from_ipc(&IpcError::ParseError("bad".into())).is_recoverable());
+    }
+
+    #[test]
+    fn test_from_ipc_error_mapping() {
+        use crate::ipc::IpcError;
+
+        type Check = fn(&TerminalError) -> bool;
+        let cases: Vec<(IpcError, Check)> = vec![
+            (IpcError::NotConnected, |e| matches!(e, TerminalError::BackendDisconnected(_))),
+            (IpcError::Timeout(Duration::from_secs(120)), |e| matches!(e, TerminalError::Timeout { seconds: 120 })),
+            (IpcError::ConnectionError("refused".into()), |e| matches!(e, TerminalError::BackendConnectionError { .. })),
+            (IpcError::SocketNotFound("/tmp/a.sock".into()), |e| {
+                matches!(e, TerminalError::BackendConnectionError { path, source }
+                    if path == "/tmp/a.sock" && source.kind() == std::io::ErrorKind::NotFound)
+            }),
+            (IpcError::StaleSocket("/tmp/b.sock".into()), |e| {
+                matches!(e, TerminalError::BackendConnectionError { path, source }
+                    if path == "/tmp/b.sock" && source.kind() == std::io::ErrorKind::ConnectionRefused)
+            }),
+            (IpcError::SendFailed("pipe".into()), |e| matches!(e, TerminalError::BackendDisconnected(_))),
+            (IpcError::ReceiveFailed("eof".into()), |e| matches!(e, TerminalError::BackendDisconnected(_))),
+            (IpcError::rpc(-32000, "model down"), |e| matches!(e, TerminalError::AgentQueryError(m) if m == "model down")),
+            (IpcError::SerializationError("x".into()), |e| matches!(e, TerminalError::ProtocolError(_))),
+            (IpcError::ParseError("x".into()), |e| matches!(e, TerminalError::ProtocolError(_))),
+            (IpcError::ProtocolError("x".into()), |e| matches!(e, TerminalError::ProtocolError(_))),
+            (IpcError::IoError(std::io::Error::other("disk")), |e| matches!(e, TerminalError::IoError(_))),
+            (IpcError::InternalError("oops".into()), |e| matches!(e, TerminalError::Other(m) if m == "oops")),
+        ];
+
+        for (ipc_err, expected) in cases {
+            let description = format!("{:?}", ipc_err);
+            let err = TerminalError::from(ipc_err);
+            assert!(expected(&err), "{} mapped to {:?}", description, err);
+        }
+    }
+
+    #[test]
+    fn test_with_socket_path() {
+        use crate::ipc::IpcError;
+
+        let err = TerminalError::from(IpcError::ConnectionError("refused".into()))
+            .with_socket_path("/run/openagent.sock");
+        assert!(err.to_string().contains("Failed to connect to backend at /run/openagent.sock"));
+
+        // Known paths are kept
+        let err = TerminalError::from(IpcError::StaleSocket("/tmp/b.sock".into()))
+            .with_socket_path("/other.sock");
+        assert!(err.to_string().contains("/tmp/b.sock"));
+    }
+
     #[test]
     fn test_short_message() {
         let err = TerminalError::timeout(30);
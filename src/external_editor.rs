@@ -0,0 +1,109 @@
+// External Editor - Compose input in $VISUAL/$EDITOR
+//
+// Mirrors bash's edit-and-execute-command (Ctrl+X Ctrl+E): the current buffer
+// is written to a temp file, the user's editor is run on it, and the edited
+// text is read back into the line editor.
+
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::Command;
+
+/// Resolve the editor command from $VISUAL, falling back to $EDITOR
+pub fn editor_command() -> Option<String> {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|cmd| !cmd.trim().is_empty())
+}
+
+/// Write `initial` to a temp file, let `run_editor` modify it, and read it back
+///
+/// The temp file is created fresh and readable only by the user (it may hold
+/// a query), and is removed afterwards. A single trailing newline (which most
+/// editors append on save) is stripped from the result.
+pub fn edit_text<F>(initial: &str, run_editor: F) -> io::Result<String>
+where
+    F: FnOnce(&Path) -> io::Result<()>,
+{
+    let mut file = tempfile::Builder::new()
+        .prefix("openagent-terminal-edit-")
+        .suffix(".md")
+        .tempfile()?;
+    file.write_all(initial.as_bytes())?;
+    file.flush()?;
+    // Close our handle so the editor may replace the file; it is still
+    // removed when `path` is dropped
+    let path = file.into_temp_path();
+
+    let result = run_editor(&path).and_then(|_| std::fs::read_to_string(&path));
+    drop(path);
+
+    let mut edited = result?;
+    if edited.ends_with('\n') {
+        edited.pop();
+        if edited.ends_with('\r') {
+            edited.pop();
+        }
+    }
+    Ok(edited)
+}
+
+/// Run the editor command on `path` and wait for it to exit
+///
+/// The command may include arguments (e.g. `code --wait`).
+pub fn spawn_editor(command: &str, path: &Path) -> io::Result<()> {
+    let mut parts = command.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty editor command"))?;
+
+    let status = Command::new(program).args(parts).arg(path).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("editor exited with {}", status)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_round_trip() {
+        let mut used_path = None;
+        let edited = edit_text("draft query", |path| {
+            used_path = Some(path.to_path_buf());
+            let contents = std::fs::read_to_string(path)?;
+            assert_eq!(contents, "draft query");
+            std::fs::write(path, "edited\nmulti-line query\n")
+        })
+        .unwrap();
+
+        assert_eq!(edited, "edited\nmulti-line query");
+        assert!(!used_path.unwrap().exists(), "temp file should be cleaned up");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_temp_file_private() {
+        use std::os::unix::fs::PermissionsExt;
+        edit_text("secret", |path| {
+            let mode = std::fs::metadata(path)?.permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_edit_failure_propagates() {
+        let mut used_path = None;
+        let result = edit_text("draft", |path| {
+            used_path = Some(path.to_path_buf());
+            Err(io::Error::other("editor crashed"))
+        });
+        assert!(result.is_err());
+        assert!(!used_path.unwrap().exists());
+    }
+}
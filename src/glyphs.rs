@@ -0,0 +1,140 @@
+// Glyphs - The icons used across the UI, with plain-text stand-ins
+//
+// Emoji render as empty boxes on terminals without an emoji font, so every
+// icon the UI prints comes from this table. `terminal.glyphs = "ascii"`
+// swaps them all for bracketed markers.
+//
+// The mode is process-wide (like the notice color switch and the code theme)
+// so any renderer can look up an icon without threading config through.
+
+use crate::config::GlyphMode;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set when `GlyphMode::Ascii` is active
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+/// An icon printed by the UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Glyph {
+    /// The model in the status line
+    Ai,
+    /// Header above a streamed response
+    Response,
+    /// Privacy mode and tool approval prompts
+    Lock,
+    /// Session ID in the status line
+    Session,
+    /// Running token total
+    Tokens,
+    /// Safe mode banner
+    Safe,
+    /// Real tool execution is on
+    RealExec,
+    /// Status line warnings (e.g. backend version mismatch)
+    Caution,
+    /// Waiting on a running tool
+    Running,
+    /// Credential prompts
+    Key,
+    /// Pinned sessions
+    Pin,
+    /// Connecting to the backend
+    Connect,
+    /// Decoration in the welcome banner
+    Sparkle,
+    /// Closing message
+    Goodbye,
+    /// Notice levels
+    Info,
+    Warn,
+    Error,
+    Success,
+}
+
+impl Glyph {
+    /// Every glyph, for checking the table
+    #[cfg(test)]
+    const ALL: [Glyph; 18] = [
+        Glyph::Ai, Glyph::Response, Glyph::Lock, Glyph::Session, Glyph::Tokens, Glyph::Safe,
+        Glyph::RealExec, Glyph::Caution, Glyph::Running, Glyph::Key, Glyph::Pin, Glyph::Connect,
+        Glyph::Sparkle, Glyph::Goodbye, Glyph::Info, Glyph::Warn, Glyph::Error, Glyph::Success,
+    ];
+
+    /// The (emoji, ascii) pair for this glyph
+    fn pair(self) -> (&'static str, &'static str) {
+        match self {
+            Glyph::Ai => ("🤖", "AI:"),
+            Glyph::Response => ("🤖 AI:", "AI:"),
+            Glyph::Lock => ("🔒", "[LOCK]"),
+            Glyph::Session => ("📝", "[S]"),
+            Glyph::Tokens => ("🔢", "#"),
+            Glyph::Safe => ("🛡", "[SAFE]"),
+            Glyph::RealExec => ("⚡", "[!]"),
+            Glyph::Caution => ("⚠", "[!]"),
+            Glyph::Running => ("⏳", "..."),
+            Glyph::Key => ("🔑", "[KEY]"),
+            Glyph::Pin => ("📌", "[P]"),
+            Glyph::Connect => ("🔌", "->"),
+            Glyph::Sparkle => ("✨", "*"),
+            Glyph::Goodbye => ("👋", "--"),
+            Glyph::Info => ("ℹ️ ", "[i]"),
+            Glyph::Warn => ("⚠️ ", "[!]"),
+            Glyph::Error => ("❌", "[X]"),
+            Glyph::Success => ("✅", "[OK]"),
+        }
+    }
+
+    /// This glyph in `mode`
+    pub fn in_mode(self, mode: GlyphMode) -> &'static str {
+        let (emoji, ascii) = self.pair();
+        match mode {
+            GlyphMode::Emoji => emoji,
+            GlyphMode::Ascii => ascii,
+        }
+    }
+
+    /// This glyph in the active mode
+    pub fn get(self) -> &'static str {
+        self.in_mode(mode())
+    }
+}
+
+/// Switch the glyph mode for everything rendered from now on
+pub fn set_mode(mode: GlyphMode) {
+    ASCII.store(mode == GlyphMode::Ascii, Ordering::Relaxed);
+}
+
+/// The active glyph mode
+pub fn mode() -> GlyphMode {
+    if ASCII.load(Ordering::Relaxed) {
+        GlyphMode::Ascii
+    } else {
+        GlyphMode::Emoji
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glyph_per_mode() {
+        assert_eq!(Glyph::Ai.in_mode(GlyphMode::Emoji), "🤖");
+        assert_eq!(Glyph::Ai.in_mode(GlyphMode::Ascii), "AI:");
+        assert_eq!(Glyph::Lock.in_mode(GlyphMode::Emoji), "🔒");
+        assert_eq!(Glyph::Lock.in_mode(GlyphMode::Ascii), "[LOCK]");
+        assert_eq!(Glyph::Success.in_mode(GlyphMode::Emoji), "✅");
+        assert_eq!(Glyph::Success.in_mode(GlyphMode::Ascii), "[OK]");
+        assert_eq!(Glyph::Session.in_mode(GlyphMode::Emoji), "📝");
+        assert_eq!(Glyph::Session.in_mode(GlyphMode::Ascii), "[S]");
+    }
+
+    #[test]
+    fn test_ascii_glyphs_are_plain() {
+        for glyph in Glyph::ALL {
+            let ascii = glyph.in_mode(GlyphMode::Ascii);
+            assert!(!ascii.is_empty() && ascii.is_ascii(), "{:?} has non-ASCII fallback {:?}", glyph, ascii);
+            assert!(!glyph.in_mode(GlyphMode::Emoji).is_ascii(), "{:?} emoji is plain text", glyph);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+// Hooks - User shell commands run on lifecycle events
+//
+// Hooks are configured under `[hooks]` and run through `sh -c` off the UI
+// thread. Event details are passed as `OPENAGENT_*` environment variables.
+// A failing hook is logged and otherwise ignored.
+
+use log::{debug, warn};
+use std::process::{Command, Stdio};
+
+/// Details of a completed response, exposed to the `on_response` hook
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResponseEvent {
+    /// Query text the response answered
+    pub query: String,
+    /// Total tokens reported by `stream.complete`, if any
+    pub total_tokens: Option<u64>,
+    /// Active session, if any
+    pub session_id: Option<String>,
+}
+
+impl ResponseEvent {
+    /// Environment variables describing this event
+    pub fn env(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("OPENAGENT_QUERY", self.query.clone()),
+            (
+                "OPENAGENT_TOTAL_TOKENS",
+                self.total_tokens.map(|t| t.to_string()).unwrap_or_default(),
+            ),
+            ("OPENAGENT_SESSION_ID", self.session_id.clone().unwrap_or_default()),
+        ]
+    }
+}
+
+/// Build the process for a hook command with the event environment applied
+pub fn hook_command(command: &str, event: &ResponseEvent) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c")
+        .arg(command)
+        .envs(event.env())
+        // Hook output would corrupt the raw-mode UI
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    cmd
+}
+
+/// Run the `on_response` hook in the background
+///
+/// Returns immediately; the hook's exit status is only logged.
+pub fn run_on_response(command: &str, event: ResponseEvent) {
+    let command = command.to_string();
+    tokio::task::spawn_blocking(move || {
+        debug!("Running on_response hook: {}", command);
+        match hook_command(&command, &event).status() {
+            Ok(status) if status.success() => {}
+            Ok(status) => warn!("on_response hook exited with {}", status),
+            Err(e) => warn!("Failed to run on_response hook: {}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    fn env_of<'a>(cmd: &'a Command, key: &str) -> Option<&'a OsStr> {
+        cmd.get_envs()
+            .find(|(k, _)| *k == OsStr::new(key))
+            .and_then(|(_, v)| v)
+    }
+
+    #[test]
+    fn test_hook_command_env() {
+        let event = ResponseEvent {
+            query: "list files".to_string(),
+            total_tokens: Some(150),
+            session_id: Some("abc-123".to_string()),
+        };
+        let cmd = hook_command("true", &event);
+
+        assert_eq!(cmd.get_program(), "sh");
+        assert_eq!(cmd.get_args().collect::<Vec<_>>(), ["-c", "true"]);
+        assert_eq!(env_of(&cmd, "OPENAGENT_QUERY"), Some(OsStr::new("list files")));
+        assert_eq!(env_of(&cmd, "OPENAGENT_TOTAL_TOKENS"), Some(OsStr::new("150")));
+        assert_eq!(env_of(&cmd, "OPENAGENT_SESSION_ID"), Some(OsStr::new("abc-123")));
+    }
+
+    #[test]
+    fn test_missing_details_are_empty() {
+        let cmd = hook_command("true", &ResponseEvent { query: "q".to_string(), ..Default::default() });
+        assert_eq!(env_of(&cmd, "OPENAGENT_TOTAL_TOKENS"), Some(OsStr::new("")));
+        assert_eq!(env_of(&cmd, "OPENAGENT_SESSION_ID"), Some(OsStr::new("")));
+    }
+}
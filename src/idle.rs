@@ -0,0 +1,180 @@
+// Idle - Wait at the prompt for the next terminal event or backend notification
+//
+// The prompt used to wake every 100ms through `event::poll` just to redraw,
+// even with nothing to do. With `terminal.event_driven_input` (the default)
+// it blocks on crossterm's async `EventStream` and the notification channel
+// together instead, so an idle terminal sleeps until something happens.
+// Setting it to false restores the polling loop.
+//
+// One `EventStream` is kept for the whole session. Dropping one that has
+// just handed out an event leaves its wake-up signal set, and the next
+// blocking read (a prompt, `cursor::position`) is interrupted by it - so
+// recreating the stream per keypress makes every redraw fail. While the
+// stream waits for input its reader thread holds the terminal input, so
+// after a notification wins the race that thread is stopped before anything
+// else reads.
+
+use crossterm::event::{self, Event, EventStream};
+use futures::{Stream, StreamExt};
+use std::future::{self, Future};
+use std::io;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use crate::ipc::message::Notification;
+
+/// How long the polling loop waits for input before redrawing
+pub const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What woke the prompt
+#[derive(Debug)]
+pub enum Wake {
+    /// A terminal event (key, resize, paste, ...)
+    Input(Event),
+    /// A notification that arrived while no query was running
+    Notification(Notification),
+    /// `POLL_INTERVAL` passed without input (polling mode only)
+    Tick,
+}
+
+/// Terminal events for the whole session
+pub struct InputEvents<S = EventStream> {
+    stream: S,
+    new_stream: fn() -> S,
+    /// The last poll returned `Pending`, so the reader thread is still waiting
+    waiting: bool,
+}
+
+impl InputEvents {
+    pub fn new() -> Self {
+        Self::with(EventStream::new)
+    }
+}
+
+impl<S> InputEvents<S>
+where
+    S: Stream<Item = io::Result<Event>> + Unpin,
+{
+    /// Events from `new_stream`, which is only called again by `release`
+    pub fn with(new_stream: fn() -> S) -> Self {
+        Self { stream: new_stream(), new_stream, waiting: false }
+    }
+    
+    /// Wait for the next terminal event or notification, whichever comes first
+    ///
+    /// `notification` resolving to `None` (the channel is gone) stops it being
+    /// waited on, leaving only the terminal events. Input wins a tie.
+    pub async fn next_wake(&mut self, notification: impl Future<Output = Option<Notification>>) -> io::Result<Wake> {
+        let wake = tokio::select! {
+            biased;
+            event = self.next_event() => event.map(Wake::Input),
+            Some(notification) = notification => Ok(Wake::Notification(notification)),
+        };
+        self.release();
+        wake
+    }
+    
+    /// Wait for the next terminal event
+    ///
+    /// Meant for `select!` loops; call `release` once the loop is done.
+    pub async fn next_event(&mut self) -> io::Result<Event> {
+        future::poll_fn(|cx| self.poll_event(cx)).await
+    }
+    
+    /// Stop waiting for input, so blocking reads can have the terminal
+    ///
+    /// Only a stream whose reader thread is waiting is replaced: dropping it
+    /// then ends the thread cleanly, where dropping an idle one would leave
+    /// its wake-up signal behind.
+    pub fn release(&mut self) {
+        if self.waiting {
+            self.stream = (self.new_stream)();
+            self.waiting = false;
+        }
+    }
+    
+    fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Event>> {
+        let poll = self.stream.poll_next_unpin(cx);
+        self.waiting = poll.is_pending();
+        poll.map(|event| {
+            event.unwrap_or_else(|| Err(io::Error::new(io::ErrorKind::UnexpectedEof, "terminal event stream ended")))
+        })
+    }
+}
+
+/// Wait up to `POLL_INTERVAL` for a terminal event (the polling fallback)
+pub fn poll_wake() -> io::Result<Wake> {
+    if event::poll(POLL_INTERVAL)? {
+        event::read().map(Wake::Input)
+    } else {
+        Ok(Wake::Tick)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use futures::stream;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Instant;
+
+    fn key(c: char) -> io::Result<Event> {
+        Ok(Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)))
+    }
+
+    #[tokio::test]
+    async fn test_wakes_on_each_injected_event() {
+        let mut events = InputEvents::with(|| stream::iter([key('a'), key('b'), Ok(Event::Resize(80, 24))]));
+        let started = Instant::now();
+
+        let mut woke = Vec::new();
+        for _ in 0..3 {
+            match events.next_wake(future::pending()).await.unwrap() {
+                Wake::Input(event) => woke.push(event),
+                other => panic!("expected input, got {:?}", other),
+            }
+        }
+        assert_eq!(woke, [key('a').unwrap(), key('b').unwrap(), Event::Resize(80, 24)]);
+        // Three wakes well inside a single poll interval
+        assert!(started.elapsed() < POLL_INTERVAL, "took {:?}", started.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_keeps_one_stream_between_wakes() {
+        // A stream recreated per wake would start over and hand out 'a' every time
+        let mut events = InputEvents::with(|| stream::iter([key('a'), key('b'), key('c')]));
+        for expected in ['a', 'b', 'c'] {
+            match events.next_wake(async { None }).await.unwrap() {
+                Wake::Input(event) => assert_eq!(event, key(expected).unwrap()),
+                other => panic!("expected input, got {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wakes_on_notification() {
+        static CREATED: AtomicUsize = AtomicUsize::new(0);
+        let mut events = InputEvents::with(|| {
+            CREATED.fetch_add(1, Ordering::SeqCst);
+            stream::pending()
+        });
+        let closed = async { Some(Notification::connection_closed("backend closed")) };
+        match events.next_wake(closed).await.unwrap() {
+            Wake::Notification(notification) => assert!(notification.is_connection_closed()),
+            other => panic!("expected a notification, got {:?}", other),
+        }
+        // The stream was left waiting for input, so it's swapped out to stop its reader
+        assert_eq!(CREATED.load(Ordering::SeqCst), 2);
+        assert!(!events.waiting);
+    }
+
+    #[tokio::test]
+    async fn test_blocks_without_events() {
+        // No tick after the poll interval: nothing wakes the prompt until input arrives,
+        // even once the notification channel is gone
+        let mut events = InputEvents::with(stream::pending);
+        let idle = tokio::time::timeout(POLL_INTERVAL * 3, events.next_wake(async { None })).await;
+        assert!(idle.is_err(), "woke without input: {:?}", idle);
+    }
+}
@@ -1,18 +1,195 @@
 // IPC Client - Unix Socket Connection to Python Backend
 
 use super::error::IpcError;
-use super::message::{Notification, Request, Response};
+use crate::error::{RetryConfig, TerminalError};
+use super::rate_limit::RateLimiter;
+use super::message::{InitializeResult, ModelInfo, Notification, Request, RequestId, Response, ServerInfo};
 use anyhow::Result;
 use log::{debug, error, info, warn};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use tokio::io::{AsyncBufReadExt, BufReader};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, BufReader};
 use tokio::net::UnixStream;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch};
 
-type RequestId = u64;
 type ResponseSender = tokio::sync::oneshot::Sender<Result<Response, IpcError>>;
 
+/// Why a connection attempt to the backend socket failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectFailure {
+    /// Nothing exists at the socket path
+    Missing,
+    /// The socket file exists but nothing is listening on it
+    Stale,
+    /// Any other I/O error (permissions, not a socket, ...)
+    Other,
+}
+
+/// Socket paths starting with this name an abstract-namespace socket (Linux only)
+pub const ABSTRACT_SOCKET_PREFIX: char = '@';
+
+/// Address for the abstract-namespace socket `name` (`\0name`, no filesystem entry)
+#[cfg(target_os = "linux")]
+pub fn abstract_socket_addr(name: &str) -> std::io::Result<std::os::unix::net::SocketAddr> {
+    use std::os::linux::net::SocketAddrExt;
+    std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes())
+}
+
+/// Abstract-namespace sockets only exist on Linux
+#[cfg(not(target_os = "linux"))]
+pub fn abstract_socket_addr(_name: &str) -> std::io::Result<std::os::unix::net::SocketAddr> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "abstract namespace sockets are only supported on Linux; use --socket <path> instead",
+    ))
+}
+
+/// Open a stream to `socket_path`, which may name an abstract socket (`@name`)
+async fn connect_stream(socket_path: &str) -> std::io::Result<UnixStream> {
+    match socket_path.strip_prefix(ABSTRACT_SOCKET_PREFIX) {
+        Some(name) => {
+            let addr = abstract_socket_addr(name)?;
+            let stream = std::os::unix::net::UnixStream::connect_addr(&addr)?;
+            stream.set_nonblocking(true)?;
+            UnixStream::from_std(stream)
+        }
+        None => UnixStream::connect(socket_path).await,
+    }
+}
+
+/// Classify a connect error for the given socket path
+pub fn classify_connect_error(socket_path: &str, err: &std::io::Error) -> ConnectFailure {
+    match err.kind() {
+        std::io::ErrorKind::NotFound => ConnectFailure::Missing,
+        std::io::ErrorKind::ConnectionRefused if std::path::Path::new(socket_path).exists() => {
+            ConnectFailure::Stale
+        }
+        _ => ConnectFailure::Other,
+    }
+}
+
+/// Fail every pending request with a connection error, e.g. once the socket is gone
+fn fail_pending_requests(pending_requests: &Mutex<HashMap<RequestId, ResponseSender>>, reason: &str) {
+    let in_flight: Vec<_> = pending_requests.lock().unwrap().drain().collect();
+    for (request_id, sender) in in_flight {
+        debug!("Failing in-flight request {}: {}", request_id, reason);
+        let _ = sender.send(Err(IpcError::ConnectionError(reason.to_string())));
+    }
+}
+
+/// Connection attempts made by `connect` unless changed with `set_connect_attempts`
+pub const DEFAULT_CONNECT_ATTEMPTS: u32 = 3;
+
+/// How long `send_request` waits for a response to methods without a specific timeout
+pub const REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// Per-method response timeouts used by `send_request`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeoutPolicy {
+    /// `agent.query`, which may wait on a slow model before answering
+    pub query: Duration,
+    /// Quick control RPCs (`initialize`, `session.*`)
+    pub control: Duration,
+    /// Every other method
+    pub default: Duration,
+}
+
+impl Default for TimeoutPolicy {
+    fn default() -> Self {
+        Self {
+            query: Duration::from_secs(120),
+            control: Duration::from_secs(10),
+            default: Duration::from_secs(REQUEST_TIMEOUT_SECS),
+        }
+    }
+}
+
+impl TimeoutPolicy {
+    /// Timeout to apply to a request for `method`
+    pub fn timeout_for_method(&self, method: &str) -> Duration {
+        match method {
+            "agent.query" => self.query,
+            "initialize" => self.control,
+            m if m.starts_with("session.") => self.control,
+            _ => self.default,
+        }
+    }
+}
+
+/// Lets an I/O task mark its connection as lost
+struct ConnectionLost {
+    state: watch::Sender<ConnectionState>,
+    generation: Arc<AtomicU64>,
+    /// Generation of the connection the task serves
+    ours: u64,
+}
+
+impl ConnectionLost {
+    /// Mark the connection `Disconnected` if it is still the current one
+    ///
+    /// Returns whether it was.
+    fn report(&self) -> bool {
+        if self.generation.load(Ordering::SeqCst) != self.ours {
+            return false;
+        }
+        self.state.send_replace(ConnectionState::Disconnected);
+        true
+    }
+}
+
+/// Counts of protocol anomalies seen by the reader task
+#[derive(Debug, Default)]
+struct DiagnosticCounters {
+    /// Messages carrying fields the protocol doesn't define
+    protocol_drift: AtomicU64,
+    /// Responses whose request ID had no pending request (late or duplicate)
+    orphan_responses: AtomicU64,
+}
+
+/// Point-in-time copy of the diagnostic counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Diagnostics {
+    pub protocol_drift: u64,
+    pub orphan_responses: u64,
+}
+
+/// Which way a frame passed to a `FrameSink` was travelling
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameDirection {
+    Sent,
+    Received,
+}
+
+impl FrameDirection {
+    /// `>>` for sent frames, `<<` for received ones
+    pub fn prefix(self) -> &'static str {
+        match self {
+            FrameDirection::Sent => ">>",
+            FrameDirection::Received => "<<",
+        }
+    }
+}
+
+/// Callback seeing every raw frame sent to or received from the backend
+pub type FrameSink = Arc<dyn Fn(FrameDirection, &str) + Send + Sync>;
+
+/// Pass `frame` to the installed sink, if any
+fn emit_frame(sink: &Mutex<Option<FrameSink>>, direction: FrameDirection, frame: &str) {
+    // Clone out so the sink runs without holding the lock
+    let sink = sink.lock().unwrap().clone();
+    if let Some(sink) = sink {
+        sink(direction, frame);
+    }
+}
+
+/// JSON-RPC error code for a method the backend doesn't implement
+pub const METHOD_NOT_FOUND: i32 = -32601;
+
+/// Methods whose params carry credentials and must never be logged
+const REDACTED_METHODS: &[&str] = &["context.auth_response"];
+
 /// Request ID space boundaries for collision prevention
 /// Interactive flow uses 0-9999, SessionManager uses 10000+
 const INTERACTIVE_ID_MIN: u64 = 0;
@@ -35,6 +212,73 @@ pub enum ConnectionState {
     Failed,
 }
 
+impl ConnectionState {
+    /// Short name for the status line
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConnectionState::Disconnected => "Disconnected",
+            ConnectionState::Connecting => "Connecting",
+            ConnectionState::Connected => "Connected",
+            ConnectionState::Reconnecting { .. } => "Reconnecting",
+            ConnectionState::Failed => "Failed",
+        }
+    }
+}
+
+/// One connection attempt, reported to the `connect_with_retry` progress callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectProgress {
+    /// 1-based attempt number
+    pub attempt: u32,
+    pub max_attempts: u32,
+    /// Backoff waited before this attempt (`None` for the first)
+    pub retry_delay: Option<std::time::Duration>,
+}
+
+impl ConnectProgress {
+    /// Status line, e.g. "Connecting... (attempt 2/3, retrying in 0.4s)"
+    pub fn message(&self) -> String {
+        match self.retry_delay {
+            Some(delay) => format!(
+                "Connecting... (attempt {}/{}, retrying in {:.1}s)",
+                self.attempt, self.max_attempts, delay.as_secs_f64()
+            ),
+            None => format!("Connecting... (attempt {}/{})", self.attempt, self.max_attempts),
+        }
+    }
+}
+
+/// A request that has been sent and is awaiting its response
+pub struct PendingResponse {
+    request_id: RequestId,
+    method: String,
+    receiver: tokio::sync::oneshot::Receiver<Result<Response, IpcError>>,
+    pending_requests: Arc<Mutex<HashMap<RequestId, ResponseSender>>>,
+}
+
+impl PendingResponse {
+    /// Wait up to `timeout` for the response
+    ///
+    /// On timeout the pending entry is dropped, so a late response is
+    /// counted as an orphan.
+    pub async fn wait(self, timeout: Duration) -> Result<Response, IpcError> {
+        match tokio::time::timeout(timeout, self.receiver).await {
+            Ok(response_result) => {
+                // Response received before timeout
+                response_result
+                    .map_err(|_| IpcError::InternalError("Response channel closed".to_string()))?
+            }
+            Err(_) => {
+                // Timeout occurred - clean up pending request to prevent memory leak!
+                let mut pending = self.pending_requests.lock().unwrap();
+                pending.remove(&self.request_id);
+                warn!("Request {} ({}) timed out after {:?}, cleaned up pending entry", self.request_id, self.method, timeout);
+                Err(IpcError::Timeout(timeout))
+            }
+        }
+    }
+}
+
 /// IPC client for communication with Python backend
 pub struct IpcClient {
     write_sender: Option<mpsc::UnboundedSender<String>>,
@@ -42,8 +286,24 @@ pub struct IpcClient {
     pending_requests: Arc<Mutex<HashMap<RequestId, ResponseSender>>>,
     notification_sender: Option<mpsc::UnboundedSender<Notification>>,
     notification_receiver: Option<mpsc::UnboundedReceiver<Notification>>,
-    connection_state: ConnectionState,
+    /// Current state, shared with the I/O tasks so they can report a lost connection
+    connection_state: watch::Sender<ConnectionState>,
+    /// Bumped for every connection, so tasks of an old one can't report on a newer one
+    connection_generation: Arc<AtomicU64>,
     socket_path: Option<String>,
+    init_result: Option<InitializeResult>,
+    timeouts: TimeoutPolicy,
+    rate_limiter: RateLimiter,
+    connect_attempts: u32,
+    /// Reconnect (and re-initialize) before sending on a lost connection
+    auto_reconnect: bool,
+    diagnostics: Arc<DiagnosticCounters>,
+    /// Result of the last successful `models.list`
+    models: Option<Vec<ModelInfo>>,
+    /// Most recent line received from the backend, for `/lastmsg`
+    last_raw_message: Arc<Mutex<Option<String>>>,
+    /// Frame dump hook (`--verbose-ipc`)
+    frame_sink: Arc<Mutex<Option<FrameSink>>>,
 }
 
 impl IpcClient {
@@ -56,39 +316,105 @@ impl IpcClient {
             pending_requests: Arc::new(Mutex::new(HashMap::new())),
             notification_sender: Some(tx),
             notification_receiver: Some(rx),
-            connection_state: ConnectionState::Disconnected,
+            connection_state: watch::Sender::new(ConnectionState::Disconnected),
+            connection_generation: Arc::new(AtomicU64::new(0)),
             socket_path: None,
+            init_result: None,
+            timeouts: TimeoutPolicy::default(),
+            rate_limiter: RateLimiter::default(),
+            connect_attempts: DEFAULT_CONNECT_ATTEMPTS,
+            auto_reconnect: false,
+            diagnostics: Arc::new(DiagnosticCounters::default()),
+            models: None,
+            last_raw_message: Arc::new(Mutex::new(None)),
+            frame_sink: Arc::new(Mutex::new(None)),
         }
     }
 
+    /// A client already connected over `stream` (e.g. one end of `tokio::io::duplex`)
+    ///
+    /// Skips the socket entirely, so tests can drive the read and write tasks
+    /// deterministically, including half-closes and arbitrary framing.
+    #[cfg(test)]
+    pub fn from_stream<S>(stream: S) -> Result<Self, IpcError>
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let mut client = Self::new();
+        let (read_half, write_half) = tokio::io::split(stream);
+        client.start_message_handler(read_half, write_half)?;
+        client.set_state(ConnectionState::Connected);
+        Ok(client)
+    }
+
     /// Connect to the Python backend via Unix socket with retry logic
+    #[cfg(test)]
     pub async fn connect(&mut self, socket_path: &str) -> Result<(), IpcError> {
+        self.connect_with_progress(socket_path, |_| {}).await
+    }
+
+    /// Like `connect`, calling `on_attempt` before each attempt so the UI can show retries
+    pub async fn connect_with_progress(
+        &mut self,
+        socket_path: &str,
+        on_attempt: impl FnMut(ConnectProgress),
+    ) -> Result<(), IpcError> {
         self.socket_path = Some(socket_path.to_string());
-        self.connect_with_retry(socket_path, 3).await
+        self.connect_with_retry(socket_path, self.connect_attempts, on_attempt).await
+    }
+
+    /// Reconnect automatically when sending on a connection found to be lost
+    pub fn set_auto_reconnect(&mut self, enabled: bool) {
+        self.auto_reconnect = enabled;
+    }
+    
+    /// With auto-reconnect on, bring a lost connection back before sending
+    async fn ensure_connected(&mut self) -> Result<(), IpcError> {
+        if self.auto_reconnect && self.connection_state() == ConnectionState::Disconnected && self.socket_path.is_some() {
+            // Boxed: reconnecting sends `initialize`, which comes back through here
+            Box::pin(self.reconnect()).await?;
+        }
+        Ok(())
+    }
+
+    /// Set how many attempts `connect` makes (1 = fail fast, no backoff)
+    pub fn set_connect_attempts(&mut self, attempts: u32) {
+        self.connect_attempts = attempts.max(1);
     }
 
     /// Connect with specified number of retry attempts
-    pub async fn connect_with_retry(&mut self, socket_path: &str, max_attempts: u32) -> Result<(), IpcError> {
+    ///
+    /// `on_attempt` is called once per attempt, before its backoff delay.
+    pub async fn connect_with_retry(
+        &mut self,
+        socket_path: &str,
+        max_attempts: u32,
+        mut on_attempt: impl FnMut(ConnectProgress),
+    ) -> Result<(), IpcError> {
         info!("🔌 Connecting to Python backend at {}", socket_path);
-        self.connection_state = ConnectionState::Connecting;
+        self.set_state(ConnectionState::Connecting);
         
         let mut last_error = None;
         
         for attempt in 0..max_attempts {
             if attempt > 0 {
-                self.connection_state = ConnectionState::Reconnecting { attempt };
+                self.set_state(ConnectionState::Reconnecting { attempt });
                 let delay = std::time::Duration::from_millis(200 * (2_u64.pow(attempt - 1)));
+                on_attempt(ConnectProgress { attempt: attempt + 1, max_attempts, retry_delay: Some(delay) });
                 info!("🔄 Reconnection attempt {} after {:?}", attempt + 1, delay);
                 tokio::time::sleep(delay).await;
+            } else {
+                on_attempt(ConnectProgress { attempt: 1, max_attempts, retry_delay: None });
             }
             
-            match UnixStream::connect(socket_path).await {
+            match connect_stream(socket_path).await {
                 Ok(stream) => {
                     info!("✅ Connected to Unix socket");
                     
                     // Start the message handling task
-                    self.start_message_handler(stream).await?;
-                    self.connection_state = ConnectionState::Connected;
+                    let (read_half, write_half) = stream.into_split();
+                    self.start_message_handler(read_half, write_half)?;
+                    self.set_state(ConnectionState::Connected);
                     
                     return Ok(());
                 }
@@ -99,18 +425,29 @@ impl IpcClient {
             }
         }
         
-        self.connection_state = ConnectionState::Failed;
+        self.set_state(ConnectionState::Failed);
+        let last_error = last_error.unwrap();
+        
+        if classify_connect_error(socket_path, &last_error) == ConnectFailure::Stale {
+            warn!("⚠️  Socket {} exists but refuses connections - the backend may have crashed and left a stale socket", socket_path);
+            return Err(IpcError::StaleSocket(socket_path.to_string()));
+        }
+        
         Err(IpcError::ConnectionError(
             format!("Failed to connect after {} attempts. Last error: {}", 
                     max_attempts, 
-                    last_error.unwrap())
+                    last_error)
         ))
     }
 
-    /// Start the background task that handles incoming messages
-    async fn start_message_handler(&mut self, stream: UnixStream) -> Result<(), IpcError> {
-        let (read_half, write_half) = stream.into_split();
+    /// Start the background tasks that read and write the connection
+    fn start_message_handler<R, W>(&mut self, read_half: R, write_half: W) -> Result<(), IpcError>
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
         let reader = BufReader::new(read_half);
+        let generation = self.connection_generation.fetch_add(1, Ordering::SeqCst) + 1;
         
         // Create channel for writing messages
         let (write_tx, mut write_rx) = mpsc::unbounded_channel::<String>();
@@ -119,21 +456,28 @@ impl IpcClient {
         self.write_sender = Some(write_tx);
         
         // Spawn task to handle writes
+        let write_pending = Arc::clone(&self.pending_requests);
+        let write_lost = ConnectionLost {
+            state: self.connection_state.clone(),
+            generation: Arc::clone(&self.connection_generation),
+            ours: generation,
+        };
         tokio::spawn(async move {
             use tokio::io::AsyncWriteExt;
             let mut writer = write_half;
             
             while let Some(message) = write_rx.recv().await {
-                if let Err(e) = writer.write_all(message.as_bytes()).await {
+                let written = async {
+                    writer.write_all(message.as_bytes()).await?;
+                    writer.write_all(b"\n").await?;
+                    writer.flush().await
+                }.await;
+                if let Err(e) = written {
                     error!("❌ Write failed: {} - Connection lost", e);
-                    break;
-                }
-                if let Err(e) = writer.write_all(b"\n").await {
-                    error!("❌ Write newline failed: {} - Connection lost", e);
-                    break;
-                }
-                if let Err(e) = writer.flush().await {
-                    error!("❌ Flush failed: {} - Connection lost", e);
+                    // The request being written (and any queued behind it) will
+                    // never be answered, so fail them now rather than at timeout
+                    fail_pending_requests(&write_pending, &format!("write failed: {}", e));
+                    write_lost.report();
                     break;
                 }
             }
@@ -141,40 +485,62 @@ impl IpcClient {
         });
         
         let pending_requests = Arc::clone(&self.pending_requests);
-        let notification_sender = self.notification_sender.take()
+        let diagnostics = Arc::clone(&self.diagnostics);
+        let last_raw_message = Arc::clone(&self.last_raw_message);
+        let frame_sink = Arc::clone(&self.frame_sink);
+        // Cloned rather than taken, so a reconnect can start new tasks
+        let notification_sender = self.notification_sender.clone()
             .ok_or_else(|| IpcError::InternalError("Notification sender not available".to_string()))?;
+        let read_lost = ConnectionLost {
+            state: self.connection_state.clone(),
+            generation: Arc::clone(&self.connection_generation),
+            ours: generation,
+        };
         
         // Spawn background task to handle incoming messages
         tokio::spawn(async move {
             let mut lines = reader.lines();
             
-            loop {
+            let reason = loop {
                 match lines.next_line().await {
                     Ok(Some(line)) => {
                         debug!("📨 Received: {}", line);
+                        emit_frame(&frame_sink, FrameDirection::Received, &line);
                         
                         if let Err(e) = Self::handle_incoming_message(
                             &line, 
                             &pending_requests, 
-                            &notification_sender
+                            &notification_sender,
+                            &diagnostics,
+                            &last_raw_message,
                         ).await {
                             warn!("Failed to handle message: {}", e);
                         }
                     }
                     Ok(None) => {
                         warn!("🔌 Connection closed by backend (EOF received)");
-                        break;
+                        break "backend closed".to_string();
                     }
                     Err(e) => {
                         error!("❌ Read error: {} - Connection lost", e);
-                        break;
+                        break format!("read error: {}", e);
                     }
                 }
-            }
+            };
             
             warn!("🔌 Message handler task ended - connection lost");
-            // Note: In a production system, you might want to notify the main client
-            // about the disconnection via a channel so it can attempt reconnection
+            
+            // A connection that was already replaced (or closed by us) has nothing to report
+            if !read_lost.report() {
+                return;
+            }
+            
+            // No response can arrive any more, so fail in-flight requests now
+            // instead of leaving them to wait out their timeouts
+            fail_pending_requests(&pending_requests, "backend closed");
+            
+            // Let notification consumers react now
+            let _ = notification_sender.send(Notification::connection_closed(&reason));
         });
         
         Ok(())
@@ -185,15 +551,25 @@ impl IpcClient {
         line: &str,
         pending_requests: &Arc<Mutex<HashMap<RequestId, ResponseSender>>>,
         notification_sender: &mpsc::UnboundedSender<Notification>,
+        diagnostics: &DiagnosticCounters,
+        last_raw_message: &Mutex<Option<String>>,
     ) -> Result<(), IpcError> {
+        *last_raw_message.lock().unwrap() = Some(line.to_string());
+        
         // First, check for unknown fields using tolerant parsing
-        Self::check_for_unknown_fields(line);
+        if Self::check_for_unknown_fields(line) {
+            diagnostics.protocol_drift.fetch_add(1, Ordering::Relaxed);
+        }
         
         // Try to parse as notification first (no 'id' field)
         match serde_json::from_str::<Notification>(line) {
+            Ok(notification) if notification.is_connection_closed() => {
+                warn!("Ignoring {} sent by the backend (reserved for the client)", notification.method);
+                return Ok(());
+            }
             Ok(notification) => {
                 debug!("📬 Received notification: {}", notification.method);
-                if let Err(_) = notification_sender.send(notification) {
+                if notification_sender.send(notification).is_err() {
                     warn!("Failed to send notification - receiver dropped");
                 }
                 return Ok(());
@@ -206,90 +582,276 @@ impl IpcClient {
         // Try to parse as response
         match serde_json::from_str::<Response>(line) {
             Ok(response) => {
-                if let super::message::RequestId::Number(id) = response.id {
-                    debug!("📬 Received response for request {}", id);
-                    let mut pending = pending_requests.lock().unwrap();
-                    if let Some(sender) = pending.remove(&id) {
-                        let _ = sender.send(Ok(response));
-                    } else {
-                        warn!("Received response for unknown request ID: {}", id);
+                debug!("📬 Received response for request {}", response.id);
+                // Matched on the full ID: 7 and "7" are different requests
+                let sender = pending_requests.lock().unwrap().remove(&response.id);
+                if let Some(sender) = sender {
+                    // Malformed responses fail the request instead of reaching callers
+                    // that would each pick result or error differently
+                    let response = response.validate().map(|()| response);
+                    if let Err(e) = &response {
+                        warn!("Rejecting malformed response: {}", e);
                     }
+                    let _ = sender.send(response);
+                } else {
+                    warn!("Received response for unknown request ID: {}", response.id);
+                    diagnostics.orphan_responses.fetch_add(1, Ordering::Relaxed);
                 }
-                return Ok(());
+                Ok(())
             }
             Err(e) => {
-                return Err(IpcError::ParseError(format!("Failed to parse message: {} (error: {})", line, e)));
+                let reason = super::message::describe_invalid_message(line, &e);
+                Err(IpcError::ParseError(format!("Invalid message: {} (message: {})", reason, line)))
             }
         }
     }
     
     /// Check for unknown fields in incoming messages (for protocol drift detection)
-    fn check_for_unknown_fields(line: &str) {
+    ///
+    /// Returns whether any unknown fields were found.
+    fn check_for_unknown_fields(line: &str) -> bool {
         use super::message::TolerantMessage;
-        if let Ok(tolerant) = serde_json::from_str::<TolerantMessage>(line) {
-            tolerant.log_unknown_fields();
+        serde_json::from_str::<TolerantMessage>(line)
+            .is_ok_and(|tolerant| tolerant.log_unknown_fields())
+    }
+
+    /// Current protocol drift and orphan response counts
+    pub fn diagnostics(&self) -> Diagnostics {
+        Diagnostics {
+            protocol_drift: self.diagnostics.protocol_drift.load(Ordering::Relaxed),
+            orphan_responses: self.diagnostics.orphan_responses.load(Ordering::Relaxed),
         }
     }
 
+    /// Zero the diagnostic counters (e.g. before reproducing an issue)
+    pub fn reset_diagnostics(&self) {
+        self.diagnostics.protocol_drift.store(0, Ordering::Relaxed);
+        self.diagnostics.orphan_responses.store(0, Ordering::Relaxed);
+    }
+
+    /// Install (or with `None`, remove) a hook called with every frame sent and received
+    ///
+    /// Credentials in redacted methods are replaced before the sink sees them.
+    pub fn set_frame_sink(&self, sink: Option<FrameSink>) {
+        *self.frame_sink.lock().unwrap() = sink;
+    }
+
+    /// The last line received from the backend, exactly as read (unredacted)
+    pub fn last_raw_message(&self) -> Option<String> {
+        self.last_raw_message.lock().unwrap().clone()
+    }
+
     /// Send initialize request and wait for response
     pub async fn initialize(&mut self) -> Result<Response, IpcError> {
         info!("🚀 Sending initialize request");
         
         let request = Request::initialize(self.next_request_id());
         
-        self.send_request(request).await
+        let response = self.send_request(request).await?;
+        self.init_result = InitializeResult::from_response(&response);
+        if let Some(init) = &self.init_result {
+            debug!("Backend capabilities: {:?}", init.capabilities);
+        }
+        if let Some(info) = self.server_info() {
+            info!("🧩 Backend: {} v{}", info.name, info.version);
+        }
+        Ok(response)
+    }
+    
+    /// Replace the per-method response timeouts
+    pub fn set_timeout_policy(&mut self, timeouts: TimeoutPolicy) {
+        self.timeouts = timeouts;
+    }
+    
+    /// Timeout for methods without a specific one in the policy
+    pub fn set_default_timeout(&mut self, timeout: Duration) {
+        self.timeouts.default = timeout;
     }
 
-    /// Send a request and wait for response
+    /// Allow at most `per_minute` calls of `method` through `check_rate_limit` (0 = unlimited)
+    pub fn set_rate_limit(&mut self, method: &str, per_minute: u32) {
+        self.rate_limiter.set_limit(method, per_minute);
+    }
+
+    /// Count a call of `method` against its limit, or return how long to wait first
+    ///
+    /// Callers check before building a request so an over-limit call is never sent.
+    pub fn check_rate_limit(&mut self, method: &str) -> Result<(), Duration> {
+        self.rate_limiter.check(method)
+    }
+
+    /// Whether the backend streams query responses as notifications
+    ///
+    /// Backends that never initialized (or sent an unparseable result) are
+    /// assumed to stream, matching the protocol's default.
+    pub fn supports_streaming(&self) -> bool {
+        self.init_result
+            .as_ref()
+            .is_none_or(|init| init.capabilities.iter().any(|c| c == "streaming"))
+    }
+
+    /// Whether the backend advertised `capability` when initializing
+    ///
+    /// Unlike streaming, optional features are off for backends that never initialized.
+    pub fn supports_capability(&self, capability: &str) -> bool {
+        self.init_result
+            .as_ref()
+            .is_some_and(|init| init.capabilities.iter().any(|c| c == capability))
+    }
+
+    /// Models offered by the backend, fetched once and then served from cache
+    ///
+    /// Returns `None` if the backend doesn't implement `models.list`.
+    pub async fn list_models(&mut self) -> Result<Option<Vec<ModelInfo>>, IpcError> {
+        if let Some(models) = &self.models {
+            return Ok(Some(models.clone()));
+        }
+        
+        let request = Request::models_list(self.next_request_id());
+        let response = self.send_request(request).await?;
+        if let Some(error) = response.error {
+            if error.code == METHOD_NOT_FOUND {
+                debug!("Backend does not support models.list");
+                return Ok(None);
+            }
+            return Err(IpcError::RpcError { code: error.code, message: error.message });
+        }
+        
+        let result = response.result
+            .ok_or_else(|| IpcError::ParseError("No result in response".to_string()))?;
+        let models = super::message::parse_models_list(&result)
+            .map_err(|e| IpcError::ParseError(format!("Failed to parse models: {}", e)))?;
+        self.models = Some(models.clone());
+        Ok(Some(models))
+    }
+
+    /// Backend identity from the last successful initialize, if reported
+    pub fn server_info(&self) -> Option<&ServerInfo> {
+        self.init_result.as_ref().and_then(|r| r.server_info.as_ref())
+    }
+
+    /// Send a request and wait for response, up to the timeout policy's limit for its method
     pub async fn send_request(&mut self, request: Request) -> Result<Response, IpcError> {
+        let timeout = self.timeouts.timeout_for_method(&request.method);
+        self.send_request_with_timeout(request, timeout).await
+    }
+    
+    /// Send a request and wait up to `timeout` for the response
+    ///
+    /// For calls known to take longer (or shorter) than their method's usual
+    /// timeout. On timeout the pending entry is dropped, so a late response
+    /// is counted as an orphan.
+    pub async fn send_request_with_timeout(&mut self, request: Request, timeout: Duration) -> Result<Response, IpcError> {
+        self.start_request(request)
+            .await?
+            .wait(timeout)
+            .await
+    }
+
+    /// Send a request without waiting for its response
+    ///
+    /// The returned handle is independent of the client, so a caller sharing
+    /// the client behind a lock can release it before a long wait.
+    pub async fn start_request(&mut self, request: Request) -> Result<PendingResponse, IpcError> {
+        self.ensure_connected().await?;
         let write_sender = self.write_sender.as_ref()
-            .ok_or_else(|| IpcError::NotConnected)?;
+            .ok_or(IpcError::NotConnected)?;
             
-        let request_id = match &request.id {
-            super::message::RequestId::Number(id) => *id,
-            super::message::RequestId::String(_) => {
-                return Err(IpcError::InternalError("String IDs not supported yet".to_string()));
-            }
-        };
+        let request_id = request.id.clone();
         
         // Create channel for response
         let (tx, rx) = tokio::sync::oneshot::channel();
         
-        // Register the pending request
+        // Register the pending request; replacing an in-flight entry would
+        // strand its caller, so an ID collision is refused instead
         {
+            use std::collections::hash_map::Entry;
             let mut pending = self.pending_requests.lock().unwrap();
-            pending.insert(request_id, tx);
+            match pending.entry(request_id.clone()) {
+                Entry::Occupied(_) => {
+                    error!("Request ID {} ({}) is already in flight", request_id, request.method);
+                    return Err(IpcError::InternalError(format!(
+                        "Request ID {} is already in flight", request_id
+                    )));
+                }
+                Entry::Vacant(slot) => {
+                    slot.insert(tx);
+                }
+            }
         }
         
         // Serialize and send the request
         let message = serde_json::to_string(&request)
             .map_err(|e| IpcError::SerializationError(e.to_string()))?;
             
-        debug!("📤 Sending: {}", message);
-        
-        write_sender.send(message)
-            .map_err(|_| IpcError::ConnectionError("Write channel closed".to_string()))?;
+        if REDACTED_METHODS.contains(&request.method.as_str()) {
+            debug!("📤 Sending: {} (params redacted)", request.method);
+            let shown = Request {
+                params: Some(serde_json::Value::String(super::message::REDACTED.to_string())),
+                ..request.clone()
+            };
+            if let Ok(shown) = serde_json::to_string(&shown) {
+                emit_frame(&self.frame_sink, FrameDirection::Sent, &shown);
+            }
+        } else {
+            debug!("📤 Sending: {}", message);
+            emit_frame(&self.frame_sink, FrameDirection::Sent, &message);
+        }
         
-        // Wait for response with timeout
-        let result = tokio::time::timeout(
-            std::time::Duration::from_secs(30), 
-            rx
-        ).await;
+        if write_sender.send(message).is_err() {
+            self.pending_requests.lock().unwrap().remove(&request_id);
+            return Err(IpcError::ConnectionError("Write channel closed".to_string()));
+        }
         
-        // Clean up pending request on timeout to prevent memory leak
-        match result {
-            Ok(response_result) => {
-                // Response received before timeout
-                response_result
-                    .map_err(|_| IpcError::InternalError("Response channel closed".to_string()))?
-            }
-            Err(_) => {
-                // Timeout occurred - clean up pending request to prevent memory leak!
-                let mut pending = self.pending_requests.lock().unwrap();
-                pending.remove(&request_id);
-                warn!("Request {} timed out after 30s, cleaned up pending entry", request_id);
-                Err(IpcError::Timeout)
+        Ok(PendingResponse {
+            request_id,
+            method: request.method,
+            receiver: rx,
+            pending_requests: Arc::clone(&self.pending_requests),
+        })
+    }
+
+    /// Send a request, retrying recoverable failures according to `retry`
+    ///
+    /// `build` is called for every attempt so each one gets a fresh request ID.
+    /// RPC error responses count as failures; if the last attempt still gets
+    /// one, that response is returned as-is. `on_retry(attempt, max_attempts)`
+    /// runs before each retry so callers can tell the user.
+    pub async fn send_request_with_retry<B, R>(
+        &mut self,
+        mut build: B,
+        retry: &RetryConfig,
+        mut on_retry: R,
+    ) -> Result<Response, IpcError>
+    where
+        B: FnMut(&mut Self) -> Request,
+        R: FnMut(u32, u32),
+    {
+        let max_attempts = retry.max_attempts.max(1);
+        let mut attempt = 1;
+        loop {
+            let request = build(self);
+            let outcome = self.send_request(request).await;
+            
+            let (recoverable, reason) = match &outcome {
+                Ok(response) => match &response.error {
+                    None => return outcome,
+                    Some(rpc) => {
+                        let err = IpcError::rpc(rpc.code, rpc.message.clone());
+                        (TerminalError::from_ipc(&err).is_recoverable(), err.to_string())
+                    }
+                },
+                Err(e) => (TerminalError::from_ipc(e).is_recoverable(), e.to_string()),
+            };
+            if attempt >= max_attempts || !recoverable {
+                return outcome;
             }
+            
+            let delay = retry.delay_for_attempt(attempt - 1);
+            warn!("⚠️  Request failed ({}), retrying in {:?}", reason, delay);
+            attempt += 1;
+            on_retry(attempt, max_attempts);
+            tokio::time::sleep(delay).await;
         }
     }
 
@@ -319,14 +881,16 @@ impl IpcClient {
     
     /// Send a notification to the backend (fire-and-forget, no response expected)
     pub async fn send_notification(&mut self, notification: Notification) -> Result<(), IpcError> {
+        self.ensure_connected().await?;
         let write_sender = self.write_sender.as_ref()
-            .ok_or_else(|| IpcError::NotConnected)?;
+            .ok_or(IpcError::NotConnected)?;
         
         // Serialize and send the notification
         let message = serde_json::to_string(&notification)
             .map_err(|e| IpcError::SerializationError(e.to_string()))?;
         
         debug!("📤 Sending notification: {}", message);
+        emit_frame(&self.frame_sink, FrameDirection::Sent, &message);
         
         write_sender.send(message)
             .map_err(|_| IpcError::ConnectionError("Write channel closed".to_string()))?;
@@ -334,12 +898,15 @@ impl IpcClient {
         Ok(())
     }
 
-    /// Attempt to reconnect to the backend
-    #[allow(dead_code)] // For future use in connection health monitoring
+    /// Attempt to reconnect to the backend and repeat the initialize handshake
+    ///
+    /// Requests that were in flight when the connection dropped have already
+    /// failed and are not replayed; see "Reconnecting" in docs/IPC_PROTOCOL.md.
     pub async fn reconnect(&mut self) -> Result<(), IpcError> {
         if let Some(socket_path) = &self.socket_path.clone() {
             info!("🔄 Attempting to reconnect to backend...");
-            self.connect_with_retry(socket_path, 5).await
+            self.connect_with_retry(socket_path, 5, |_| {}).await?;
+            self.initialize().await.map(|_| ())
         } else {
             Err(IpcError::InternalError("No socket path stored for reconnection".to_string()))
         }
@@ -349,9 +916,10 @@ impl IpcClient {
     pub async fn disconnect(&mut self) -> Result<(), IpcError> {
         info!("🔌 Disconnecting from backend");
         
-        // Drop write sender to close channel
+        // Drop write sender to close channel (and keep the tasks from reporting the close)
         self.write_sender = None;
-        self.connection_state = ConnectionState::Disconnected;
+        self.connection_generation.fetch_add(1, Ordering::SeqCst);
+        self.set_state(ConnectionState::Disconnected);
         
         // Clear pending requests
         self.pending_requests.lock().unwrap().clear();
@@ -374,13 +942,24 @@ impl IpcClient {
     /// Check if connected
     #[allow(dead_code)] // Public API for connection state checks
     pub fn is_connected(&self) -> bool {
-        self.connection_state == ConnectionState::Connected
+        self.connection_state() == ConnectionState::Connected
     }
     
     /// Get the current connection state
-    #[allow(dead_code)] // Public API for detailed connection state
     pub fn connection_state(&self) -> ConnectionState {
-        self.connection_state
+        *self.connection_state.borrow()
+    }
+    
+    /// Watch the connection state, including the I/O tasks noticing the connection is gone
+    ///
+    /// Unlike `connection_state`, this needs no lock on the client, so it can
+    /// be polled while another task holds it.
+    pub fn connection_state_changes(&self) -> watch::Receiver<ConnectionState> {
+        self.connection_state.subscribe()
+    }
+    
+    fn set_state(&self, state: ConnectionState) {
+        self.connection_state.send_replace(state);
     }
 }
 
@@ -399,4 +978,137 @@ mod tests {
         let _client = IpcClient::new();
         // TODO: Add more tests as implementation progresses
     }
+
+    #[tokio::test]
+    async fn test_reset_diagnostics() {
+        let client = IpcClient::new();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let drift = r#"{"jsonrpc":"2.0","method":"stream.token","params":{},"extra":1}"#;
+        let orphan = r#"{"jsonrpc":"2.0","id":42,"result":{}}"#;
+        for line in [drift, orphan, orphan] {
+            // Strict parsing rejects the drifted message, but it is still counted
+            let _ = IpcClient::handle_incoming_message(
+                line, &client.pending_requests, &tx, &client.diagnostics, &client.last_raw_message,
+            ).await;
+        }
+        assert_eq!(client.diagnostics(), Diagnostics { protocol_drift: 1, orphan_responses: 2 });
+
+        client.reset_diagnostics();
+        assert_eq!(client.diagnostics(), Diagnostics::default());
+    }
+
+    #[tokio::test]
+    async fn test_malformed_response_fails_request() {
+        let client = IpcClient::new();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let lines = [
+            (1, r#"{"jsonrpc":"2.0","id":1,"result":{"ok":true}}"#, true),
+            (2, r#"{"jsonrpc":"2.0","id":2,"error":{"code":-32000,"message":"failed"}}"#, true),
+            (3, r#"{"jsonrpc":"2.0","id":3,"result":{},"error":{"code":-32000,"message":"failed"}}"#, false),
+            (4, r#"{"jsonrpc":"2.0","id":4}"#, false),
+        ];
+        for (id, line, delivered) in lines {
+            let (sender, receiver) = tokio::sync::oneshot::channel();
+            client.pending_requests.lock().unwrap().insert(RequestId::Number(id), sender);
+            IpcClient::handle_incoming_message(
+                line, &client.pending_requests, &tx, &client.diagnostics, &client.last_raw_message,
+            ).await.unwrap();
+            match receiver.await.unwrap() {
+                Ok(response) => assert!(delivered, "request {} got {:?}", id, response),
+                Err(e) => {
+                    assert!(!delivered, "request {} failed: {}", id, e);
+                    assert!(matches!(e, IpcError::ProtocolError(_)));
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_last_raw_message_tracks_latest_line() {
+        let client = IpcClient::new();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        assert_eq!(client.last_raw_message(), None);
+
+        let token = r#"{"jsonrpc":"2.0","method":"stream.token","params":{"content":"hi"}}"#;
+        let response = r#"{"jsonrpc":"2.0","id":7,"result":{}}"#;
+        for line in [token, response] {
+            IpcClient::handle_incoming_message(
+                line, &client.pending_requests, &tx, &client.diagnostics, &client.last_raw_message,
+            ).await.unwrap();
+            assert_eq!(client.last_raw_message().as_deref(), Some(line));
+        }
+
+        // Unparseable lines are kept too, since those are what need inspecting
+        let garbage = "not json";
+        let _ = IpcClient::handle_incoming_message(
+            garbage, &client.pending_requests, &tx, &client.diagnostics, &client.last_raw_message,
+        ).await;
+        assert_eq!(client.last_raw_message().as_deref(), Some(garbage));
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_request_id_rejected() {
+        let mut client = IpcClient::new();
+        let (write_tx, mut write_rx) = mpsc::unbounded_channel();
+        client.write_sender = Some(write_tx);
+
+        // Pretend a session request with this ID is already waiting
+        let (first_tx, mut first_rx) = tokio::sync::oneshot::channel();
+        client.pending_requests.lock().unwrap().insert(RequestId::Number(10001), first_tx);
+
+        let result = client.send_request(Request::new(10001, "session.list", None)).await;
+        assert!(
+            matches!(result, Err(IpcError::InternalError(ref msg)) if msg.contains("10001")),
+            "got {:?}", result
+        );
+
+        // The first request is still registered (not failed, not replaced) and nothing was written
+        assert_eq!(client.pending_requests.lock().unwrap().len(), 1);
+        assert!(matches!(first_rx.try_recv(), Err(tokio::sync::oneshot::error::TryRecvError::Empty)));
+        assert!(write_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_supports_streaming_follows_capabilities() {
+        let mut client = IpcClient::new();
+        assert!(client.supports_streaming());
+
+        client.init_result = Some(InitializeResult {
+            capabilities: vec!["streaming".to_string(), "blocks".to_string()],
+            ..Default::default()
+        });
+        assert!(client.supports_streaming());
+
+        client.init_result = Some(InitializeResult::default());
+        assert!(!client.supports_streaming());
+    }
+
+    #[test]
+    fn test_supports_capability() {
+        let mut client = IpcClient::new();
+        assert!(!client.supports_capability("threading"));
+
+        client.init_result = Some(InitializeResult {
+            capabilities: vec!["streaming".to_string(), "threading".to_string()],
+            ..Default::default()
+        });
+        assert!(client.supports_capability("threading"));
+        assert!(!client.supports_capability("blocks"));
+    }
+
+    #[test]
+    fn test_timeout_for_method() {
+        let policy = TimeoutPolicy::default();
+        assert_eq!(policy.timeout_for_method("agent.query"), Duration::from_secs(120));
+        assert_eq!(policy.timeout_for_method("session.list"), Duration::from_secs(10));
+        assert_eq!(policy.timeout_for_method("initialize"), Duration::from_secs(10));
+        assert_eq!(
+            policy.timeout_for_method("tool.approve"),
+            Duration::from_secs(REQUEST_TIMEOUT_SECS)
+        );
+
+        let policy = TimeoutPolicy { query: Duration::from_secs(300), ..Default::default() };
+        assert_eq!(policy.timeout_for_method("agent.query"), Duration::from_secs(300));
+        assert_eq!(policy.timeout_for_method("session.list"), Duration::from_secs(10));
+    }
 }
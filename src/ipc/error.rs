@@ -11,6 +11,9 @@ pub enum IpcError {
     #[error("Socket not found at path: {0}")]
     SocketNotFound(String),
 
+    #[error("Stale socket at {0}: connection refused (the backend may have crashed)")]
+    StaleSocket(String),
+
     #[error("Failed to send message: {0}")]
     SendFailed(String),
 
@@ -26,8 +29,8 @@ pub enum IpcError {
     #[error("Protocol error: {0}")]
     ProtocolError(String),
 
-    #[error("Timeout waiting for response")]
-    Timeout,
+    #[error("Timeout waiting for response after {0:?}")]
+    Timeout(std::time::Duration),
 
     #[error("RPC error (code {code}): {message}")]
     RpcError { code: i32, message: String },
@@ -44,7 +47,6 @@ pub enum IpcError {
 
 impl IpcError {
     /// Create an RPC error
-    #[allow(dead_code)] // For future error handling
     pub fn rpc(code: i32, message: impl Into<String>) -> Self {
         Self::RpcError {
             code,
@@ -104,8 +106,9 @@ mod tests {
 
     #[test]
     fn test_timeout() {
-        let err = IpcError::Timeout;
+        let err = IpcError::Timeout(std::time::Duration::from_secs(5));
         assert!(err.to_string().contains("Timeout"));
+        assert!(err.to_string().contains("5s"));
     }
 
     #[test]
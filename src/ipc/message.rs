@@ -1,7 +1,9 @@
 // IPC Message Types - JSON-RPC 2.0 Messages
 
+use super::error::IpcError;
+use chrono::{DateTime, Utc};
 use log::warn;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
 
 /// JSON-RPC 2.0 Request
@@ -23,12 +25,18 @@ pub struct Request {
 pub struct Response {
     pub jsonrpc: String,
     pub id: RequestId,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// `Some(Value::Null)` for an explicit `"result": null`, `None` when absent
+    #[serde(default, deserialize_with = "present", skip_serializing_if = "Option::is_none")]
     pub result: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<RpcError>,
 }
 
+/// Deserialize a field that is present (even as `null`) into `Some`
+fn present<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
 /// JSON-RPC 2.0 Notification (no response expected)
 /// Uses deny_unknown_fields for strict validation to catch protocol drift
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +56,15 @@ pub enum RequestId {
     String(String),
 }
 
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RequestId::Number(id) => write!(f, "{}", id),
+            RequestId::String(id) => write!(f, "\"{}\"", id),
+        }
+    }
+}
+
 /// JSON-RPC Error
 /// Uses deny_unknown_fields for strict validation to catch protocol drift
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -59,6 +76,291 @@ pub struct RpcError {
     pub data: Option<Value>,
 }
 
+/// Backend identity reported in the initialize response
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct ServerInfo {
+    pub name: String,
+    pub version: String,
+    /// When the backend process started (if reported)
+    #[serde(default)]
+    pub start_time: Option<DateTime<Utc>>,
+}
+
+impl ServerInfo {
+    /// Time the backend has been running, if it reported a start time
+    pub fn uptime(&self, now: DateTime<Utc>) -> Option<std::time::Duration> {
+        self.start_time.and_then(|start| (now - start).to_std().ok())
+    }
+
+    /// Check whether the backend's major version differs from `client_version`
+    ///
+    /// Unparseable versions are treated as compatible to avoid false alarms.
+    pub fn major_version_mismatch(&self, client_version: &str) -> bool {
+        match (major_version(&self.version), major_version(client_version)) {
+            (Some(backend), Some(client)) => backend != client,
+            _ => false,
+        }
+    }
+}
+
+/// Extract the major component of a `MAJOR.MINOR.PATCH` version string
+fn major_version(version: &str) -> Option<u64> {
+    version.trim_start_matches('v').split('.').next()?.parse().ok()
+}
+
+/// Result of the initialize handshake
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct InitializeResult {
+    #[serde(default)]
+    #[allow(dead_code)] // Reported by the backend, not acted on yet
+    pub status: Option<String>,
+    #[serde(default)]
+    pub server_info: Option<ServerInfo>,
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+}
+
+impl InitializeResult {
+    /// Parse the initialize result from a response, if present and well-formed
+    pub fn from_response(response: &Response) -> Option<Self> {
+        let result = response.result.as_ref()?;
+        match serde_json::from_value(result.clone()) {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                warn!("⚠️  Could not parse initialize result: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// A model offered by the backend in `models.list`
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct ModelInfo {
+    pub name: String,
+    /// Context window in tokens, if reported
+    #[serde(default)]
+    pub context_window: Option<u64>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Entries in `models.list` may be bare names or full objects
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ModelEntry {
+    Name(String),
+    Info(ModelInfo),
+}
+
+/// Parse a `models.list` result (`{"models": [...]}` or a bare array)
+pub fn parse_models_list(result: &Value) -> Result<Vec<ModelInfo>, serde_json::Error> {
+    let list = result.get("models").unwrap_or(result);
+    let entries: Vec<ModelEntry> = serde_json::from_value(list.clone())?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| match entry {
+            ModelEntry::Name(name) => ModelInfo { name, context_window: None, description: None },
+            ModelEntry::Info(info) => info,
+        })
+        .collect())
+}
+
+/// Key fragments marking a field as sensitive (matched case-insensitively)
+const SENSITIVE_KEY_PARTS: &[&str] = &["password", "secret", "token", "api_key", "apikey", "authorization", "credential"];
+
+/// Replacement written over sensitive values by `redact_sensitive`
+pub const REDACTED: &str = "<redacted>";
+
+/// Blank out string values under sensitive-looking keys, at any depth
+///
+/// Only strings are replaced, so counts such as `total_tokens` stay readable.
+pub fn redact_sensitive(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                let key = key.to_ascii_lowercase();
+                if field.is_string() && SENSITIVE_KEY_PARTS.iter().any(|part| key.contains(part)) {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact_sensitive(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_sensitive),
+        _ => {}
+    }
+}
+
+/// Method of the notification the client injects when the backend connection ends
+///
+/// Reserved: the client drops backend notifications that use it, so receiving
+/// one always means the local reader saw EOF or a read error.
+pub const CONNECTION_CLOSED: &str = "connection.closed";
+
+/// Params of a `stream.token` notification
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct StreamTokenParams {
+    pub content: String,
+}
+
+/// Params of a `stream.block` notification
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct StreamBlockParams {
+    /// `code`, `diff`, `text`, ...
+    #[serde(rename = "type", default = "default_block_type")]
+    pub block_type: String,
+    pub content: String,
+    /// Only sent for code blocks
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+fn default_block_type() -> String {
+    "text".to_string()
+}
+
+impl StreamBlockParams {
+    /// Highlighting language, `text` if the backend didn't name one
+    pub fn language(&self) -> &str {
+        self.language.as_deref().unwrap_or("text")
+    }
+}
+
+/// Deserialize a field, falling back to its default if it has the wrong type
+///
+/// One malformed field then costs only itself, not the rest of the params.
+fn lenient<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::de::DeserializeOwned + Default,
+{
+    let value = Value::deserialize(deserializer)?;
+    Ok(T::deserialize(value).unwrap_or_default())
+}
+
+/// Params of a `stream.complete` notification
+///
+/// Every field is parsed leniently, since all of them are informational.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+pub struct StreamCompleteParams {
+    #[serde(default, deserialize_with = "lenient")]
+    pub query_id: Option<String>,
+    /// `success`, `error`, `cancelled` or `timeout`
+    #[serde(default, deserialize_with = "lenient")]
+    pub status: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub finish_reason: Option<FinishReason>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub metadata: CompleteMetadata,
+}
+
+impl StreamCompleteParams {
+    /// Why generation stopped, sent at the top level or under `metadata`
+    pub fn finish_reason(&self) -> Option<&FinishReason> {
+        self.finish_reason.as_ref().or(self.metadata.finish_reason.as_ref())
+    }
+}
+
+/// `metadata` of a `stream.complete` notification (only the fields the client uses)
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+pub struct CompleteMetadata {
+    #[serde(default, deserialize_with = "lenient")]
+    pub total_tokens: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub finish_reason: Option<FinishReason>,
+}
+
+/// Why the model stopped generating
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FinishReason {
+    /// Finished normally
+    Stop,
+    /// Cut off at the token limit
+    #[serde(alias = "max_tokens", alias = "truncated")]
+    Length,
+    /// Withheld by the provider's content filter
+    ContentFilter,
+    /// Ended by an error while generating
+    Error,
+    /// Anything else (e.g. `tool_calls`), shown like `stop`
+    #[serde(other)]
+    Other,
+}
+
+/// Params of a `tool.request_approval` notification
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct ToolApprovalParams {
+    /// Echoed back in `tool.approve`; required since the approval can't be answered without it
+    pub execution_id: String,
+    #[serde(default = "default_unknown")]
+    pub tool_name: String,
+    #[serde(default)]
+    pub description: String,
+    /// `low`, `medium`, `high`
+    #[serde(default = "default_unknown")]
+    pub risk_level: String,
+    #[serde(default)]
+    pub preview: String,
+}
+
+fn default_unknown() -> String {
+    "unknown".to_string()
+}
+
+/// Params of a `context.auth` credential challenge
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct ContextAuthParams {
+    /// Echoed back in `context.auth_response`
+    pub auth_id: String,
+    /// Label shown before the hidden input
+    #[serde(default = "default_auth_prompt")]
+    pub prompt: String,
+}
+
+fn default_auth_prompt() -> String {
+    "Credential".to_string()
+}
+
+/// Per-query options sent with `agent.query`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryOptions {
+    /// System prompt giving the assistant consistent instructions
+    pub system_prompt: Option<String>,
+    /// Earlier conversation to give the agent as context (e.g. a loaded session)
+    pub history: Vec<ContextMessage>,
+    /// ID of the previous turn this query follows up on (sent as `parent`)
+    pub parent: Option<String>,
+    /// Sampling temperature (unset = backend default)
+    pub temperature: Option<f32>,
+    /// Cap on response tokens (unset = backend default)
+    pub max_tokens: Option<u32>,
+}
+
+/// ID identifying a finished turn, from `stream.complete` params or a query result
+///
+/// Prefers an explicit `message_id` or `turn_id` and falls back to the `query_id`.
+pub fn turn_id(value: &Value) -> Option<&str> {
+    ["message_id", "turn_id", "query_id"]
+        .iter()
+        .find_map(|key| value.get(*key).and_then(Value::as_str))
+        .filter(|id| !id.is_empty())
+}
+
+/// Whether `stream.complete` params (or an inline query result) report a cut-off response
+pub fn is_truncated(value: &Value) -> bool {
+    StreamCompleteParams::deserialize(value)
+        .is_ok_and(|params| params.finish_reason() == Some(&FinishReason::Length))
+}
+
+/// A prior message sent as `context.history` with `agent.query`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContextMessage {
+    pub role: String,
+    pub content: String,
+}
+
 /// Tolerant wrapper for parsing messages with unknown fields
 /// Used for logging unknown fields without failing the parse
 #[derive(Debug, Clone, Deserialize)]
@@ -75,8 +377,8 @@ pub(crate) struct TolerantMessage {
 }
 
 impl TolerantMessage {
-    /// Check for and log unknown fields
-    pub fn log_unknown_fields(&self) {
+    /// Check for and log unknown fields, returning whether any were found
+    pub fn log_unknown_fields(&self) -> bool {
         let expected_fields = ["jsonrpc", "method", "id", "params", "result", "error"];
         let unknown: Vec<&String> = self.extra.keys()
             .filter(|k| !expected_fields.contains(&k.as_str()))
@@ -86,6 +388,80 @@ impl TolerantMessage {
             let method_info = self.method.as_deref().unwrap_or("response");
             warn!("⚠️  Protocol drift detected in '{}': unknown fields {:?}", method_info, unknown);
         }
+        !unknown.is_empty()
+    }
+}
+
+/// Add the temperature and token cap from `options` to `params["options"]`
+fn insert_sampling_options(params: &mut Value, options: &QueryOptions) {
+    if let Some(temperature) = options.temperature {
+        // Via the shortest decimal form, so 0.7 goes out as 0.7 rather than 0.699999988
+        let temperature: f64 = temperature.to_string().parse().unwrap_or(f64::from(temperature));
+        params["options"]["temperature"] = serde_json::json!(temperature);
+    }
+    if let Some(max_tokens) = options.max_tokens {
+        params["options"]["max_tokens"] = serde_json::json!(max_tokens);
+    }
+}
+
+/// Serialize `message`, parse it back with the strict types and check nothing changed
+///
+/// Catches constructors producing JSON the `deny_unknown_fields` types reject.
+fn validate_roundtrip<T: Serialize + DeserializeOwned>(message: &T) -> Result<(), IpcError> {
+    let json = serde_json::to_string(message).map_err(|e| IpcError::SerializationError(e.to_string()))?;
+    let parsed: T = serde_json::from_str(&json).map_err(|e| IpcError::ParseError(format!("{} in {}", e, json)))?;
+    let (before, after) = (serde_json::to_value(message), serde_json::to_value(&parsed));
+    match (before, after) {
+        (Ok(before), Ok(after)) if before == after => Ok(()),
+        _ => Err(IpcError::ProtocolError(format!("message changed in a serialization round trip: {}", json))),
+    }
+}
+
+/// Explain why `line` is not a valid notification or response
+///
+/// Called after both strict parses failed; `strict_error` (from the response
+/// parse) is reported when the message has the right shape but still fails,
+/// e.g. because of unknown fields or wrong value types.
+pub(crate) fn describe_invalid_message(line: &str, strict_error: &serde_json::Error) -> String {
+    let value: Value = match serde_json::from_str(line) {
+        Ok(value) => value,
+        Err(e) => return format!("invalid JSON: {}", e),
+    };
+    let Some(obj) = value.as_object() else {
+        return "message is not a JSON object".to_string();
+    };
+    if obj.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+        return "missing or unsupported 'jsonrpc' version (expected \"2.0\")".to_string();
+    }
+
+    let has_id = obj.get("id").is_some_and(|id| !id.is_null());
+    let is_response = obj.contains_key("result") || obj.contains_key("error");
+    match (has_id, obj.get("method")) {
+        (true, Some(_)) => "unexpected request from backend (has both 'id' and 'method')".to_string(),
+        (false, Some(method)) => match method.as_str() {
+            None => "notification 'method' is not a string".to_string(),
+            Some(method) => match obj.get("params") {
+                Some(params) if !params.is_object() && !params.is_array() && !params.is_null() => {
+                    format!("notification '{}' has non-structured 'params'", method)
+                }
+                _ => format!("notification '{}' rejected: {}", method, strict_error),
+            },
+        },
+        (false, None) if is_response => "response missing 'id'".to_string(),
+        (false, None) => "neither 'id' nor 'method' present".to_string(),
+        (true, None) => {
+            let id = &obj["id"];
+            if !id.is_u64() && !id.is_string() {
+                return format!("response has invalid 'id' {} (expected a non-negative integer or string)", id);
+            }
+            match obj.get("error") {
+                _ if !is_response => format!("response {} missing 'result' or 'error'", id),
+                Some(error) if !error.is_null() && serde_json::from_value::<RpcError>(error.clone()).is_err() => {
+                    format!("response {} has malformed 'error' (needs integer 'code' and string 'message')", id)
+                }
+                _ => format!("response {} rejected: {}", id, strict_error),
+            }
+        }
     }
 }
 
@@ -100,6 +476,16 @@ impl Request {
         }
     }
 
+    /// Check this request survives serialization and strict parsing unchanged
+    pub fn validate_roundtrip(&self) -> Result<(), IpcError> {
+        validate_roundtrip(self)
+    }
+
+    /// Create models.list request
+    pub fn models_list(id: u64) -> Self {
+        Self::new(id, "models.list", None)
+    }
+
     /// Create initialize request (see IPC_PROTOCOL.md)
     /// Uses actual terminal size if available, falls back to defaults
     pub fn initialize(id: u64) -> Self {
@@ -126,17 +512,99 @@ impl Request {
         Self::new(id, "initialize", Some(params))
     }
 
-    /// Create agent.query request
+    /// Create a streaming agent.query request
+    #[allow(dead_code)] // Convenience for callers without query options
     pub fn agent_query(id: u64, message: impl Into<String>) -> Self {
-        let params = serde_json::json!({
+        Self::agent_query_with_options(id, message, &QueryOptions::default(), true)
+    }
+
+    /// Create agent.query request with per-query options
+    ///
+    /// `stream` should reflect the backend's negotiated `streaming` capability;
+    /// without it the answer comes back inline in the response.
+    pub fn agent_query_with_options(
+        id: u64,
+        message: impl Into<String>,
+        options: &QueryOptions,
+        stream: bool,
+    ) -> Self {
+        let mut params = serde_json::json!({
             "message": message.into(),
             "options": {
-                "stream": true,
+                "stream": stream,
             },
         });
 
+        if let Some(system) = options.system_prompt.as_deref().filter(|s| !s.trim().is_empty()) {
+            params["system"] = serde_json::json!(system);
+        }
+        if !options.history.is_empty() {
+            params["context"] = serde_json::json!({ "history": options.history });
+        }
+        if let Some(parent) = &options.parent {
+            params["parent"] = serde_json::json!(parent);
+        }
+        insert_sampling_options(&mut params, options);
+
         Self::new(id, "agent.query", Some(params))
     }
+
+    /// Create agent.continue request resuming the cancelled or truncated response to `query_id`
+    pub fn agent_continue(id: u64, query_id: impl Into<String>, options: &QueryOptions, stream: bool) -> Self {
+        let mut params = serde_json::json!({
+            "query_id": query_id.into(),
+            "options": {
+                "stream": stream,
+            },
+        });
+        insert_sampling_options(&mut params, options);
+
+        Self::new(id, "agent.continue", Some(params))
+    }
+
+    /// Create context.auth_response request answering a `context.auth` challenge
+    ///
+    /// `value` of `None` tells the backend the user cancelled.
+    pub fn auth_response(id: u64, auth_id: impl Into<String>, value: Option<&str>) -> Self {
+        let params = match value {
+            Some(value) => serde_json::json!({ "auth_id": auth_id.into(), "value": value }),
+            None => serde_json::json!({ "auth_id": auth_id.into(), "cancelled": true }),
+        };
+
+        Self::new(id, "context.auth_response", Some(params))
+    }
+
+    /// Create tool.approve request
+    ///
+    /// `command_timeout` (seconds) lets the backend enforce the user's configured limit.
+    pub fn tool_approve(id: u64, execution_id: impl Into<String>, approved: bool, command_timeout: u64) -> Self {
+        let params = serde_json::json!({
+            "execution_id": execution_id.into(),
+            "approved": approved,
+            "command_timeout": command_timeout,
+        });
+
+        Self::new(id, "tool.approve", Some(params))
+    }
+}
+
+impl Response {
+    /// Check the response carries exactly one of `result` and `error`
+    ///
+    /// JSON-RPC forbids both and neither; the strict type can't express that,
+    /// so the client checks it before handing the response to its caller.
+    pub fn validate(&self) -> Result<(), IpcError> {
+        let id = serde_json::to_string(&self.id).unwrap_or_default();
+        match (&self.result, &self.error) {
+            (Some(_), None) | (None, Some(_)) => Ok(()),
+            (Some(_), Some(_)) => Err(IpcError::ProtocolError(format!(
+                "response {} has both 'result' and 'error'", id
+            ))),
+            (None, None) => Err(IpcError::ProtocolError(format!(
+                "response {} has neither 'result' nor 'error'", id
+            ))),
+        }
+    }
 }
 
 impl Notification {
@@ -150,6 +618,36 @@ impl Notification {
         }
     }
 
+    /// Check this notification survives serialization and strict parsing unchanged
+    pub fn validate_roundtrip(&self) -> Result<(), IpcError> {
+        validate_roundtrip(self)
+    }
+
+    /// Synthetic `connection.closed` notification carrying why the reader stopped
+    pub fn connection_closed(reason: &str) -> Self {
+        Self::new(CONNECTION_CLOSED, Some(serde_json::json!({ "reason": reason })))
+    }
+
+    /// Whether this is the client's synthetic `connection.closed` notification
+    pub fn is_connection_closed(&self) -> bool {
+        self.method == CONNECTION_CLOSED
+    }
+
+    /// Deserialize the params into one of the typed `*Params` structs
+    ///
+    /// Missing params are an error, like a missing required field.
+    pub fn params_as<T: serde::de::DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        T::deserialize(self.params.as_ref().unwrap_or(&Value::Null))
+    }
+
+    /// Response text carried by a `stream.token` or `stream.block` notification
+    pub fn stream_content(&self) -> Option<&str> {
+        match self.method.as_str() {
+            "stream.token" | "stream.block" => self.params.as_ref()?.get("content")?.as_str(),
+            _ => None,
+        }
+    }
+
     /// Create context.update notification with working directory
     #[allow(dead_code)] // For backward compatibility
     pub fn context_update(cwd: impl Into<String>) -> Self {
@@ -172,8 +670,29 @@ impl Notification {
         Self::new("context.update", Some(params))
     }
     
+    /// Create context.update notification for a tool execution mode change
+    pub fn context_update_tools(enable_real_execution: bool) -> Self {
+        let params = serde_json::json!({
+            "tools": {
+                "enable_real_execution": enable_real_execution,
+            },
+        });
+        
+        Self::new("context.update", Some(params))
+    }
+    
+    /// Create context.update notification for a change to the tool directory allowlist
+    pub fn context_update_safe_directories(safe_directories: &[String]) -> Self {
+        let params = serde_json::json!({
+            "tools": {
+                "safe_directories": safe_directories,
+            },
+        });
+        
+        Self::new("context.update", Some(params))
+    }
+    
     /// Create context.update notification with multiple context fields
-    #[allow(dead_code)] // Public API for future multi-field context updates
     pub fn context_update_full(cwd: Option<String>, terminal_size: Option<(u16, u16)>) -> Self {
         let mut context = serde_json::Map::new();
         
@@ -196,6 +715,146 @@ impl Notification {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_response_validate() {
+        let parse = |line: &str| serde_json::from_str::<Response>(line).unwrap();
+
+        assert!(parse(r#"{"jsonrpc":"2.0","id":1,"result":{"ok":true}}"#).validate().is_ok());
+        assert!(parse(r#"{"jsonrpc":"2.0","id":2,"error":{"code":-32601,"message":"nope"}}"#).validate().is_ok());
+        // A null result is still a result (void methods)
+        assert!(parse(r#"{"jsonrpc":"2.0","id":5,"result":null}"#).validate().is_ok());
+
+        let both = parse(r#"{"jsonrpc":"2.0","id":3,"result":{},"error":{"code":-32000,"message":"bad"}}"#);
+        let err = both.validate().unwrap_err();
+        assert!(matches!(err, IpcError::ProtocolError(_)));
+        assert!(err.to_string().contains("response 3 has both"), "{}", err);
+
+        let neither = parse(r#"{"jsonrpc":"2.0","id":"abc"}"#);
+        let err = neither.validate().unwrap_err();
+        assert!(matches!(err, IpcError::ProtocolError(_)));
+        assert!(err.to_string().contains(r#"response "abc" has neither"#), "{}", err);
+    }
+
+    #[test]
+    fn test_request_roundtrip() {
+        Request::initialize(1).validate_roundtrip().unwrap();
+        Request::agent_query(2, "hello").validate_roundtrip().unwrap();
+        let options = QueryOptions {
+            system_prompt: Some("Be brief".to_string()),
+            history: vec![ContextMessage { role: "user".to_string(), content: "hi".to_string() }],
+            parent: Some("m1".to_string()),
+            temperature: Some(0.3),
+            max_tokens: Some(512),
+        };
+        Request::agent_query_with_options(3, "and then?", &options, false).validate_roundtrip().unwrap();
+        Request::agent_continue(4, "q1", &options, true).validate_roundtrip().unwrap();
+    }
+
+    #[test]
+    fn test_context_update_roundtrip() {
+        Notification::context_update("/tmp").validate_roundtrip().unwrap();
+        Notification::context_update_terminal_size(120, 40).validate_roundtrip().unwrap();
+        Notification::context_update_tools(true).validate_roundtrip().unwrap();
+        Notification::context_update_safe_directories(&["/home/user".to_string()]).validate_roundtrip().unwrap();
+        Notification::context_update_full(Some("/tmp".to_string()), Some((80, 24))).validate_roundtrip().unwrap();
+        Notification::context_update_full(None, None).validate_roundtrip().unwrap();
+    }
+
+    #[test]
+    fn test_roundtrip_rejects_lossy_messages() {
+        // NaN serializes as null, which doesn't parse back as a number
+        #[derive(Serialize, Deserialize)]
+        struct Loose { id: f64 }
+        let err = validate_roundtrip(&Loose { id: f64::NAN }).unwrap_err();
+        assert!(matches!(err, IpcError::ProtocolError(_) | IpcError::ParseError(_)), "{:?}", err);
+    }
+
+    #[test]
+    fn test_redact_sensitive() {
+        let mut value = serde_json::json!({
+            "method": "context.auth",
+            "params": {
+                "api_key": "sk-123",
+                "headers": [{"Authorization": "Bearer abc"}],
+                "total_tokens": 42,
+                "content": "hello",
+            },
+        });
+        redact_sensitive(&mut value);
+        assert_eq!(value["params"]["api_key"], REDACTED);
+        assert_eq!(value["params"]["headers"][0]["Authorization"], REDACTED);
+        assert_eq!(value["params"]["total_tokens"], 42);
+        assert_eq!(value["params"]["content"], "hello");
+    }
+
+    #[test]
+    fn test_stream_token_params() {
+        let n = Notification::new("stream.token", Some(serde_json::json!({"content": "Hel"})));
+        assert_eq!(n.params_as::<StreamTokenParams>().unwrap().content, "Hel");
+
+        let n = Notification::new("stream.token", Some(serde_json::json!({"content": 5})));
+        assert!(n.params_as::<StreamTokenParams>().is_err());
+        let n = Notification::new("stream.token", None);
+        assert!(n.params_as::<StreamTokenParams>().is_err());
+    }
+
+    #[test]
+    fn test_stream_block_params() {
+        let n = Notification::new("stream.block", Some(serde_json::json!({
+            "type": "code", "content": "ls -la", "language": "bash",
+        })));
+        let params: StreamBlockParams = n.params_as().unwrap();
+        assert_eq!(params.block_type, "code");
+        assert_eq!(params.content, "ls -la");
+        assert_eq!(params.language(), "bash");
+
+        // Type and language are optional
+        let n = Notification::new("stream.block", Some(serde_json::json!({"content": "plain"})));
+        let params: StreamBlockParams = n.params_as().unwrap();
+        assert_eq!(params.block_type, "text");
+        assert_eq!(params.language(), "text");
+
+        let n = Notification::new("stream.block", Some(serde_json::json!({"type": "code"})));
+        assert!(n.params_as::<StreamBlockParams>().is_err());
+    }
+
+    #[test]
+    fn test_tool_approval_params() {
+        let n = Notification::new("tool.request_approval", Some(serde_json::json!({
+            "execution_id": "exec-1",
+            "tool_name": "shell",
+            "description": "Run a command",
+            "risk_level": "high",
+            "preview": "rm -rf build",
+        })));
+        let params: ToolApprovalParams = n.params_as().unwrap();
+        assert_eq!(params.execution_id, "exec-1");
+        assert_eq!(params.tool_name, "shell");
+        assert_eq!(params.risk_level, "high");
+        assert_eq!(params.preview, "rm -rf build");
+
+        let n = Notification::new("tool.request_approval", Some(serde_json::json!({"execution_id": "exec-2"})));
+        let params: ToolApprovalParams = n.params_as().unwrap();
+        assert_eq!(params.tool_name, "unknown");
+        assert_eq!(params.risk_level, "unknown");
+        assert_eq!(params.description, "");
+
+        // Without an execution ID the request can't be answered
+        let n = Notification::new("tool.request_approval", Some(serde_json::json!({"tool_name": "shell"})));
+        assert!(n.params_as::<ToolApprovalParams>().is_err());
+    }
+
+    #[test]
+    fn test_context_auth_params() {
+        let n = Notification::new("context.auth", Some(serde_json::json!({"auth_id": "a1"})));
+        let params: ContextAuthParams = n.params_as().unwrap();
+        assert_eq!(params.auth_id, "a1");
+        assert_eq!(params.prompt, "Credential");
+
+        let n = Notification::new("context.auth", Some(serde_json::json!({"auth_id": null, "prompt": "Token"})));
+        assert!(n.params_as::<ContextAuthParams>().is_err());
+    }
+
     #[test]
     fn test_serialize_request() {
         let req = Request::new(1, "test_method", None);
@@ -205,6 +864,199 @@ mod tests {
         assert!(json.contains("\"method\":\"test_method\""));
     }
 
+    #[test]
+    fn test_parse_initialize_server_info() {
+        let response: Response = serde_json::from_value(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "status": "ready",
+                "server_info": {
+                    "name": "openagent-terminal-backend",
+                    "version": "1.4.2",
+                    "start_time": "2025-10-04T12:00:00Z"
+                },
+                "capabilities": ["streaming", "blocks"]
+            }
+        })).unwrap();
+
+        let init = InitializeResult::from_response(&response).unwrap();
+        let info = init.server_info.unwrap();
+        assert_eq!(info.name, "openagent-terminal-backend");
+        assert_eq!(info.version, "1.4.2");
+        assert_eq!(init.capabilities, vec!["streaming", "blocks"]);
+
+        let now: DateTime<Utc> = "2025-10-04T13:30:00Z".parse().unwrap();
+        assert_eq!(info.uptime(now), Some(std::time::Duration::from_secs(5400)));
+
+        assert!(info.major_version_mismatch("0.1.0"));
+        assert!(!info.major_version_mismatch("1.0.0"));
+        assert!(!info.major_version_mismatch("not-a-version"));
+    }
+
+    #[test]
+    fn test_parse_initialize_without_server_info() {
+        let response: Response = serde_json::from_value(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {"status": "ready"}
+        })).unwrap();
+
+        let init = InitializeResult::from_response(&response).unwrap();
+        assert!(init.server_info.is_none());
+        assert!(init.capabilities.is_empty());
+    }
+
+    #[test]
+    fn test_agent_query_without_system_prompt() {
+        let req = Request::agent_query(1, "hello");
+        let params = req.params.unwrap();
+        assert_eq!(params["message"], "hello");
+        assert!(params.get("system").is_none());
+    }
+
+    #[test]
+    fn test_agent_query_with_system_prompt() {
+        let options = QueryOptions {
+            system_prompt: Some("You are a concise shell expert.".to_string()),
+            ..Default::default()
+        };
+        let req = Request::agent_query_with_options(1, "hello", &options, true);
+        let params = req.params.unwrap();
+        assert_eq!(params["system"], "You are a concise shell expert.");
+        assert_eq!(params["options"]["stream"], true);
+
+        // Blank prompts are not sent
+        let options = QueryOptions { system_prompt: Some("   ".to_string()), ..Default::default() };
+        let params = Request::agent_query_with_options(2, "hi", &options, true).params.unwrap();
+        assert!(params.get("system").is_none());
+        assert!(params.get("context").is_none());
+    }
+
+    #[test]
+    fn test_agent_query_history_context() {
+        let options = QueryOptions {
+            history: vec![ContextMessage { role: "user".to_string(), content: "list files".to_string() }],
+            ..Default::default()
+        };
+        let params = Request::agent_query_with_options(1, "and hidden ones?", &options, true).params.unwrap();
+        assert_eq!(params["context"]["history"][0]["role"], "user");
+        assert_eq!(params["context"]["history"][0]["content"], "list files");
+    }
+
+    #[test]
+    fn test_agent_query_parent() {
+        let params = Request::agent_query(1, "hi").params.unwrap();
+        assert!(params.get("parent").is_none());
+
+        let options = QueryOptions { parent: Some("msg-1".to_string()), ..Default::default() };
+        let params = Request::agent_query_with_options(2, "and then?", &options, true).params.unwrap();
+        assert_eq!(params["parent"], "msg-1");
+    }
+
+    #[test]
+    fn test_agent_query_sampling_options() {
+        let params = Request::agent_query(1, "hi").params.unwrap();
+        assert!(params["options"].get("temperature").is_none());
+        assert!(params["options"].get("max_tokens").is_none());
+
+        let options = QueryOptions { temperature: Some(0.7), max_tokens: Some(256), ..Default::default() };
+        let params = Request::agent_query_with_options(2, "hi", &options, true).params.unwrap();
+        assert_eq!(params["options"]["temperature"], 0.7);
+        assert_eq!(params["options"]["max_tokens"], 256);
+    }
+
+    #[test]
+    fn test_agent_continue() {
+        let options = QueryOptions { max_tokens: Some(256), system_prompt: Some("unused".to_string()), ..Default::default() };
+        let req = Request::agent_continue(5, "q1", &options, true);
+        assert_eq!(req.method, "agent.continue");
+        let params = req.params.unwrap();
+        assert_eq!(params["query_id"], "q1");
+        assert_eq!(params["options"]["stream"], true);
+        assert_eq!(params["options"]["max_tokens"], 256);
+        assert!(params.get("system").is_none());
+    }
+
+    #[test]
+    fn test_truncated_finish_reason() {
+        use serde_json::json;
+        assert!(is_truncated(&json!({"query_id": "q1", "status": "success", "finish_reason": "length"})));
+        assert!(is_truncated(&json!({"query_id": "q1", "metadata": {"finish_reason": "max_tokens"}})));
+        assert!(!is_truncated(&json!({"query_id": "q1", "status": "success", "finish_reason": "stop"})));
+        assert!(!is_truncated(&json!({"query_id": "q1", "status": "success"})));
+        assert!(!is_truncated(&json!({"finish_reason": 3})));
+    }
+
+    #[test]
+    fn test_stream_complete_params() {
+        use serde_json::json;
+        let params: StreamCompleteParams = serde_json::from_value(json!({
+            "query_id": "q1",
+            "status": "success",
+            "metadata": {"total_tokens": 150, "model_used": "m", "finish_reason": "content_filter"},
+        })).unwrap();
+        assert_eq!(params.query_id.as_deref(), Some("q1"));
+        assert_eq!(params.metadata.total_tokens, Some(150));
+        assert_eq!(params.finish_reason(), Some(&FinishReason::ContentFilter));
+
+        let reason = |value: Value| StreamCompleteParams::deserialize(&value).unwrap().finish_reason().cloned();
+        assert_eq!(reason(json!({"finish_reason": "stop"})), Some(FinishReason::Stop));
+        assert_eq!(reason(json!({"finish_reason": "length"})), Some(FinishReason::Length));
+        assert_eq!(reason(json!({"finish_reason": "max_tokens"})), Some(FinishReason::Length));
+        assert_eq!(reason(json!({"finish_reason": "error"})), Some(FinishReason::Error));
+        assert_eq!(reason(json!({"finish_reason": "tool_calls"})), Some(FinishReason::Other));
+        assert_eq!(reason(json!({"status": "success"})), None);
+
+        // A mistyped field is dropped on its own
+        let params: StreamCompleteParams = serde_json::from_value(json!({
+            "query_id": 7,
+            "finish_reason": "length",
+            "metadata": {"total_tokens": "150", "finish_reason": "stop"},
+        })).unwrap();
+        assert_eq!(params.query_id, None);
+        assert_eq!(params.finish_reason, Some(FinishReason::Length));
+        assert_eq!(params.metadata.total_tokens, None);
+        assert_eq!(params.metadata.finish_reason, Some(FinishReason::Stop));
+        let params: StreamCompleteParams = serde_json::from_value(json!({
+            "finish_reason": ["length"],
+            "metadata": {"total_tokens": 150},
+        })).unwrap();
+        assert_eq!(params.finish_reason(), None);
+        assert_eq!(params.metadata.total_tokens, Some(150));
+        let params: StreamCompleteParams = serde_json::from_value(json!({"metadata": "none", "status": "success"})).unwrap();
+        assert_eq!(params.metadata, CompleteMetadata::default());
+        assert_eq!(params.status.as_deref(), Some("success"));
+    }
+
+    #[test]
+    fn test_turn_id() {
+        let complete = serde_json::json!({"query_id": "q1", "message_id": "m1", "turn_id": "t1"});
+        assert_eq!(turn_id(&complete), Some("m1"));
+        assert_eq!(turn_id(&serde_json::json!({"query_id": "q1", "turn_id": "t1"})), Some("t1"));
+        assert_eq!(turn_id(&serde_json::json!({"query_id": "q1", "status": "success"})), Some("q1"));
+        assert_eq!(turn_id(&serde_json::json!({"status": "success"})), None);
+    }
+
+    #[test]
+    fn test_agent_query_stream_flag() {
+        let options = QueryOptions::default();
+        let params = Request::agent_query_with_options(1, "hi", &options, false).params.unwrap();
+        assert_eq!(params["options"]["stream"], false);
+        let params = Request::agent_query_with_options(2, "hi", &options, true).params.unwrap();
+        assert_eq!(params["options"]["stream"], true);
+    }
+
+    #[test]
+    fn test_tool_approve_includes_timeout() {
+        let req = Request::tool_approve(7, "exec-1", true, 45);
+        assert_eq!(req.method, "tool.approve");
+        let params = req.params.unwrap();
+        assert_eq!(params["execution_id"], "exec-1");
+        assert_eq!(params["approved"], true);
+        assert_eq!(params["command_timeout"], 45);
+    }
+
     #[test]
     fn test_initialize_request() {
         let req = Request::initialize(1);
@@ -214,4 +1066,73 @@ mod tests {
         let params = req.params.unwrap();
         assert!(params.get("terminal_size").is_some());
     }
+
+    #[test]
+    fn test_stream_content() {
+        let token = Notification::new("stream.token", Some(serde_json::json!({"content": "Hel"})));
+        assert_eq!(token.stream_content(), Some("Hel"));
+        let block = Notification::new("stream.block", Some(serde_json::json!({"type": "code", "content": "ls"})));
+        assert_eq!(block.stream_content(), Some("ls"));
+        let complete = Notification::new("stream.complete", Some(serde_json::json!({"content": "x"})));
+        assert_eq!(complete.stream_content(), None);
+    }
+
+    #[test]
+    fn test_describe_invalid_message() {
+        let describe = |line: &str| {
+            let strict = serde_json::from_str::<Response>(line).unwrap_err();
+            describe_invalid_message(line, &strict)
+        };
+
+        assert!(describe("{not json").starts_with("invalid JSON"));
+        assert_eq!(describe("[1, 2]"), "message is not a JSON object");
+        assert!(describe(r#"{"id": 1, "result": {}}"#).contains("'jsonrpc' version"));
+        assert_eq!(describe(r#"{"jsonrpc": "2.0", "result": {}}"#), "response missing 'id'");
+        assert_eq!(describe(r#"{"jsonrpc": "2.0", "id": null, "error": {}}"#), "response missing 'id'");
+        assert_eq!(describe(r#"{"jsonrpc": "2.0"}"#), "neither 'id' nor 'method' present");
+        assert_eq!(describe(r#"{"jsonrpc": "2.0", "id": 3, "data": 1}"#), "response 3 missing 'result' or 'error'");
+        assert!(describe(r#"{"jsonrpc": "2.0", "id": -1, "result": {}}"#).starts_with("response has invalid 'id' -1"));
+        assert_eq!(
+            describe(r#"{"jsonrpc": "2.0", "id": 4, "error": {"code": "bad"}}"#),
+            "response 4 has malformed 'error' (needs integer 'code' and string 'message')"
+        );
+        assert_eq!(
+            describe(r#"{"jsonrpc": "2.0", "id": 5, "method": "x"}"#),
+            "unexpected request from backend (has both 'id' and 'method')"
+        );
+        assert_eq!(describe(r#"{"jsonrpc": "2.0", "method": 7}"#), "notification 'method' is not a string");
+        assert_eq!(
+            describe(r#"{"jsonrpc": "2.0", "method": "stream.token", "params": "hi"}"#),
+            "notification 'stream.token' has non-structured 'params'"
+        );
+        // Right shape, but strict parsing still fails: the serde reason is kept
+        assert!(describe(r#"{"jsonrpc": "2.0", "id": 6, "result": {}, "extra": 1}"#)
+            .starts_with("response 6 rejected: unknown field `extra`"));
+    }
+
+    #[test]
+    fn test_parse_models_list() {
+        let result = serde_json::json!({
+            "models": [
+                { "name": "gpt-4", "context_window": 128000, "description": "Most capable" },
+                { "name": "mock" },
+                "local-llama"
+            ]
+        });
+        let models = parse_models_list(&result).unwrap();
+        assert_eq!(models.len(), 3);
+        assert_eq!(models[0], ModelInfo {
+            name: "gpt-4".to_string(),
+            context_window: Some(128000),
+            description: Some("Most capable".to_string()),
+        });
+        assert_eq!(models[1].context_window, None);
+        assert_eq!(models[2].name, "local-llama");
+
+        // A bare array works too; anything else is an error
+        let bare = parse_models_list(&serde_json::json!(["mock"])).unwrap();
+        assert_eq!(bare[0].name, "mock");
+        assert!(parse_models_list(&serde_json::json!({ "models": "mock" })).is_err());
+        assert!(Request::models_list(1).params.is_none());
+    }
 }
@@ -4,6 +4,7 @@
 pub mod client;
 pub mod message;
 pub mod error;
+pub mod rate_limit;
 
 #[cfg(test)]
 mod client_tests;
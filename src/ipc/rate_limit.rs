@@ -0,0 +1,138 @@
+// Rate limiting - Client-side cap on how often a method may be called
+//
+// Guards the backend (and a paid model behind it) against runaway loops. Each
+// limited method gets a token bucket that refills continuously; methods without
+// a limit, such as the `session.*` control RPCs, always pass.
+
+use crate::clock::{Clock, SystemClock};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Bucket of up to `capacity` calls, refilled evenly over a minute
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    /// Calls allowed per minute, and the most that can be saved up
+    capacity: f64,
+    tokens: f64,
+    last_refill: DateTime<Utc>,
+}
+
+impl TokenBucket {
+    /// Allow `limit` calls per minute, starting full at `now`
+    pub fn per_minute(limit: u32, now: DateTime<Utc>) -> Self {
+        let capacity = f64::from(limit.max(1));
+        Self { capacity, tokens: capacity, last_refill: now }
+    }
+
+    /// Take a token, or return how long until one is available
+    pub fn try_acquire(&mut self, now: DateTime<Utc>) -> Result<(), Duration> {
+        let elapsed = (now - self.last_refill).to_std().unwrap_or_default();
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.capacity / 60.0).min(self.capacity);
+        self.last_refill = self.last_refill.max(now);
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            let wait_ms = (1.0 - self.tokens) * 60_000.0 / self.capacity;
+            Err(Duration::from_millis(wait_ms.round() as u64))
+        }
+    }
+}
+
+/// Token buckets keyed on method name
+pub struct RateLimiter {
+    clock: Arc<dyn Clock>,
+    buckets: HashMap<String, TokenBucket>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::with_clock(Arc::new(SystemClock))
+    }
+}
+
+impl RateLimiter {
+    /// Create a limiter with a custom clock (for deterministic tests)
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        Self { clock, buckets: HashMap::new() }
+    }
+
+    /// Allow at most `per_minute` calls of `method` (0 removes the limit)
+    pub fn set_limit(&mut self, method: &str, per_minute: u32) {
+        if per_minute == 0 {
+            self.buckets.remove(method);
+        } else {
+            let bucket = TokenBucket::per_minute(per_minute, self.clock.now());
+            self.buckets.insert(method.to_string(), bucket);
+        }
+    }
+
+    /// Record a call of `method`, or return how long to wait if it's over its limit
+    pub fn check(&mut self, method: &str) -> Result<(), Duration> {
+        match self.buckets.get_mut(method) {
+            Some(bucket) => bucket.try_acquire(self.clock.now()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use chrono::TimeZone;
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 10, 4, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_bucket_allows_burst_then_refills() {
+        let mut bucket = TokenBucket::per_minute(3, start());
+        for _ in 0..3 {
+            assert!(bucket.try_acquire(start()).is_ok());
+        }
+        // One token comes back every 20s at 3 per minute
+        assert_eq!(bucket.try_acquire(start()), Err(Duration::from_secs(20)));
+
+        let later = start() + chrono::Duration::seconds(5);
+        assert_eq!(bucket.try_acquire(later), Err(Duration::from_secs(15)));
+
+        let refilled = start() + chrono::Duration::seconds(20);
+        assert!(bucket.try_acquire(refilled).is_ok());
+        assert!(bucket.try_acquire(refilled).is_err());
+
+        // A long idle period refills only up to capacity
+        let idle = refilled + chrono::Duration::minutes(10);
+        for _ in 0..3 {
+            assert!(bucket.try_acquire(idle).is_ok());
+        }
+        assert!(bucket.try_acquire(idle).is_err());
+    }
+
+    #[test]
+    fn test_limiter_keyed_on_method() {
+        let clock = Arc::new(MockClock::new(start()));
+        let mut limiter = RateLimiter::with_clock(clock.clone());
+        limiter.set_limit("agent.query", 2);
+
+        assert!(limiter.check("agent.query").is_ok());
+        assert!(limiter.check("agent.query").is_ok());
+        assert_eq!(limiter.check("agent.query"), Err(Duration::from_secs(30)));
+
+        // Methods without a limit are never held back
+        for _ in 0..100 {
+            assert!(limiter.check("session.list").is_ok());
+        }
+
+        clock.advance(chrono::Duration::seconds(30));
+        assert!(limiter.check("agent.query").is_ok());
+
+        limiter.set_limit("agent.query", 0);
+        assert!(limiter.check("agent.query").is_ok());
+        assert!(limiter.check("agent.query").is_ok());
+    }
+}
@@ -3,8 +3,12 @@
 // Provides a line editing experience with cursor movement, history navigation,
 // and keyboard shortcuts with proper Unicode grapheme cluster support.
 
+use crate::config::WordBoundary;
+use crate::session::SessionCycle;
 use crossterm::event::{KeyCode, KeyModifiers};
 use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::path::Path;
 use unicode_segmentation::UnicodeSegmentation;
 
 /// Actions that result from key handling
@@ -27,15 +31,88 @@ pub enum EditorAction {
     /// Clear screen (Ctrl+L)
     ClearScreen,
     /// Show history list
+    #[allow(dead_code)] // Not bound to a key yet
     ShowHistory,
     /// Reverse search (Ctrl+R)
     ReverseSearch,
+    /// Forward search toward newer entries (Ctrl+S)
+    ReverseSearchForward,
     /// Delete to beginning of line (Ctrl+U)
     DeleteToStart,
     /// Delete to end of line (Ctrl+K)
     DeleteToEnd,
     /// Delete previous word (Ctrl+W)
     DeletePrevWord,
+    /// Edit the buffer in an external editor (Ctrl+X Ctrl+E)
+    ExternalEdit,
+    /// Open the command palette (Ctrl+P)
+    CommandPalette,
+    /// Switch to the previous or next session in recency order
+    CycleSession(SessionCycle),
+}
+
+/// Prompt in front of every input line after the first
+pub const CONTINUATION_PROMPT: &str = "... ";
+
+/// The input as drawn at the prompt
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RenderedInput {
+    /// Lines to draw, prompts included
+    pub lines: Vec<String>,
+    /// Line the cursor is on
+    pub cursor_row: usize,
+    /// Display column of the cursor on that line
+    pub cursor_col: usize,
+}
+
+/// A key plus modifiers, parsed from a config string like `Alt+Up`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// Parse `Ctrl+`, `Alt+` and `Shift+` prefixes followed by a key name
+    /// (`Up`, `PageDown`, `F5`, `Tab`, a single character, ...), case-insensitively
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut parts: Vec<&str> = spec.split('+').map(str::trim).collect();
+        let key = parts.pop().filter(|k| !k.is_empty())?;
+        let mut modifiers = KeyModifiers::NONE;
+        for part in parts {
+            modifiers |= match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+        }
+        let lower = key.to_ascii_lowercase();
+        let code = match lower.as_str() {
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "tab" => KeyCode::Tab,
+            "enter" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            _ => match lower.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                Some(n @ 1..=12) => KeyCode::F(n),
+                _ if lower.chars().count() == 1 => KeyCode::Char(lower.chars().next()?),
+                _ => return None,
+            },
+        };
+        Some(Self { code, modifiers })
+    }
+
+    /// Whether a key event is this binding
+    pub fn matches(&self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        self.code == code && self.modifiers == modifiers
+    }
 }
 
 /// Line editor with cursor and history management
@@ -50,6 +127,8 @@ pub struct LineEditor {
     search_query: String,
     /// Reverse search result index
     search_result_idx: Option<usize>,
+    /// Buffer saved when reverse search started, restored on cancel
+    search_original: String,
     /// Command history (most recent last)
     history: VecDeque<String>,
     /// Current position in history during navigation (None = not navigating)
@@ -58,6 +137,16 @@ pub struct LineEditor {
     saved_buffer: Option<String>,
     /// Maximum history size
     max_history: usize,
+    /// Entries added since the history was last loaded or saved
+    unsaved: usize,
+    /// Ctrl+X prefix pressed, waiting for the second key of the chord
+    pending_ctrl_x: bool,
+    /// Privacy mode: render the buffer as bullets (the buffer itself is unchanged)
+    mask: bool,
+    /// Word rule for word movement and deletion
+    word_boundary: WordBoundary,
+    /// Previous/next session keys, if bound
+    session_keys: Option<(KeyBinding, KeyBinding)>,
 }
 
 impl LineEditor {
@@ -70,9 +159,15 @@ impl LineEditor {
             history_index: None,
             saved_buffer: None,
             max_history: 1000,
+            unsaved: 0,
             reverse_search: false,
             search_query: String::new(),
             search_result_idx: None,
+            search_original: String::new(),
+            pending_ctrl_x: false,
+            mask: false,
+            word_boundary: WordBoundary::default(),
+            session_keys: None,
         }
     }
     
@@ -86,14 +181,86 @@ impl LineEditor {
             history_index: None,
             saved_buffer: None,
             max_history,
+            unsaved: 0,
             reverse_search: false,
             search_query: String::new(),
             search_result_idx: None,
+            search_original: String::new(),
+            pending_ctrl_x: false,
+            mask: false,
+            word_boundary: WordBoundary::default(),
+            session_keys: None,
         }
     }
     
+    /// Keys for switching to the previous and next session (checked before other bindings)
+    pub fn set_session_keys(&mut self, previous: KeyBinding, next: KeyBinding) {
+        self.session_keys = Some((previous, next));
+    }
+    
+    /// Choose what word movement and Ctrl+W treat as a word
+    pub fn set_word_boundary(&mut self, word_boundary: WordBoundary) {
+        self.word_boundary = word_boundary;
+    }
+    
     /// Handle a key event and return the appropriate action
     pub fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> EditorAction {
+        // Resolve a pending Ctrl+X chord; any other key cancels the prefix
+        if std::mem::take(&mut self.pending_ctrl_x) {
+            match (code, modifiers) {
+                (KeyCode::Char('e'), KeyModifiers::CONTROL) => return EditorAction::ExternalEdit,
+                (KeyCode::Char('p'), KeyModifiers::CONTROL) => {
+                    self.toggle_mask();
+                    return EditorAction::Redraw;
+                }
+                _ => {}
+            }
+        }
+        
+        if self.reverse_search {
+            match (code, modifiers) {
+                (KeyCode::Char('r'), KeyModifiers::CONTROL) => {
+                    self.search_find_next();
+                    return EditorAction::Redraw;
+                }
+                (KeyCode::Char('s'), KeyModifiers::CONTROL) => {
+                    self.search_find_prev();
+                    return EditorAction::Redraw;
+                }
+                (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                    if let Some(c) = sanitize_input_char(c) {
+                        self.search_add_char(c);
+                    }
+                    return EditorAction::Redraw;
+                }
+                (KeyCode::Backspace, KeyModifiers::NONE) => {
+                    self.search_backspace();
+                    return EditorAction::Redraw;
+                }
+                (KeyCode::Enter, KeyModifiers::NONE) => {
+                    self.accept_reverse_search();
+                    return EditorAction::Redraw;
+                }
+                (KeyCode::Esc, _)
+                | (KeyCode::Char('g'), KeyModifiers::CONTROL)
+                | (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                    self.cancel_reverse_search();
+                    return EditorAction::Redraw;
+                }
+                // Like readline, any other key keeps the match and then acts normally
+                _ => self.accept_reverse_search(),
+            }
+        }
+        
+        if let Some((previous, next)) = self.session_keys {
+            if previous.matches(code, modifiers) {
+                return EditorAction::CycleSession(SessionCycle::Previous);
+            }
+            if next.matches(code, modifiers) {
+                return EditorAction::CycleSession(SessionCycle::Next);
+            }
+        }
+        
         match (code, modifiers) {
             // Navigation
             (KeyCode::Left, KeyModifiers::NONE) => {
@@ -112,6 +279,14 @@ impl LineEditor {
                 self.move_word_right();
                 EditorAction::Redraw
             }
+            (KeyCode::Left, KeyModifiers::ALT) => {
+                self.move_arg_left();
+                EditorAction::Redraw
+            }
+            (KeyCode::Right, KeyModifiers::ALT) => {
+                self.move_arg_right();
+                EditorAction::Redraw
+            }
             (KeyCode::Home, _) | (KeyCode::Char('a'), KeyModifiers::CONTROL) => {
                 self.cursor = 0;
                 EditorAction::Redraw
@@ -123,6 +298,10 @@ impl LineEditor {
             
             // Editing
             (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                let Some(c) = sanitize_input_char(c) else {
+                    return EditorAction::None;
+                };
+                self.clamp_cursor_to_grapheme_boundary();
                 self.buffer.insert(self.cursor, c);
                 self.cursor += c.len_utf8();
                 EditorAction::Redraw
@@ -142,19 +321,40 @@ impl LineEditor {
                 EditorAction::DeleteToStart
             }
             
-            // History navigation
+            // Line movement in multi-line input, history navigation otherwise
             (KeyCode::Up, KeyModifiers::NONE) => {
-                EditorAction::HistoryUp
+                if self.move_line(true) {
+                    EditorAction::Redraw
+                } else {
+                    EditorAction::HistoryUp
+                }
             }
             (KeyCode::Down, KeyModifiers::NONE) => {
-                EditorAction::HistoryDown
+                if self.move_line(false) {
+                    EditorAction::Redraw
+                } else {
+                    EditorAction::HistoryDown
+                }
             }
             
             // Commands
             (KeyCode::Enter, KeyModifiers::NONE) => {
+                // A trailing backslash continues the input on a new line
+                if self.buffer.ends_with('\\') {
+                    self.buffer.pop();
+                    self.buffer.push('\n');
+                    self.cursor = self.buffer.len();
+                    return EditorAction::Redraw;
+                }
                 let input = self.buffer.clone();
                 EditorAction::Submit(input)
             }
+            (KeyCode::Enter, KeyModifiers::ALT) => {
+                self.clamp_cursor_to_grapheme_boundary();
+                self.buffer.insert(self.cursor, '\n');
+                self.cursor += 1;
+                EditorAction::Redraw
+            }
             (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
                 EditorAction::Cancel
             }
@@ -162,8 +362,9 @@ impl LineEditor {
                 if self.buffer.is_empty() {
                     EditorAction::Exit
                 } else {
-                    // Ctrl+D on non-empty line: delete char at cursor (optional)
-                    EditorAction::None
+                    // Ctrl+D on non-empty line: delete char at cursor (like readline)
+                    self.delete_grapheme_forward();
+                    EditorAction::Redraw
                 }
             }
             (KeyCode::Char('k'), KeyModifiers::CONTROL) => {
@@ -175,6 +376,16 @@ impl LineEditor {
             (KeyCode::Char('r'), KeyModifiers::CONTROL) => {
                 EditorAction::ReverseSearch
             }
+            (KeyCode::Char('s'), KeyModifiers::CONTROL) => {
+                EditorAction::ReverseSearchForward
+            }
+            (KeyCode::Char('p'), KeyModifiers::CONTROL) => {
+                EditorAction::CommandPalette
+            }
+            (KeyCode::Char('x'), KeyModifiers::CONTROL) => {
+                self.pending_ctrl_x = true;
+                EditorAction::None
+            }
             
             _ => EditorAction::None,
         }
@@ -194,10 +405,12 @@ impl LineEditor {
         }
         
         self.history.push_back(command.to_string());
+        self.unsaved += 1;
         
         if self.history.len() > self.max_history {
             self.history.pop_front();
         }
+        self.unsaved = self.unsaved.min(self.history.len());
     }
     
     /// Navigate up in history (older commands)
@@ -240,10 +453,10 @@ impl LineEditor {
     pub fn set_buffer(&mut self, text: String) {
         self.buffer = text;
         self.cursor = self.buffer.len();
+        self.clamp_cursor_to_grapheme_boundary();
     }
     
     /// Get the current buffer
-    #[allow(dead_code)]
     pub fn get_buffer(&self) -> &str {
         &self.buffer
     }
@@ -257,10 +470,29 @@ impl LineEditor {
         self.reverse_search = false;
         self.search_query.clear();
         self.search_result_idx = None;
+        self.search_original.clear();
     }
     
     // === Unicode-aware cursor movement ===
     
+    /// Move the cursor back to the nearest grapheme boundary at or before it
+    ///
+    /// `String::insert`/`replace_range` panic on non-char boundaries, so any
+    /// buffer swap must leave the cursor on a boundary.
+    fn clamp_cursor_to_grapheme_boundary(&mut self) {
+        if self.cursor >= self.buffer.len() {
+            self.cursor = self.buffer.len();
+            return;
+        }
+        
+        self.cursor = self.buffer
+            .grapheme_indices(true)
+            .map(|(idx, _)| idx)
+            .take_while(|idx| *idx <= self.cursor)
+            .last()
+            .unwrap_or(0);
+    }
+    
     /// Move cursor left by one grapheme cluster
     fn move_cursor_left(&mut self) {
         if self.cursor == 0 {
@@ -302,15 +534,46 @@ impl LineEditor {
         self.cursor = self.buffer.len();
     }
     
+    /// Move the cursor to the same column on the line above (or below)
+    ///
+    /// Returns false, leaving the cursor alone, when already on the first
+    /// (or last) line of the input.
+    fn move_line(&mut self, up: bool) -> bool {
+        let start = self.buffer[..self.cursor].rfind('\n').map_or(0, |i| i + 1);
+        let end = self.buffer[self.cursor..].find('\n').map_or(self.buffer.len(), |i| self.cursor + i);
+        let (target_start, target_end) = if up {
+            if start == 0 {
+                return false;
+            }
+            (self.buffer[..start - 1].rfind('\n').map_or(0, |i| i + 1), start - 1)
+        } else {
+            if end == self.buffer.len() {
+                return false;
+            }
+            (end + 1, self.buffer[end + 1..].find('\n').map_or(self.buffer.len(), |i| end + 1 + i))
+        };
+        
+        // Stop on the last grapheme that still fits before the column
+        let column = crate::ansi::display_width(&self.buffer[start..self.cursor]);
+        let mut width = 0;
+        self.cursor = target_start;
+        for grapheme in self.buffer[target_start..target_end].graphemes(true) {
+            width += crate::ansi::display_width(grapheme);
+            if width > column {
+                break;
+            }
+            self.cursor += grapheme.len();
+        }
+        true
+    }
+    
     /// Move cursor left to the beginning of previous word
     fn move_word_left(&mut self) {
         if self.cursor == 0 {
             return;
         }
         
-        let words: Vec<(usize, &str)> = self.buffer
-            .unicode_word_indices()
-            .collect();
+        let words = word_indices(&self.buffer, self.word_boundary);
         
         // Find the word before current cursor
         for i in (0..words.len()).rev() {
@@ -330,9 +593,7 @@ impl LineEditor {
             return;
         }
         
-        let words: Vec<(usize, &str)> = self.buffer
-            .unicode_word_indices()
-            .collect();
+        let words = word_indices(&self.buffer, self.word_boundary);
         
         // Find the word after current cursor
         let mut found_current = false;
@@ -352,6 +613,39 @@ impl LineEditor {
         }
     }
     
+    /// Byte offsets where whitespace-separated arguments start
+    ///
+    /// Unlike Unicode words, an argument keeps its punctuation, so paths, URLs
+    /// and flags like `--format=json` count as a single token.
+    fn arg_starts(&self) -> Vec<usize> {
+        let mut starts = Vec::new();
+        let mut prev_is_space = true;
+        for (idx, c) in self.buffer.char_indices() {
+            if prev_is_space && !c.is_whitespace() {
+                starts.push(idx);
+            }
+            prev_is_space = c.is_whitespace();
+        }
+        starts
+    }
+    
+    /// Move cursor left to the beginning of the previous argument
+    fn move_arg_left(&mut self) {
+        self.cursor = self.arg_starts()
+            .into_iter()
+            .rev()
+            .find(|&start| start < self.cursor)
+            .unwrap_or(0);
+    }
+    
+    /// Move cursor right to the beginning of the next argument (or the end)
+    fn move_arg_right(&mut self) {
+        self.cursor = self.arg_starts()
+            .into_iter()
+            .find(|&start| start > self.cursor)
+            .unwrap_or(self.buffer.len());
+    }
+    
     /// Delete one grapheme cluster backward from cursor
     fn delete_grapheme_backward(&mut self) {
         if self.cursor == 0 {
@@ -415,9 +709,7 @@ impl LineEditor {
             return;
         }
         
-        let words: Vec<(usize, &str)> = self.buffer
-            .unicode_word_indices()
-            .collect();
+        let words = word_indices(&self.buffer, self.word_boundary);
         
         // Find the word that contains or is before the cursor
         let mut delete_start = 0;
@@ -445,7 +737,7 @@ impl LineEditor {
             // Check if there's whitespace between delete_start and cursor
             let between = &self.buffer[delete_start..self.cursor];
             if let Some(last_word_start) = between.rfind(|c: char| !c.is_whitespace()) {
-                delete_start = delete_start + between[..=last_word_start].rfind(|c: char| c.is_whitespace())
+                delete_start += between[..=last_word_start].rfind(|c: char| c.is_whitespace())
                     .map(|i| i + 1)
                     .unwrap_or(0);
             }
@@ -457,18 +749,36 @@ impl LineEditor {
     
     // === Reverse search support ===
     
-    /// Start reverse search mode
+    /// Start reverse search mode, remembering the buffer to restore on cancel
     pub fn start_reverse_search(&mut self) {
         self.reverse_search = true;
         self.search_query.clear();
         self.search_result_idx = None;
+        self.search_original = self.buffer.clone();
     }
     
-    /// Exit reverse search mode
+    /// Exit reverse search mode, leaving the buffer as it is
     pub fn exit_reverse_search(&mut self) {
         self.reverse_search = false;
         self.search_query.clear();
         self.search_result_idx = None;
+        self.search_original.clear();
+    }
+    
+    /// Leave reverse search with the current match (if any) in the buffer
+    pub fn accept_reverse_search(&mut self) {
+        if let Some(entry) = self.search_match().map(str::to_string) {
+            self.set_buffer(entry);
+        }
+        self.history_index = None;
+        self.exit_reverse_search();
+    }
+    
+    /// Leave reverse search and restore the buffer from before it started
+    pub fn cancel_reverse_search(&mut self) {
+        let original = std::mem::take(&mut self.search_original);
+        self.set_buffer(original);
+        self.exit_reverse_search();
     }
     
     /// Check if in reverse search mode
@@ -476,43 +786,74 @@ impl LineEditor {
         self.reverse_search
     }
     
-    /// Add character to search query and find next match
+    /// Add character to search query and find the newest match
+    ///
+    /// The current match is kept if it still matches the longer query.
     pub fn search_add_char(&mut self, c: char) -> Option<String> {
         self.search_query.push(c);
-        self.search_find_next()
+        let before = self.search_result_idx.map_or(self.history.len(), |i| i + 1);
+        self.search_older_than(before)
     }
     
-    /// Remove last character from search query
+    /// Remove last character from search query and search again from the newest entry
     pub fn search_backspace(&mut self) -> Option<String> {
         self.search_query.pop();
-        if self.search_query.is_empty() {
-            self.search_result_idx = None;
-            return None;
+        self.search_older_than(self.history.len())
+    }
+    
+    /// Cycle to the next older match, keeping the current one if there is none
+    pub fn search_find_next(&mut self) -> Option<String> {
+        let Some(current) = self.search_result_idx else {
+            return self.search_older_than(self.history.len());
+        };
+        let found = self.search_older_than(current);
+        if found.is_none() {
+            self.search_result_idx = Some(current);
         }
-        self.search_find_next()
+        found
     }
     
-    /// Find next matching history entry
-    fn search_find_next(&mut self) -> Option<String> {
+    /// Cycle back to the next newer match, stopping at the newest one
+    ///
+    /// Returns `None` (keeping the current match) rather than wrapping
+    /// around to the oldest entry.
+    pub fn search_find_prev(&mut self) -> Option<String> {
+        let current = self.search_result_idx?;
         if self.search_query.is_empty() {
             return None;
         }
         
-        let start_idx = self.search_result_idx.map(|i| i + 1).unwrap_or(0);
-        
-        // Search backwards through history
-        for (i, entry) in self.history.iter().enumerate().rev() {
-            if i < start_idx {
-                continue;
-            }
-            
-            if entry.contains(&self.search_query) {
-                self.search_result_idx = Some(i);
-                return Some(entry.clone());
-            }
+        let found = self.history
+            .iter()
+            .enumerate()
+            .skip(current + 1)
+            .find(|(_, entry)| entry.contains(&self.search_query));
+        let (i, entry) = found?;
+        self.search_result_idx = Some(i);
+        Some(entry.clone())
+    }
+    
+    /// Find the newest entry before index `before` containing the query
+    fn search_older_than(&mut self, before: usize) -> Option<String> {
+        self.search_result_idx = None;
+        if self.search_query.is_empty() {
+            return None;
         }
         
-        None
+        let found = self.history
+            .iter()
+            .enumerate()
+            .take(before)
+            .rev()
+            .find(|(_, entry)| entry.contains(&self.search_query));
+        let (i, entry) = found?;
+        self.search_result_idx = Some(i);
+        Some(entry.clone())
+    }
+    
+    /// The history entry the search currently points at
+    fn search_match(&self) -> Option<&str> {
+        self.search_result_idx.and_then(|i| self.history.get(i)).map(String::as_str)
     }
     
     /// Get current search query
@@ -520,21 +861,213 @@ impl LineEditor {
         &self.search_query
     }
     
-    /// Render the current line with cursor position
-    pub fn render(&self, prompt: &str) -> (String, usize) {
-        let line = format!("{}{}", prompt, self.buffer);
-        let cursor_pos = prompt.len() + self.cursor;
-        (line, cursor_pos)
+    /// Render the reverse search line and the cursor's display column
+    ///
+    /// Shows `(reverse-i-search)'query': entry` with the matched text
+    /// highlighted and the cursor on it, or `(failed reverse-i-search)` when
+    /// nothing matches. Newlines in the entry are drawn as ↵. In privacy mode
+    /// the query and entry are bullets, like the buffer.
+    pub fn render_search(&self, color: bool) -> (String, usize) {
+        let Some(entry) = self.search_match() else {
+            let label = if self.get_search_query().is_empty() { "(reverse-i-search)" } else { "(failed reverse-i-search)" };
+            let line = format!("{}'{}': ", label, self.search_display(self.get_search_query()));
+            let width = crate::ansi::display_width(&line);
+            return (line, width);
+        };
+        
+        let start = entry.find(&self.search_query).unwrap_or(0);
+        let end = start + self.search_query.len();
+        let (before, matched, after) = (&entry[..start], &entry[start..end], &entry[end..]);
+        let (before, matched, after) = (self.search_display(before), self.search_display(matched), self.search_display(after));
+        
+        let prefix = format!("(reverse-i-search)'{}': {}", self.search_display(self.get_search_query()), before);
+        let cursor = crate::ansi::display_width(&prefix);
+        let line = if color {
+            use crate::ansi::colors::{BOLD, RESET, YELLOW};
+            format!("{}{}{}{}{}{}", prefix, BOLD, YELLOW, matched, RESET, after)
+        } else {
+            format!("{}{}{}", prefix, matched, after)
+        };
+        (line, cursor)
+    }
+    
+    /// Text as the search line shows it: one line, masked in privacy mode
+    fn search_display(&self, text: &str) -> String {
+        if self.mask {
+            "•".repeat(text.graphemes(true).count())
+        } else {
+            text.replace('\n', "↵")
+        }
+    }
+    
+    /// Flip privacy mode, returning the new state
+    pub fn toggle_mask(&mut self) -> bool {
+        self.mask = !self.mask;
+        self.mask
+    }
+    
+    /// Whether typed input is rendered as bullets
+    pub fn is_masked(&self) -> bool {
+        self.mask
     }
     
-    /// Get recent history entries
-    pub fn get_recent_history(&self, limit: usize) -> Vec<&str> {
-        self.history
+    /// Render the input wrapped to `width` columns, and the cursor's position
+    ///
+    /// The first line of the buffer follows `prompt` and the rest
+    /// `CONTINUATION_PROMPT`; a line wider than `width` continues on the next
+    /// row the way the terminal would soft-wrap it. In privacy mode every
+    /// grapheme is drawn as one bullet, so neither the text nor its length in
+    /// columns gives the input away.
+    pub fn render(&self, prompt: &str, width: usize) -> RenderedInput {
+        let width = width.max(1);
+        let mut rendered = RenderedInput::default();
+        let mut offset = 0;
+        for (row, text) in self.buffer.split('\n').enumerate() {
+            let lead = if row == 0 { prompt } else { CONTINUATION_PROMPT };
+            let shown = |text: &str| {
+                if self.mask {
+                    "•".repeat(text.graphemes(true).count())
+                } else {
+                    text.to_string()
+                }
+            };
+            
+            let first_row = rendered.lines.len();
+            rendered.lines.extend(crate::ansi::wrap_display(&format!("{}{}", lead, shown(text)), width));
+            
+            let end = offset + text.len();
+            if (offset..=end).contains(&self.cursor) {
+                let before_cursor = crate::ansi::wrap_display(
+                    &format!("{}{}", lead, shown(&self.buffer[offset..self.cursor])),
+                    width,
+                );
+                rendered.cursor_row = first_row + before_cursor.len() - 1;
+                rendered.cursor_col = before_cursor.last().map_or(0, |line| crate::ansi::display_width(line));
+                // A full row leaves the cursor at the start of the next one
+                if rendered.cursor_col >= width {
+                    rendered.cursor_row += 1;
+                    rendered.cursor_col = 0;
+                    if rendered.cursor_row == rendered.lines.len() {
+                        rendered.lines.push(String::new());
+                    }
+                }
+            }
+            offset = end + 1;
+        }
+        rendered
+    }
+    
+    /// Get the last `limit` history entries matching `search`, oldest first
+    ///
+    /// Entries carry their 1-based position in the full history so they can
+    /// be referred to consistently across searches.
+    pub fn history_entries(&self, limit: usize, search: Option<&str>) -> Vec<(usize, &str)> {
+        let needle = search.map(|s| s.to_lowercase());
+        let mut entries: Vec<(usize, &str)> = self.history
             .iter()
+            .enumerate()
             .rev()
+            .filter(|(_, entry)| needle.as_ref().is_none_or(|n| entry.to_lowercase().contains(n)))
             .take(limit)
-            .map(|s| s.as_str())
-            .collect()
+            .map(|(i, entry)| (i + 1, entry.as_str()))
+            .collect();
+        entries.reverse();
+        entries
+    }
+    
+    /// Write the history (oldest first) to `path` as a JSON array
+    ///
+    /// Returns the number of entries written.
+    pub fn export_history(&self, path: &Path) -> io::Result<usize> {
+        let json = serde_json::to_string_pretty(&self.history)?;
+        std::fs::write(path, json)?;
+        Ok(self.history.len())
+    }
+    
+    /// Load the persistent history file written by `save_history`
+    ///
+    /// Entries go through `add_to_history`, so consecutive duplicates are
+    /// dropped and only the newest `max_history` are kept. A missing file
+    /// is an empty history. Returns the number of entries now in history.
+    pub fn load_history(&mut self, path: &Path) -> io::Result<usize> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e),
+        };
+        
+        for line in contents.lines().filter(|line| !line.is_empty()) {
+            // One JSON string per line (so multi-line entries fit); plain text is taken as is
+            let entry = serde_json::from_str::<String>(line).unwrap_or_else(|_| line.to_string());
+            self.add_to_history(&entry);
+        }
+        self.unsaved = 0;
+        Ok(self.history.len())
+    }
+    
+    /// Append the entries added since the last load or save to `path`
+    ///
+    /// Appending (rather than rewriting) keeps entries saved by other
+    /// terminals in the meantime. Once the file holds more than
+    /// `max_history` entries it is cut back to the newest ones, so it doesn't
+    /// grow without bound. Returns the number of entries written.
+    pub fn save_history(&mut self, path: &Path) -> io::Result<usize> {
+        if self.unsaved == 0 {
+            return Ok(0);
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        
+        let mut lines = String::new();
+        for entry in self.history.range(self.history.len() - self.unsaved..) {
+            lines.push_str(&serde_json::to_string(entry)?);
+            lines.push('\n');
+        }
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(lines.as_bytes())?;
+        let written = std::mem::take(&mut self.unsaved);
+        
+        let contents = std::fs::read_to_string(path)?;
+        let saved: Vec<&str> = contents.lines().filter(|line| !line.is_empty()).collect();
+        if saved.len() > self.max_history {
+            // Replace the file in one step, so a crash can't leave it half written
+            let mut kept = String::new();
+            for line in &saved[saved.len() - self.max_history..] {
+                kept.push_str(line);
+                kept.push('\n');
+            }
+            let mut file = tempfile::NamedTempFile::new_in(path.parent().unwrap_or(Path::new(".")))?;
+            file.write_all(kept.as_bytes())?;
+            file.persist(path).map_err(|e| e.error)?;
+        }
+        Ok(written)
+    }
+    
+    /// Append history entries from a file written by `export_history`
+    ///
+    /// Entries already in the history are skipped and `max_history` still
+    /// applies. Returns the number of entries added.
+    pub fn import_history(&mut self, path: &Path) -> io::Result<usize> {
+        let contents = std::fs::read_to_string(path)?;
+        let entries: Vec<String> = serde_json::from_str(&contents)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        
+        let mut added = 0;
+        for entry in entries {
+            if self.history.contains(&entry) {
+                continue;
+            }
+            let before = self.history.back().cloned();
+            self.add_to_history(&entry);
+            if self.history.back() != before.as_ref() {
+                added += 1;
+            }
+        }
+        Ok(added)
     }
     
     /// Get history size
@@ -550,6 +1083,86 @@ impl Default for LineEditor {
     }
 }
 
+/// Character to insert for a typed or pasted `c`, or `None` to drop it
+///
+/// Control characters would move the terminal cursor without the editor
+/// knowing, so they are dropped (a tab becomes a space). Bidi override and
+/// isolate marks are dropped too, since they reorder the rendered line.
+/// Zero-width joiners and combining marks are kept; the grapheme-based cursor
+/// movement treats the sequences they form as one character.
+pub fn sanitize_input_char(c: char) -> Option<char> {
+    match c {
+        '\t' => Some(' '),
+        c if c.is_control() => None,
+        '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}' => None,
+        c => Some(c),
+    }
+}
+
+/// Whether a key pressed while a response is streaming should cancel it (Esc or Ctrl+C)
+pub fn is_stream_cancel_key(code: KeyCode, modifiers: KeyModifiers) -> bool {
+    match code {
+        KeyCode::Esc => true,
+        KeyCode::Char('c') => modifiers.contains(KeyModifiers::CONTROL),
+        _ => false,
+    }
+}
+
+/// Words in `text` under `boundary`, as (byte offset, word) like `unicode_word_indices`
+pub fn word_indices(text: &str, boundary: WordBoundary) -> Vec<(usize, &str)> {
+    match boundary {
+        WordBoundary::Unicode => text.unicode_word_indices().collect(),
+        WordBoundary::Whitespace => spans(text, |c| !c.is_whitespace(), |_, _, _| false),
+        WordBoundary::Subword => spans(
+            text,
+            char::is_alphanumeric,
+            |prev, c, next| {
+                // fooBar -> foo|Bar, HTTPServer -> HTTP|Server
+                (c.is_uppercase() && prev.is_some_and(|p| p.is_lowercase() || p.is_numeric()))
+                    || (c.is_uppercase()
+                        && prev.is_some_and(char::is_uppercase)
+                        && next.is_some_and(char::is_lowercase))
+            },
+        ),
+    }
+}
+
+/// Runs of chars accepted by `in_word`, also split before chars where `breaks_before` holds
+///
+/// `breaks_before` gets (previous char, char, next char).
+fn spans(
+    text: &str,
+    in_word: impl Fn(char) -> bool,
+    breaks_before: impl Fn(Option<char>, char, Option<char>) -> bool,
+) -> Vec<(usize, &str)> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut words = Vec::new();
+    let mut start: Option<usize> = None;
+
+    for (i, &(idx, c)) in chars.iter().enumerate() {
+        let prev = i.checked_sub(1).map(|j| chars[j].1);
+        let next = chars.get(i + 1).map(|&(_, n)| n);
+        if !in_word(c) {
+            if let Some(s) = start.take() {
+                words.push((s, &text[s..idx]));
+            }
+            continue;
+        }
+        match start {
+            Some(s) if breaks_before(prev, c, next) => {
+                words.push((s, &text[s..idx]));
+                start = Some(idx);
+            }
+            Some(_) => {}
+            None => start = Some(idx),
+        }
+    }
+    if let Some(s) = start {
+        words.push((s, &text[s..]));
+    }
+    words
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -633,6 +1246,242 @@ mod tests {
         assert_eq!(action, EditorAction::Exit);
     }
     
+    #[test]
+    fn test_ctrl_d_forward_delete() {
+        let mut editor = LineEditor::new();
+        editor.set_buffer("hello".to_string());
+        editor.handle_key(KeyCode::Home, KeyModifiers::NONE);
+        editor.handle_key(KeyCode::Right, KeyModifiers::NONE);
+        
+        let action = editor.handle_key(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert_eq!(action, EditorAction::Redraw);
+        assert_eq!(editor.get_buffer(), "hllo");
+        
+        // At the end of the line there is nothing to delete, and no exit
+        editor.handle_key(KeyCode::End, KeyModifiers::NONE);
+        let action = editor.handle_key(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert_eq!(action, EditorAction::Redraw);
+        assert_eq!(editor.get_buffer(), "hllo");
+        
+        // Once the line is empty, Ctrl+D exits again
+        editor.clear();
+        let action = editor.handle_key(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert_eq!(action, EditorAction::Exit);
+    }
+    
+    #[test]
+    fn test_ctrl_x_ctrl_e_external_edit() {
+        let mut editor = LineEditor::new();
+        
+        let action = editor.handle_key(KeyCode::Char('x'), KeyModifiers::CONTROL);
+        assert_eq!(action, EditorAction::None);
+        let action = editor.handle_key(KeyCode::Char('e'), KeyModifiers::CONTROL);
+        assert_eq!(action, EditorAction::ExternalEdit);
+        
+        // Ctrl+E without the prefix still moves to end of line
+        let action = editor.handle_key(KeyCode::Char('e'), KeyModifiers::CONTROL);
+        assert_eq!(action, EditorAction::Redraw);
+        
+        // Any other key cancels the prefix and is handled normally
+        editor.handle_key(KeyCode::Char('x'), KeyModifiers::CONTROL);
+        editor.handle_key(KeyCode::Char('a'), KeyModifiers::NONE);
+        assert_eq!(editor.get_buffer(), "a");
+    }
+    
+    #[test]
+    fn test_render_cursor_column() {
+        let mut editor = LineEditor::new();
+        editor.set_buffer("héllo 🤖".to_string());
+        let prompt = "\x1b[32m>\x1b[0m ";
+        
+        let rendered = editor.render(prompt, 80);
+        assert_eq!(rendered.lines, [format!("{}héllo 🤖", prompt)]);
+        // Prompt escapes take no columns; the emoji takes two
+        assert_eq!(rendered.cursor_col, 2 + 8);
+        
+        editor.move_cursor_left();
+        assert_eq!(editor.render(prompt, 80).cursor_col, 2 + 6);
+    }
+    
+    #[test]
+    fn test_render_masked() {
+        let mut editor = LineEditor::new();
+        editor.set_buffer("secret 🤖".to_string());
+        assert!(editor.toggle_mask());
+        
+        let rendered = editor.render("> ", 80);
+        assert_eq!(rendered.lines, ["> ••••••••"]);
+        assert_eq!(rendered.cursor_col, 2 + 8);
+        assert_eq!(editor.get_buffer(), "secret 🤖");
+        
+        // Cursor follows graphemes, one column per bullet
+        editor.move_cursor_left();
+        editor.move_cursor_left();
+        assert_eq!(editor.render("> ", 80).cursor_col, 2 + 6);
+        
+        // Editing still works on the real text
+        editor.handle_key(KeyCode::Char('!'), KeyModifiers::NONE);
+        assert_eq!(editor.get_buffer(), "secret! 🤖");
+        assert_eq!(editor.render("> ", 80).lines, ["> •••••••••"]);
+        
+        // Ctrl+X Ctrl+P toggles privacy mode
+        editor.handle_key(KeyCode::Char('x'), KeyModifiers::CONTROL);
+        let action = editor.handle_key(KeyCode::Char('p'), KeyModifiers::CONTROL);
+        assert_eq!(action, EditorAction::Redraw);
+        assert!(!editor.is_masked());
+        assert_eq!(editor.render("> ", 80).lines, ["> secret! 🤖"]);
+    }
+    
+    #[test]
+    fn test_multi_line_input() {
+        let mut editor = LineEditor::new();
+        editor.set_buffer("first line \\".to_string());
+        
+        // Enter after a trailing backslash continues instead of submitting
+        assert_eq!(editor.handle_key(KeyCode::Enter, KeyModifiers::NONE), EditorAction::Redraw);
+        assert_eq!(editor.get_buffer(), "first line \n");
+        for c in "二 end".chars() {
+            editor.handle_key(KeyCode::Char(c), KeyModifiers::NONE);
+        }
+        
+        // Alt+Enter breaks the line at the cursor
+        for _ in 0..4 {
+            editor.move_cursor_left();
+        }
+        assert_eq!(editor.handle_key(KeyCode::Enter, KeyModifiers::ALT), EditorAction::Redraw);
+        assert_eq!(editor.get_buffer(), "first line \n二\n end");
+        
+        let rendered = editor.render("> ", 80);
+        assert_eq!(rendered.lines, ["> first line ", "... 二", "...  end"]);
+        assert_eq!((rendered.cursor_row, rendered.cursor_col), (2, 4));
+        
+        // Left crosses the embedded newline
+        editor.move_cursor_left();
+        let rendered = editor.render("> ", 80);
+        assert_eq!((rendered.cursor_row, rendered.cursor_col), (1, 6));
+        
+        match editor.handle_key(KeyCode::Enter, KeyModifiers::NONE) {
+            EditorAction::Submit(text) => assert_eq!(text, "first line \n二\n end"),
+            other => panic!("Expected Submit, got {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_render_wraps_at_width() {
+        let mut editor = LineEditor::new();
+        editor.set_buffer("abcdefghi二jk\nx".to_string());
+        
+        // Rows hold 6 columns; the wide char doesn't fit at the end of the second
+        let rendered = editor.render("> ", 6);
+        assert_eq!(rendered.lines, ["> abcd", "efghi", "二jk", "... x"]);
+        assert_eq!((rendered.cursor_row, rendered.cursor_col), (3, 5));
+        
+        editor.cursor = "abcdefgh".len();
+        let rendered = editor.render("> ", 6);
+        assert_eq!((rendered.cursor_row, rendered.cursor_col), (1, 4));
+        
+        // A cursor at the end of a full row goes to the start of the next
+        editor.set_buffer("abcd".to_string());
+        let rendered = editor.render("> ", 6);
+        assert_eq!(rendered.lines, ["> abcd", ""]);
+        assert_eq!((rendered.cursor_row, rendered.cursor_col), (1, 0));
+    }
+    
+    #[test]
+    fn test_up_down_move_between_lines() {
+        let mut editor = LineEditor::new();
+        editor.add_to_history("older");
+        editor.set_buffer("abcdef\n二三\nxy".to_string());
+        editor.cursor = 3; // after "abc"
+        
+        // Down keeps the column, stopping before a wide char that would cross it
+        assert_eq!(editor.handle_key(KeyCode::Down, KeyModifiers::NONE), EditorAction::Redraw);
+        let rendered = editor.render("> ", 80);
+        assert_eq!((rendered.cursor_row, rendered.cursor_col), (1, 4 + 2));
+        assert_eq!(editor.handle_key(KeyCode::Down, KeyModifiers::NONE), EditorAction::Redraw);
+        assert_eq!(editor.render("> ", 80).cursor_col, 4 + 2);
+        
+        // On the last line Down goes to history; on the first, Up does
+        assert_eq!(editor.handle_key(KeyCode::Down, KeyModifiers::NONE), EditorAction::HistoryDown);
+        editor.handle_key(KeyCode::Up, KeyModifiers::NONE);
+        editor.handle_key(KeyCode::Up, KeyModifiers::NONE);
+        assert_eq!(editor.render("> ", 80).cursor_row, 0);
+        assert_eq!(editor.handle_key(KeyCode::Up, KeyModifiers::NONE), EditorAction::HistoryUp);
+    }
+    
+    fn word_starts(text: &str, boundary: WordBoundary) -> Vec<usize> {
+        word_indices(text, boundary).into_iter().map(|(idx, _)| idx).collect()
+    }
+    
+    /// Cursor positions visited by Ctrl+Right from the start, then Ctrl+Left from the end
+    fn word_stops(text: &str, boundary: WordBoundary) -> (Vec<usize>, Vec<usize>) {
+        let mut editor = LineEditor::new();
+        editor.set_word_boundary(boundary);
+        editor.set_buffer(text.to_string());
+        
+        editor.cursor = 0;
+        let mut right = Vec::new();
+        while editor.cursor < text.len() {
+            editor.handle_key(KeyCode::Right, KeyModifiers::CONTROL);
+            right.push(editor.cursor);
+        }
+        let mut left = Vec::new();
+        while editor.cursor > 0 {
+            editor.handle_key(KeyCode::Left, KeyModifiers::CONTROL);
+            left.push(editor.cursor);
+        }
+        (right, left)
+    }
+    
+    #[test]
+    fn test_word_boundary_unicode() {
+        let text = "fooBar_baz.qux";
+        // UAX #29 keeps letters joined by `_` and `.` together
+        assert_eq!(word_indices(text, WordBoundary::Unicode), vec![(0, text)]);
+        assert_eq!(word_stops(text, WordBoundary::Unicode), (vec![14], vec![0]));
+        assert_eq!(word_starts("foo.bar baz", WordBoundary::Unicode), vec![0, 8]);
+    }
+    
+    #[test]
+    fn test_word_boundary_whitespace() {
+        let text = "fooBar_baz.qux";
+        assert_eq!(word_starts(text, WordBoundary::Whitespace), vec![0]);
+        assert_eq!(word_stops(text, WordBoundary::Whitespace), (vec![14], vec![0]));
+        assert_eq!(
+            word_indices("cat ./src/main.rs  --x", WordBoundary::Whitespace),
+            vec![(0, "cat"), (4, "./src/main.rs"), (19, "--x")]
+        );
+    }
+    
+    #[test]
+    fn test_word_boundary_subword() {
+        let text = "fooBar_baz.qux";
+        assert_eq!(
+            word_indices(text, WordBoundary::Subword),
+            vec![(0, "foo"), (3, "Bar"), (7, "baz"), (11, "qux")]
+        );
+        assert_eq!(word_stops(text, WordBoundary::Subword), (vec![3, 7, 11, 14], vec![11, 7, 3, 0]));
+        assert_eq!(word_starts("parseHTTPServer2Go", WordBoundary::Subword), vec![0, 5, 9, 16]);
+        
+        // Ctrl+W removes one subword at a time
+        let mut editor = LineEditor::new();
+        editor.set_word_boundary(WordBoundary::Subword);
+        editor.set_buffer(text.to_string());
+        editor.delete_prev_word();
+        assert_eq!(editor.get_buffer(), "fooBar_baz.");
+        editor.delete_prev_word();
+        assert_eq!(editor.get_buffer(), "fooBar_");
+        editor.delete_prev_word();
+        assert_eq!(editor.get_buffer(), "foo");
+    }
+    
+    #[test]
+    fn test_ctrl_p_command_palette() {
+        let mut editor = LineEditor::new();
+        let action = editor.handle_key(KeyCode::Char('p'), KeyModifiers::CONTROL);
+        assert_eq!(action, EditorAction::CommandPalette);
+    }
+    
     #[test]
     fn test_ctrl_c_cancel() {
         let mut editor = LineEditor::new();
@@ -642,6 +1491,190 @@ mod tests {
         assert_eq!(action, EditorAction::Cancel);
     }
     
+    #[test]
+    fn test_cursor_clamped_to_grapheme_boundary() {
+        let mut editor = LineEditor::new();
+        editor.set_buffer("café 👋🏽!".to_string());
+        assert_eq!(editor.cursor, editor.buffer.len());
+        
+        // Put the cursor inside every multi-byte grapheme and insert there
+        for bad in 0..=editor.buffer.len() + 2 {
+            let mut editor = LineEditor::new();
+            editor.set_buffer("café 👋🏽!".to_string());
+            editor.cursor = bad;
+            editor.handle_key(KeyCode::Char('x'), KeyModifiers::NONE);
+            assert!(editor.buffer.is_char_boundary(editor.cursor));
+            assert!(editor.buffer.contains('x'));
+        }
+        
+        // Clamping moves back to the start of the grapheme, not past it
+        editor.cursor = "café ".len() + 1;
+        editor.clamp_cursor_to_grapheme_boundary();
+        assert_eq!(editor.cursor, "café ".len());
+    }
+    
+    #[test]
+    fn test_control_chars_filtered_on_insert() {
+        let mut editor = LineEditor::new();
+        for c in ['a', '\x1b', '\x07', '\u{202E}', '\t', 'b'] {
+            editor.handle_key(KeyCode::Char(c), KeyModifiers::NONE);
+        }
+        assert_eq!(editor.get_buffer(), "a b");
+        assert_eq!(editor.cursor, editor.buffer.len());
+        
+        assert_eq!(sanitize_input_char('\u{200D}'), Some('\u{200D}'));
+        assert_eq!(sanitize_input_char('\u{0301}'), Some('\u{0301}'));
+    }
+    
+    #[test]
+    fn test_zwj_sequence_typed_as_one_grapheme() {
+        // Family emoji typed one code point at a time: man ZWJ woman ZWJ girl
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let mut editor = LineEditor::new();
+        editor.handle_key(KeyCode::Char('x'), KeyModifiers::NONE);
+        for c in family.chars() {
+            editor.handle_key(KeyCode::Char(c), KeyModifiers::NONE);
+        }
+        editor.handle_key(KeyCode::Char('y'), KeyModifiers::NONE);
+        assert_eq!(editor.get_buffer(), format!("x{}y", family));
+        
+        // One step left over 'y', one more over the whole family
+        editor.handle_key(KeyCode::Left, KeyModifiers::NONE);
+        editor.handle_key(KeyCode::Left, KeyModifiers::NONE);
+        assert_eq!(editor.cursor, 1);
+        editor.handle_key(KeyCode::Right, KeyModifiers::NONE);
+        assert_eq!(editor.cursor, 1 + family.len());
+        
+        // Backspace removes the sequence as a unit
+        editor.handle_key(KeyCode::Backspace, KeyModifiers::NONE);
+        assert_eq!(editor.get_buffer(), "xy");
+    }
+    
+    #[test]
+    fn test_history_export_import_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        
+        let mut editor = LineEditor::new();
+        for cmd in ["ls", "git status", "echo 'multi\nline'"] {
+            editor.add_to_history(cmd);
+        }
+        assert_eq!(editor.export_history(&path).unwrap(), 3);
+        
+        // Existing entries are not duplicated and the size cap is kept
+        let mut other = LineEditor::with_history_size(3);
+        other.add_to_history("ls");
+        other.add_to_history("pwd");
+        assert_eq!(other.import_history(&path).unwrap(), 2);
+        let entries: Vec<&str> = other.history_entries(10, None).into_iter().map(|(_, e)| e).collect();
+        assert_eq!(entries, ["pwd", "git status", "echo 'multi\nline'"]);
+        
+        // Importing into a history that already has every entry adds nothing
+        assert_eq!(editor.import_history(&path).unwrap(), 0);
+        assert_eq!(editor.history_len(), 3);
+    }
+    
+    #[test]
+    fn test_history_import_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut editor = LineEditor::new();
+        
+        let missing = editor.import_history(&dir.path().join("missing.json")).unwrap_err();
+        assert_eq!(missing.kind(), io::ErrorKind::NotFound);
+        
+        let garbage = dir.path().join("garbage.json");
+        std::fs::write(&garbage, "not json").unwrap();
+        let invalid = editor.import_history(&garbage).unwrap_err();
+        assert_eq!(invalid.kind(), io::ErrorKind::InvalidData);
+    }
+    
+    #[test]
+    fn test_persistent_history_across_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("openagent-terminal").join("history");
+        
+        // A missing file is an empty history
+        let mut first = LineEditor::new();
+        assert_eq!(first.load_history(&path).unwrap(), 0);
+        for cmd in ["ls", "echo 'multi\nline'", "pwd"] {
+            first.add_to_history(cmd);
+        }
+        assert_eq!(first.save_history(&path).unwrap(), 3);
+        assert_eq!(first.save_history(&path).unwrap(), 0);
+        
+        // The next session only appends what it added
+        let mut second = LineEditor::new();
+        assert_eq!(second.load_history(&path).unwrap(), 3);
+        second.add_to_history("pwd");
+        second.add_to_history("git log");
+        assert_eq!(second.save_history(&path).unwrap(), 1);
+        
+        // Loading keeps the newest max_history, dropping consecutive duplicates
+        std::fs::write(&path, format!("{}\nplain line\nplain line\n", std::fs::read_to_string(&path).unwrap())).unwrap();
+        let mut third = LineEditor::with_history_size(3);
+        assert_eq!(third.load_history(&path).unwrap(), 3);
+        let entries: Vec<&str> = third.history_entries(10, None).into_iter().map(|(_, e)| e).collect();
+        assert_eq!(entries, ["pwd", "git log", "plain line"]);
+        
+        // Saving cuts the file back to its newest max_history lines
+        third.add_to_history("make");
+        assert_eq!(third.save_history(&path).unwrap(), 1);
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(saved.lines().collect::<Vec<_>>(), ["plain line", "plain line", "\"make\""]);
+        
+        // An unreadable file leaves the history empty
+        let mut fourth = LineEditor::new();
+        assert!(fourth.load_history(dir.path()).is_err());
+        assert_eq!(fourth.history_len(), 0);
+    }
+    
+    #[test]
+    fn test_stream_cancel_keys() {
+        assert!(is_stream_cancel_key(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(is_stream_cancel_key(KeyCode::Char('c'), KeyModifiers::CONTROL));
+        
+        assert!(!is_stream_cancel_key(KeyCode::Char('c'), KeyModifiers::NONE));
+        assert!(!is_stream_cancel_key(KeyCode::Char('d'), KeyModifiers::CONTROL));
+        assert!(!is_stream_cancel_key(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(!is_stream_cancel_key(KeyCode::Char('q'), KeyModifiers::NONE));
+    }
+    
+    #[test]
+    fn test_key_binding_parse() {
+        let alt_up = KeyBinding::parse("Alt+Up").unwrap();
+        assert_eq!(alt_up, KeyBinding { code: KeyCode::Up, modifiers: KeyModifiers::ALT });
+        assert!(alt_up.matches(KeyCode::Up, KeyModifiers::ALT));
+        assert!(!alt_up.matches(KeyCode::Up, KeyModifiers::NONE));
+        
+        assert_eq!(
+            KeyBinding::parse("ctrl+shift+pagedown"),
+            Some(KeyBinding { code: KeyCode::PageDown, modifiers: KeyModifiers::CONTROL | KeyModifiers::SHIFT })
+        );
+        assert_eq!(KeyBinding::parse("F5"), Some(KeyBinding { code: KeyCode::F(5), modifiers: KeyModifiers::NONE }));
+        assert_eq!(KeyBinding::parse("Ctrl+N"), Some(KeyBinding { code: KeyCode::Char('n'), modifiers: KeyModifiers::CONTROL }));
+        assert_eq!(KeyBinding::parse("Hyper+Up"), None);
+        assert_eq!(KeyBinding::parse("Alt+"), None);
+        assert_eq!(KeyBinding::parse("Alt+Upp"), None);
+    }
+    
+    #[test]
+    fn test_session_keys_take_precedence() {
+        let mut editor = LineEditor::new();
+        editor.set_buffer("a b".to_string());
+        // Unbound, Alt+Left moves by argument
+        assert!(matches!(editor.handle_key(KeyCode::Left, KeyModifiers::ALT), EditorAction::Redraw));
+        
+        editor.set_session_keys(KeyBinding::parse("Alt+Left").unwrap(), KeyBinding::parse("Alt+Right").unwrap());
+        assert!(matches!(
+            editor.handle_key(KeyCode::Left, KeyModifiers::ALT),
+            EditorAction::CycleSession(SessionCycle::Previous)
+        ));
+        assert!(matches!(
+            editor.handle_key(KeyCode::Right, KeyModifiers::ALT),
+            EditorAction::CycleSession(SessionCycle::Next)
+        ));
+    }
+    
     #[test]
     fn test_cursor_movement() {
         let mut editor = LineEditor::new();
@@ -772,6 +1805,39 @@ mod tests {
         assert!(editor.cursor < editor.buffer.len());
     }
     
+    #[test]
+    fn test_arg_movement() {
+        let mut editor = LineEditor::new();
+        editor.set_buffer("fetch https://example.com/a?b=c  --format=json".to_string());
+        let url = editor.buffer.find("https").unwrap();
+        let flag = editor.buffer.find("--format").unwrap();
+        
+        editor.cursor = 0;
+        editor.handle_key(KeyCode::Right, KeyModifiers::ALT);
+        assert_eq!(editor.cursor, url);
+        // The whole URL is skipped in one step
+        editor.handle_key(KeyCode::Right, KeyModifiers::ALT);
+        assert_eq!(editor.cursor, flag);
+        editor.handle_key(KeyCode::Right, KeyModifiers::ALT);
+        assert_eq!(editor.cursor, editor.buffer.len());
+        
+        // From the end, the flag is one token; from inside it, its own start
+        editor.handle_key(KeyCode::Left, KeyModifiers::ALT);
+        assert_eq!(editor.cursor, flag);
+        editor.cursor = flag + 5;
+        editor.handle_key(KeyCode::Left, KeyModifiers::ALT);
+        assert_eq!(editor.cursor, flag);
+        editor.handle_key(KeyCode::Left, KeyModifiers::ALT);
+        assert_eq!(editor.cursor, url);
+        editor.handle_key(KeyCode::Left, KeyModifiers::ALT);
+        assert_eq!(editor.cursor, 0);
+        
+        // Word movement still stops inside the URL
+        editor.cursor = url;
+        editor.handle_key(KeyCode::Right, KeyModifiers::CONTROL);
+        assert!(editor.cursor > url && editor.cursor < flag);
+    }
+    
     #[test]
     fn test_reverse_search_mode() {
         let mut editor = LineEditor::new();
@@ -794,6 +1860,113 @@ mod tests {
         assert!(!editor.is_reverse_search());
     }
     
+    fn type_search(editor: &mut LineEditor, query: &str) {
+        for c in query.chars() {
+            editor.handle_key(KeyCode::Char(c), KeyModifiers::NONE);
+        }
+    }
+    
+    #[test]
+    fn test_reverse_search_accept_and_cycle() {
+        let mut editor = LineEditor::new();
+        editor.add_to_history("git status");
+        editor.add_to_history("ls -la");
+        editor.add_to_history("git log");
+        editor.set_buffer("draft".to_string());
+        
+        editor.start_reverse_search();
+        type_search(&mut editor, "git");
+        assert_eq!(editor.render_search(false), ("(reverse-i-search)'git': git log".to_string(), 25));
+        
+        // Ctrl+R cycles to the older match, and stays there once none are left
+        editor.handle_key(KeyCode::Char('r'), KeyModifiers::CONTROL);
+        assert_eq!(editor.render_search(false).0, "(reverse-i-search)'git': git status");
+        editor.handle_key(KeyCode::Char('r'), KeyModifiers::CONTROL);
+        assert_eq!(editor.render_search(false).0, "(reverse-i-search)'git': git status");
+        
+        // Narrowing keeps the current match while it still matches
+        type_search(&mut editor, " s");
+        assert_eq!(editor.render_search(false).0, "(reverse-i-search)'git s': git status");
+        
+        assert_eq!(editor.handle_key(KeyCode::Enter, KeyModifiers::NONE), EditorAction::Redraw);
+        assert!(!editor.is_reverse_search());
+        assert_eq!(editor.get_buffer(), "git status");
+    }
+    
+    #[test]
+    fn test_reverse_search_forward_stops_at_newest() {
+        let mut editor = LineEditor::new();
+        editor.add_to_history("make build");
+        editor.add_to_history("make test");
+        editor.add_to_history("echo done");
+        editor.add_to_history("make clean");
+        assert_eq!(editor.handle_key(KeyCode::Char('s'), KeyModifiers::CONTROL), EditorAction::ReverseSearchForward);
+        
+        editor.start_reverse_search();
+        type_search(&mut editor, "make");
+        assert_eq!(editor.search_find_next().as_deref(), Some("make test"));
+        assert_eq!(editor.search_find_next().as_deref(), Some("make build"));
+        
+        // Ctrl+S walks back toward newer matches, skipping entries that don't match
+        editor.handle_key(KeyCode::Char('s'), KeyModifiers::CONTROL);
+        assert_eq!(editor.render_search(false).0, "(reverse-i-search)'make': make test");
+        assert_eq!(editor.search_find_prev().as_deref(), Some("make clean"));
+        
+        // No wrapping past the newest match
+        assert_eq!(editor.search_find_prev(), None);
+        assert_eq!(editor.render_search(false).0, "(reverse-i-search)'make': make clean");
+    }
+    
+    #[test]
+    fn test_reverse_search_cancel_restores_buffer() {
+        let mut editor = LineEditor::new();
+        editor.add_to_history("cargo test");
+        editor.set_buffer("draft".to_string());
+        
+        editor.start_reverse_search();
+        type_search(&mut editor, "cargo");
+        editor.handle_key(KeyCode::Esc, KeyModifiers::NONE);
+        assert!(!editor.is_reverse_search());
+        assert_eq!(editor.get_buffer(), "draft");
+    }
+    
+    #[test]
+    fn test_reverse_search_failed_and_highlight() {
+        let mut editor = LineEditor::new();
+        editor.add_to_history("echo hello");
+        
+        editor.start_reverse_search();
+        assert_eq!(editor.render_search(false).0, "(reverse-i-search)'': ");
+        type_search(&mut editor, "xyz");
+        assert_eq!(editor.render_search(false), ("(failed reverse-i-search)'xyz': ".to_string(), 32));
+        
+        // Backspacing to a matching query finds the entry again
+        for _ in 0..3 {
+            editor.handle_key(KeyCode::Backspace, KeyModifiers::NONE);
+        }
+        type_search(&mut editor, "hel");
+        let (line, cursor) = editor.render_search(true);
+        assert!(line.contains("echo \x1b[1m\x1b[33mhel\x1b[0mlo"), "{:?}", line);
+        assert_eq!(cursor, "(reverse-i-search)'hel': echo ".len());
+        
+        // Accepting with an empty match leaves the buffer alone
+        editor.handle_key(KeyCode::Char('x'), KeyModifiers::NONE);
+        editor.handle_key(KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(editor.get_buffer(), "");
+    }
+    
+    #[test]
+    fn test_history_entries() {
+        let mut editor = LineEditor::new();
+        editor.add_to_history("git status");
+        editor.add_to_history("ls -la");
+        editor.add_to_history("git log");
+        
+        assert_eq!(editor.history_entries(2, None), vec![(2, "ls -la"), (3, "git log")]);
+        assert_eq!(editor.history_entries(10, Some("GIT")), vec![(1, "git status"), (3, "git log")]);
+        assert!(editor.history_entries(10, Some("cargo")).is_empty());
+    }
+    
     #[test]
     fn test_grapheme_cluster_deletion() {
         let mut editor = LineEditor::new();
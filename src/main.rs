@@ -3,69 +3,117 @@
 
 mod ansi;
 mod cli;
+mod clock;
 mod commands;
 mod config;
 mod error;
+mod external_editor;
+mod glyphs;
+mod hooks;
+mod idle;
 mod ipc;
 mod line_editor;
+mod notice;
+mod output;
+mod palette;
+mod prompt;
 mod session;
+mod streams;
 mod terminal_manager;
+mod tokenizer;
+mod transcript;
 
 use anyhow::Result;
-use crossterm::{
-    cursor,
-    event::{self, Event, KeyCode},
-    execute,
-};
+use crossterm::event::{Event, KeyCode};
+use glyphs::Glyph;
 use line_editor::{EditorAction, LineEditor};
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use std::io::{self, Write};
 use std::sync::Arc;
 use tokio::sync::{Mutex, watch};
 
+/// Extra time allowed beyond `command_timeout` before warning about a missing tool result
+const TOOL_RESULT_GRACE: std::time::Duration = std::time::Duration::from_secs(5);
+
 /// Handle --generate-config flag
-fn handle_generate_config() -> Result<()> {
+async fn handle_generate_config() -> Result<()> {
     println!("⚙️  Generating default configuration...");
     
     let config_path = config::Config::config_path()?;
     
     // Check if config already exists
     if config_path.exists() {
-        println!("⚠️   Configuration file already exists at: {:?}", config_path);
+        notice::warn(format!("Configuration file already exists at: {:?}", config_path));
         print!("Overwrite? [y/N]: ");
         std::io::Write::flush(&mut std::io::stdout())?;
         
-        let mut input = String::new();
-        std::io::stdin().read_line(&mut input)?;
-        
-        if !input.trim().eq_ignore_ascii_case("y") {
+        if !prompt::read_yes_no_stdin().await? {
             println!("Aborted.");
             return Ok(());
         }
     }
     
     config::Config::generate_default()?;
-    println!("✅ Configuration generated at: {:?}", config_path);
+    notice::success(format!("Configuration generated at: {:?}", config_path));
     println!("📝 Edit the file to customize your settings.");
     
     Ok(())
 }
 
+/// Handle --dry-connect: validate and print sample messages without a backend
+fn handle_dry_connect() -> Result<()> {
+    let options = ipc::message::QueryOptions {
+        system_prompt: Some("You are a concise assistant for terminal users.".to_string()),
+        ..Default::default()
+    };
+    let requests = [
+        ipc::message::Request::initialize(1),
+        ipc::message::Request::agent_query_with_options(2, "list files in src", &options, true),
+    ];
+    let notifications = [
+        ipc::message::Notification::context_update_full(
+            std::env::current_dir().ok().map(|cwd| cwd.display().to_string()),
+            Some((80, 24)),
+        ),
+        ipc::message::Notification::context_update_tools(false),
+    ];
+    
+    for request in &requests {
+        request.validate_roundtrip()?;
+        println!("{}", serde_json::to_string_pretty(request)?);
+    }
+    for notification in &notifications {
+        notification.validate_roundtrip()?;
+        println!("{}", serde_json::to_string_pretty(notification)?);
+    }
+    notice::success(format!("{} messages round-tripped", requests.len() + notifications.len()));
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Parse CLI arguments first
     let cli = cli::Cli::parse_args();
+    notice::set_color_enabled(cli.color_enabled());
     
     // Handle --generate-config flag
     if cli.should_generate_config() {
-        return handle_generate_config();
+        return handle_generate_config().await;
+    }
+    if cli.dry_connect {
+        return handle_dry_connect();
     }
     
-    // Initialize logging with CLI-specified level
+    // Initialize logging with CLI-specified level. Without RUST_LOG the logger
+    // itself passes everything and the level is applied as the global max, so
+    // /loglevel can raise it later as well as lower it.
     let log_level = cli.effective_log_level();
     env_logger::Builder::from_env(
-        env_logger::Env::default().default_filter_or(log_level.to_filter_str())
+        env_logger::Env::default().default_filter_or(cli::LogLevel::Trace.to_filter_str())
     ).init();
+    if std::env::var_os("RUST_LOG").is_none() {
+        log_level.apply();
+    }
 
     info!("🚀 Starting OpenAgent-Terminal v{}", env!("CARGO_PKG_VERSION"));
     info!("📝 Status: Alpha - Early Development");
@@ -73,10 +121,10 @@ async fn main() -> Result<()> {
     // Load configuration with CLI precedence: CLI > File > Default
     let mut config = if let Some(config_path) = cli.effective_config_path() {
         // Load from CLI-specified path
-        config::Config::load_from(config_path).unwrap_or_else(|e| {
+        config::Config::load_from(&config_path).unwrap_or_else(|e| {
             log::warn!("Failed to load config from CLI path: {}", e);
             log::info!("Using default configuration");
-            config::Config::default()
+            config::Config::default().with_file(config_path)
         })
     } else {
         // Load from default path
@@ -92,46 +140,110 @@ async fn main() -> Result<()> {
         info!("CLI override: model = {}", model);
         config.agent.model = model.clone();
     }
+    if let Some(secs) = cli.timeout_per_query {
+        info!("CLI override: query timeout = {}s", secs);
+        config.agent.query_timeout_secs = secs;
+    }
+    
+    if let Some(ref path) = cli.transcript {
+        info!("CLI override: transcript = {:?}", path);
+        config.terminal.transcript_file = Some(path.clone());
+    }
+    
+    // Safe mode goes last so it wins over everything above
+    if cli.safe {
+        config.safe_mode.enabled = true;
+    }
+    config.apply_safe_mode();
+    ansi::set_truecolor(ansi::detect_truecolor(std::env::var("COLORTERM").ok().as_deref()));
+    if !ansi::set_theme(&config.terminal.theme) {
+        notice::warn(format!("Unknown theme '{}', using {}", config.terminal.theme, ansi::active_theme().name));
+    }
+    glyphs::set_mode(config.terminal.glyphs);
     
     info!("Configuration loaded:");
     info!("  Theme: {}", config.terminal.theme);
     info!("  Font: {} ({}pt)", config.terminal.font_family, config.terminal.font_size);
     info!("  Model: {}", config.agent.model);
     info!("  Real execution: {}", config.tools.enable_real_execution);
+    info!("  Safe mode: {}", config.safe_mode.enabled);
     
     // Show welcome message
-    println!("╔════════════════════════════════════════════╗");
-    println!("║      OpenAgent-Terminal (Alpha)           ║");
-    println!("║   AI-Native Terminal Emulator             ║");
-    println!("║   ✨ With Session Persistence ✨          ║");
-    println!("╚════════════════════════════════════════════╝");
+    let sparkle = Glyph::Sparkle.get();
+    println!("{}", ansi::boxed(
+        "OpenAgent-Terminal (Alpha)",
+        &["AI-Native Terminal Emulator", &format!("{} With Session Persistence {}", sparkle, sparkle)],
+        ansi::content_width(None),
+    ));
     println!();
     println!("Type /help for available commands");
     println!();
+    if config.safe_mode.enabled {
+        notice::warn("SAFE MODE: tools run in demo mode and high-risk requests are denied");
+        println!();
+    }
+    
+    // A transcript that can't be opened is not fatal
+    if let Some(path) = &config.terminal.transcript_file {
+        match transcript::open_global(path) {
+            Ok(()) => info!("Writing transcript to {:?}", path),
+            Err(e) => {
+                notice::warn(format!("Could not open transcript file {}: {} (continuing without it)", path.display(), e));
+                println!();
+            }
+        }
+    }
 
     // Determine socket path with precedence: CLI > Environment > Default
     let socket_path = cli.effective_socket_path();
 
     info!("Socket path: {}", socket_path);
-    println!("🔌 Connecting to Python backend at: {}", socket_path);
+    println!("{} Connecting to Python backend at: {}", Glyph::Connect.get(), socket_path);
     println!("   (Make sure the Python backend is running!)");
     println!();
 
     // Create IPC client and session manager
     let mut client = ipc::client::IpcClient::new();
+    client.set_timeout_policy(ipc::client::TimeoutPolicy {
+        query: std::time::Duration::from_secs(config.agent.query_timeout_secs),
+        control: std::time::Duration::from_secs(config.agent.control_timeout_secs),
+        ..Default::default()
+    });
+    client.set_default_timeout(std::time::Duration::from_secs(config.agent.request_timeout_secs));
+    client.set_auto_reconnect(config.agent.auto_reconnect);
+    client.set_rate_limit("agent.query", config.agent.max_queries_per_minute);
+    if cli.no_retry {
+        client.set_connect_attempts(1);
+    }
+    if cli.verbose_ipc {
+        client.set_frame_sink(Some(Arc::new(|direction, frame| {
+            eprintln!("{} {}", direction.prefix(), frame);
+        })));
+    }
 
-    // Try to connect
-    match client.connect(&socket_path).await {
+    // Try to connect, keeping retries visible on one updating line
+    let mut retried = false;
+    let connected = client.connect_with_progress(&socket_path, |progress| {
+        if progress.retry_delay.is_some() {
+            retried = true;
+            print!("\r\x1b[K   {}", progress.message());
+            let _ = std::io::Write::flush(&mut std::io::stdout());
+        }
+    }).await;
+    if retried {
+        println!();
+    }
+    match connected {
         Ok(()) => {
             info!("✅ Connected successfully");
-            println!("✅ Connected to Python backend");
+            notice::success("Connected to Python backend");
             println!();
 
             // Send initialize request
             match client.initialize().await {
                 Ok(response) => {
                     info!("Initialize response: {:?}", response);
-                    println!("✅ Backend initialized successfully!");
+                    notice::success("Backend initialized successfully!");
                     println!();
                     
                     // Wrap client in Arc<Mutex> for shared ownership
@@ -141,14 +253,17 @@ async fn main() -> Result<()> {
                     let mut session_manager = session::SessionManager::new(Arc::clone(&client));
                     info!("📝 Session manager connected");
                     
+                    // Raw mode and the alternate screen would garble stderr frames
+                    client.lock().await.set_frame_sink(None);
+                    
                     // Run interactive loop
                     if let Err(e) = run_interactive_loop(
                         Arc::clone(&client), 
                         &mut session_manager,
-                        &config
+                        &mut config
                     ).await {
                         error!("Interactive loop error: {}", e);
-                        println!("{}Error:{} {}", ansi::colors::RED, ansi::colors::RESET, e);
+                        notice::error(format!("Error: {}", e));
                     }
                     
                     // Disconnect
@@ -156,27 +271,56 @@ async fn main() -> Result<()> {
                 }
                 Err(e) => {
                     error!("Initialize failed: {}", e);
-                    println!("❌ Initialize failed: {}", e);
-                    return Err(e.into());
+                    // Protocol-level failures mean the backend answered but init didn't work
+                    let err = match e {
+                        ipc::error::IpcError::RpcError { .. }
+                        | ipc::error::IpcError::ParseError(_)
+                        | ipc::error::IpcError::ProtocolError(_) => {
+                            error::TerminalError::InitializationError(e.to_string())
+                        }
+                        other => error::TerminalError::from(other).with_socket_path(&socket_path),
+                    };
+                    notice::error(err.short_message());
+                    println!();
+                    println!("{}", err);
+                    return Err(err.into());
                 }
             }
         }
+        Err(ipc::error::IpcError::StaleSocket(path)) => {
+            error!("Stale socket at {}", path);
+            notice::warn(format!("Socket {} exists but nothing is listening on it.", path));
+            println!("The backend may have crashed and left a stale socket behind.");
+            println!();
+            
+            if cli.remove_stale_socket {
+                print!("Remove stale socket {}? [y/N]: ", path);
+                std::io::Write::flush(&mut std::io::stdout())?;
+                if prompt::read_yes_no_stdin().await? {
+                    std::fs::remove_file(&path)?;
+                    notice::success("Removed stale socket. Restart the backend and try again.");
+                } else {
+                    println!("Leaving socket in place.");
+                }
+            } else {
+                println!("Restart the backend, or remove the socket with:");
+                println!("  rm {}", path);
+                println!("(or run with --remove-stale-socket)");
+            }
+            return Err(ipc::error::IpcError::StaleSocket(path).into());
+        }
         Err(e) => {
             error!("Connection failed: {}", e);
-            println!("❌ Connection failed: {}", e);
+            let err = error::TerminalError::from(e).with_socket_path(&socket_path);
+            notice::error(err.short_message());
             println!();
-            println!("Make sure the Python backend is running:");
-            println!("  cd backend");
-            println!("  python -m openagent_terminal.bridge");
-            println!();
-            println!("Or set a custom socket path:");
-            println!("  export OPENAGENT_SOCKET=/path/to/socket.sock");
-            return Err(e.into());
+            println!("{}", err);
+            return Err(err.into());
         }
     }
 
     println!();
-    println!("👋 Goodbye!");
+    println!("{} Goodbye!", Glyph::Goodbye.get());
     Ok(())
 }
 
@@ -185,21 +329,50 @@ async fn main() -> Result<()> {
 async fn run_interactive_loop(
     client: Arc<Mutex<ipc::client::IpcClient>>,
     session_manager: &mut session::SessionManager,
-    config: &config::Config,
+    config: &mut config::Config,
 ) -> Result<()> {
     // Create terminal manager (enables raw mode)
     let mut terminal = terminal_manager::TerminalManager::new()?;
     let mut editor = LineEditor::new();
+    let history_path = config::history_path();
+    if let Some(path) = &history_path {
+        if let Err(e) = editor.load_history(path) {
+            warn!("Could not load history from {:?}, starting empty: {}", path, e);
+        }
+    }
+    editor.set_word_boundary(config.terminal.word_boundary);
+    terminal.set_cursor_style(config.terminal.cursor_style);
+    match session_keys(config) {
+        Some((previous, next)) => editor.set_session_keys(previous, next),
+        None => notice::warn(format!(
+            "Invalid session keybindings '{}' / '{}', quick session switching is off",
+            config.keybindings.previous_session, config.keybindings.next_session
+        )),
+    }
+    terminal.set_layout(terminal_manager::ScreenLayout::from_config(config.terminal.use_alternate_screen));
+    
+    // Enter alternate screen buffer for clean UX (unless rendering inline)
+    terminal.enter_layout()?;
+    
+    // Backend version is only surfaced in the status line when incompatible
+    let backend_version_mismatch = client.lock().await.server_info()
+        .filter(|info| info.major_version_mismatch(env!("CARGO_PKG_VERSION")))
+        .map(|info| info.version.clone());
     
-    // Enter alternate screen buffer for clean UX
-    terminal.enter_alternate_screen()?;
-    terminal.clear_screen()?;
+    // Updated by the client's I/O tasks too, so a dropped connection shows up without a request
+    let connection_states = client.lock().await.connection_state_changes();
     
     // Initialize status line
     let status = terminal_manager::StatusInfo {
-        connection_state: "Connected".to_string(),
+        connection_state: connection_states.borrow().label().to_string(),
         model: config.agent.model.clone(),
         session_id: session_manager.current_session_id().map(|s| s.to_string()),
+        backend_version_mismatch: backend_version_mismatch.clone(),
+        real_execution: config.tools.enable_real_execution,
+        safe_mode: config.safe_mode.enabled,
+        tokens: session_manager.transcript().total_tokens(),
+        tokens_estimated: session_manager.transcript().is_estimate(),
+        private: editor.is_masked(),
     };
     terminal.set_status(status);
     terminal.draw_status_line()?;
@@ -207,70 +380,168 @@ async fn run_interactive_loop(
     // Create cancellation token for stream interruption
     let (cancel_tx, _cancel_rx) = watch::channel(false);
     
+    // Active query options (system prompt can be overridden with /system)
+    let mut query_options = ipc::message::QueryOptions {
+        system_prompt: config.agent.system_prompt.clone(),
+        temperature: Some(config.agent.temperature),
+        max_tokens: Some(config.agent.max_tokens),
+        ..Default::default()
+    };
+    
+    // Submit-time normalization from config
+    let submit_opts = commands::SubmitOptions {
+        trim_trailing_whitespace: config.terminal.trim_trailing_whitespace,
+        collapse_blank_lines: config.terminal.collapse_blank_lines,
+    };
+    
+    // Terminal events for the whole session, also read while a response streams
+    let mut events = idle::InputEvents::new();
+    
     loop {
+        // The connection dropped (while idle or mid-stream): try once to get it back
+        if config.agent.auto_reconnect && *connection_states.borrow() == ipc::client::ConnectionState::Disconnected {
+            terminal.leave_input()?;
+            notice::info("Reconnecting to backend...");
+            match client.lock().await.reconnect().await {
+                Ok(()) => notice::success("Reconnected to backend"),
+                Err(e) => {
+                    error!("Reconnect failed: {}", e);
+                    notice::error(format!("Reconnect failed: {}", e));
+                }
+            }
+            println!();
+            terminal.invalidate_status();
+        }
+        
+        // A response that ended on a notification leaves the stream waiting for
+        // input, which would block the cursor position reads while redrawing
+        events.release();
+        
         // Update status line (in case session changed)
         let status = terminal_manager::StatusInfo {
-            connection_state: "Connected".to_string(),
+            connection_state: connection_states.borrow().label().to_string(),
             model: config.agent.model.clone(),
             session_id: session_manager.current_session_id().map(|s| s.to_string()),
+            backend_version_mismatch: backend_version_mismatch.clone(),
+            real_execution: config.tools.enable_real_execution,
+            safe_mode: config.safe_mode.enabled,
+            tokens: session_manager.transcript().total_tokens(),
+            tokens_estimated: session_manager.transcript().is_estimate(),
+            private: editor.is_masked(),
         };
         terminal.set_status(status);
         terminal.draw_status_line()?;
         
-        // Move to prompt area at bottom
-        terminal.move_to_prompt_area()?;
-        
         // Show prompt (simpler now that session is in status line)
-        let prompt = format!("{}>{} ", ansi::colors::GREEN, ansi::colors::RESET);
-        
-        // Render prompt and input line
-        let (line, cursor_pos) = editor.render(&prompt);
-        terminal.clear_current_line()?;
-        print!("{}", line);
-        io::stdout().flush()?;
-        
-        // Position cursor correctly
-        let current_row = cursor::position()?.1;
-        execute!(io::stdout(), cursor::MoveTo(cursor_pos as u16, current_row))?;
-        
-        // Wait for keyboard event with timeout (allows checking other state)
-        if !event::poll(std::time::Duration::from_millis(100))? {
-            continue;
+        let mut prompt = format!("{}>{} ", ansi::colors::GREEN, ansi::colors::RESET);
+        if let Some(badge) = terminal.prompt_badge() {
+            prompt = format!("{}{}{} {}", ansi::colors::YELLOW, badge, ansi::colors::RESET, prompt);
         }
         
-        // Read the event
-        let event = event::read()?;
+        // Render prompt and input lines (or the reverse search in their place)
+        let input = if editor.is_reverse_search() {
+            let (line, cursor_col) = editor.render_search(notice::color_enabled());
+            line_editor::RenderedInput { lines: vec![line], cursor_row: 0, cursor_col }
+        } else {
+            editor.render(&prompt, terminal.size()?.0 as usize)
+        };
+        terminal.draw_input(&input)?;
+        
+        // Sleep until input (or a notification) arrives, or poll if configured to
+        let wake = if config.terminal.event_driven_input {
+            events.next_wake(async { client.lock().await.next_notification().await.ok() }).await?
+        } else {
+            idle::poll_wake()?
+        };
+        let event = match wake {
+            idle::Wake::Input(event) => event,
+            idle::Wake::Notification(notification) => {
+                if notification.is_connection_closed() {
+                    terminal.leave_input()?;
+                    println!();
+                    notice::error("Connection to backend lost");
+                    terminal.invalidate_status();
+                } else {
+                    // Leftovers from a finished or cancelled stream
+                    debug!("Ignoring {} while idle", notification.method);
+                }
+                continue;
+            }
+            idle::Wake::Tick => continue,
+        };
         
         match event {
             Event::Key(key_event) => {
                 let action = editor.handle_key(key_event.code, key_event.modifiers);
                 
+                // Anything that prints must start below the whole input
+                if !matches!(
+                    action,
+                    EditorAction::Redraw
+                        | EditorAction::None
+                        | EditorAction::HistoryUp
+                        | EditorAction::HistoryDown
+                        | EditorAction::ReverseSearch
+                        | EditorAction::ReverseSearchForward
+                        | EditorAction::DeleteToStart
+                        | EditorAction::DeleteToEnd
+                        | EditorAction::DeletePrevWord
+                ) {
+                    terminal.leave_input()?;
+                }
+                
                 match action {
                     EditorAction::Submit(input) => {
                         println!(); // Move to next line after submission
                         
-                        let input = input.trim();
-                        if input.is_empty() {
+                        let input = commands::normalize_submission(input.trim_start(), &submit_opts);
+                        if input.trim().is_empty() {
                             continue;
                         }
                         
-                        // Add to local history
-                        editor.add_to_history(input);
+                        // Add to local history, saved right away so a crash doesn't lose it
+                        editor.add_to_history(&input);
+                        if let Some(path) = &history_path {
+                            if let Err(e) = editor.save_history(path) {
+                                warn!("Could not save history to {:?}: {}", path, e);
+                            }
+                        }
+                        
+                        let command = commands::parse_command(&input);
+                        if matches!(command, commands::Command::Exit) {
+                            if confirm_exit(config, session_manager, false, &cancel_tx, &mut events).await? {
+                                break;
+                            }
+                            editor.clear();
+                            terminal.invalidate_status();
+                            continue;
+                        }
                         
                         // Process command
-                        if let Err(e) = process_command_with_streaming(
-                            input,
+                        match process_command_with_streaming(
+                            command,
                             Arc::clone(&client),
                             session_manager,
+                            config,
+                            &mut editor,
+                            &mut query_options,
                             &cancel_tx,
+                            &mut events,
                         ).await {
-                            error!("Command failed: {}", e);
-                            println!("{}Error:{} {}", ansi::colors::RED, ansi::colors::RESET, e);
+                            Ok(true) => break,
+                            Ok(false) => {}
+                            Err(e) => {
+                                error!("Command failed: {}", e);
+                                notice::error(format!("Error: {}", e));
+                            }
                         }
                         
                         // Clear editor for next input
                         editor.clear();
                         println!(); // Extra line for spacing
+                        
+                        // Command output may have scrolled the status line away
+                        terminal.invalidate_status();
                     }
                     EditorAction::HistoryUp => {
                         if let Some(cmd) = editor.navigate_up() {
@@ -289,24 +560,14 @@ async fn run_interactive_loop(
                     }
                     EditorAction::ShowHistory => {
                         println!();
-                        let history = editor.get_recent_history(10);
-                        if history.is_empty() {
-                            println!("{}No history yet{}", ansi::colors::YELLOW, ansi::colors::RESET);
-                        } else {
-                            println!("{}Recent commands:{}", ansi::colors::CYAN, ansi::colors::RESET);
-                            for (i, cmd) in history.iter().enumerate() {
-                                println!("  {}. {}", history.len() - i, cmd);
-                            }
-                        }
+                        let entries = editor.history_entries(10, None);
+                        commands::display_history(&mut output::StdoutOutput, &entries, None, ansi::content_width(None));
                         println!();
+                        terminal.invalidate_status();
                     }
-                    EditorAction::ReverseSearch => {
-                        // Start reverse search mode
+                    EditorAction::ReverseSearch | EditorAction::ReverseSearchForward => {
+                        // Later keys go to the search until it is accepted or cancelled
                         editor.start_reverse_search();
-                        println!();
-                        println!("{}(reverse-i-search): {}", ansi::colors::CYAN, ansi::colors::RESET);
-                        // TODO: Implement full reverse search UI in future iteration
-                        editor.exit_reverse_search();
                     }
                     EditorAction::DeleteToStart => {
                         editor.delete_to_start();
@@ -317,16 +578,71 @@ async fn run_interactive_loop(
                     EditorAction::DeletePrevWord => {
                         editor.delete_prev_word();
                     }
+                    EditorAction::ExternalEdit => {
+                        let Some(editor_cmd) = external_editor::editor_command() else {
+                            println!();
+                            notice::warn("No external editor configured - set $EDITOR (or $VISUAL), e.g. export EDITOR=vim");
+                            continue;
+                        };
+                        
+                        terminal.suspend()?;
+                        let initial = editor.get_buffer().to_string();
+                        let edited = tokio::task::spawn_blocking(move || {
+                            external_editor::edit_text(&initial, |path| {
+                                external_editor::spawn_editor(&editor_cmd, path)
+                            })
+                        }).await;
+                        // Even if the editor task panicked, give the terminal back first
+                        terminal.resume()?;
+                        if !terminal.is_inline() {
+                            terminal.clear_screen()?;
+                        }
+                        
+                        match edited? {
+                            Ok(text) => editor.set_buffer(text),
+                            Err(e) => notice::error(format!("External editor failed: {}", e)),
+                        }
+                    }
                     EditorAction::Cancel => {
                         // Cancel by sending cancellation signal
                         if cancel_tx.send(true).is_ok() {
-                            println!("\n{}Cancellation signal sent...{}", ansi::colors::YELLOW, ansi::colors::RESET);
+                            println!();
+                            notice::warn("Cancellation signal sent...");
                         }
                         editor.clear();
                         println!();
+                        terminal.invalidate_status();
+                    }
+                    EditorAction::CycleSession(direction) => {
+                        println!();
+                        match session_manager.cycle_session(direction, config.session.on_load).await {
+                            Ok(Some(session)) => {
+                                notice::success(format!("Switched to session: {}", session.metadata.title));
+                                println!("   {} messages, {} tokens", 
+                                    session.messages.len(), session.metadata.total_tokens);
+                                print_session_preview(config, &session);
+                            }
+                            Ok(None) => notice::info("No other session to switch to"),
+                            Err(e) => {
+                                error!("Failed to switch session: {}", e);
+                                notice::error(format!("Failed to switch session: {}", e));
+                            }
+                        }
+                        println!();
+                        terminal.invalidate_status();
+                    }
+                    EditorAction::CommandPalette => {
+                        println!();
+                        if let Some(text) = palette::run()? {
+                            editor.set_buffer(text);
+                        }
+                        terminal.invalidate_status();
                     }
                     EditorAction::Exit => {
-                        break;
+                        if confirm_exit(config, session_manager, false, &cancel_tx, &mut events).await? {
+                            break;
+                        }
+                        terminal.invalidate_status();
                     }
                     EditorAction::Redraw => {
                         // Will redraw on next loop iteration
@@ -339,6 +655,16 @@ async fn run_interactive_loop(
             Event::Resize(cols, rows) => {
                 info!("📱 Terminal resized to {}x{}", cols, rows);
                 
+                // The status line and input are redrawn (and the input re-wrapped)
+                // at the new size by the next loop iteration. Output above the
+                // prompt isn't ours to repaint, so a width change only clears
+                // the status rows the old width may have left broken.
+                match terminal.handle_resize(cols, rows) {
+                    terminal_manager::ResizeRedraw::Nothing => continue,
+                    terminal_manager::ResizeRedraw::StatusAndPrompt => {}
+                    terminal_manager::ResizeRedraw::Reflow => terminal.clear_status_area()?,
+                }
+                
                 // Send context.update notification to backend
                 let notification = ipc::message::Notification::context_update_terminal_size(cols, rows);
                 let mut client_lock = client.lock().await;
@@ -355,142 +681,682 @@ async fn run_interactive_loop(
         }
     }
     
+    if let Some(path) = &history_path {
+        if let Err(e) = editor.save_history(path) {
+            warn!("Could not save history to {:?}: {}", path, e);
+        }
+    }
+    
     // Restore terminal before exiting
     terminal.restore()?;
     Ok(())
 }
 
+/// Ask before exiting if `terminal.confirm_on_exit` is set and there is work to lose
+///
+/// Returns whether to exit.
+async fn confirm_exit(
+    config: &config::Config,
+    session_manager: &session::SessionManager,
+    stream_active: bool,
+    cancel_tx: &watch::Sender<bool>,
+    events: &mut idle::InputEvents,
+) -> Result<bool> {
+    let has_content = !session_manager.transcript().messages().is_empty();
+    if !commands::should_confirm_exit(config.terminal.confirm_on_exit, stream_active, has_content) {
+        return Ok(true);
+    }
+    
+    println!();
+    notice::warn("This conversation will be closed.");
+    println!("{}Exit anyway? (y/N):{} ", ansi::colors::BRIGHT_WHITE, ansi::colors::RESET);
+    io::stdout().flush()?;
+    wait_for_approval(cancel_tx, events).await
+}
+
 /// Process a command with non-blocking streaming support
+///
+/// Returns whether the user chose to quit while a response was streaming.
+#[allow(clippy::too_many_arguments)]
 async fn process_command_with_streaming(
-    input: &str,
+    command: commands::Command,
     client: Arc<Mutex<ipc::client::IpcClient>>,
     session_manager: &mut session::SessionManager,
+    config: &mut config::Config,
+    editor: &mut LineEditor,
+    query_options: &mut ipc::message::QueryOptions,
     cancel_tx: &watch::Sender<bool>,
-) -> Result<()> {
-    let command = commands::parse_command(input);
-    
+    events: &mut idle::InputEvents,
+) -> Result<bool> {
     match command {
         commands::Command::Query(query) => {
             // Reset cancellation before starting
             let _ = cancel_tx.send(false);
-            if let Err(e) = handle_agent_query_concurrent(Arc::clone(&client), &query, cancel_tx).await {
-                error!("Query failed: {}", e);
-                println!("{}Error:{} {}", ansi::colors::RED, ansi::colors::RESET, e);
+            match handle_agent_query_concurrent(Arc::clone(&client), &query, None, query_options, config, session_manager, cancel_tx, events).await {
+                Ok(exit) => return Ok(exit),
+                Err(e) => {
+                    error!("Query failed: {}", e);
+                    notice::error(format!("Query failed: {}", e));
+                }
+            }
+        }
+        commands::Command::Continue => {
+            let Some(interrupted) = session_manager.interrupted().cloned() else {
+                notice::info("Nothing to continue: the last response finished normally");
+                return Ok(false);
+            };
+            let _ = cancel_tx.send(false);
+            match handle_agent_query_concurrent(
+                Arc::clone(&client), &interrupted.query, Some(&interrupted.query_id), query_options, config, session_manager, cancel_tx, events,
+            ).await {
+                Ok(exit) => return Ok(exit),
+                Err(e) => {
+                    error!("Continue failed: {}", e);
+                    notice::error(format!("Continue failed: {}", e));
+                }
             }
         }
         commands::Command::ListSessions(limit) => {
             match session_manager.list_sessions(limit).await {
-                Ok(sessions) => commands::display_sessions_list(&sessions),
+                Ok(sessions) => commands::display_sessions_list(&mut output::StdoutOutput, &sessions, session_manager.now()),
                 Err(e) => {
                     error!("Failed to list sessions: {}", e);
-                    println!("{}Error:{} {}", ansi::colors::RED, ansi::colors::RESET, e);
+                    notice::error(format!("Failed to list sessions: {}", e));
+                }
+            }
+        }
+        commands::Command::SearchSessions(query) => {
+            match session_manager.search_sessions(&query, None).await {
+                Ok(hits) => commands::display_search_results(&mut output::StdoutOutput, &query, &hits, session_manager.now()),
+                Err(e) => {
+                    error!("Failed to search sessions: {}", e);
+                    notice::error(format!("Failed to search sessions: {}", e));
                 }
             }
         }
         commands::Command::LoadSession(session_id) => {
-            match session_manager.load_session(&session_id).await {
+            match session_manager.load_and_activate(&session_id, config.session.on_load).await {
                 Ok(session) => {
-                    println!("{}✅ Loaded session:{} {}", 
-                        ansi::colors::GREEN, ansi::colors::RESET, session.metadata.title);
+                    notice::success(format!("Loaded session: {}", session.metadata.title));
                     println!("   {} messages, {} tokens", 
                         session.messages.len(), session.metadata.total_tokens);
+                    print_session_preview(config, &session);
                     println!();
                 }
                 Err(e) => {
                     error!("Failed to load session: {}", e);
-                    println!("{}Error:{} {}", ansi::colors::RED, ansi::colors::RESET, e);
+                    notice::error(format!("Failed to load session: {}", e));
                 }
             }
         }
-        commands::Command::ExportSession { session_id, format, output_file } => {
+        commands::Command::NewSession(title) => {
+            match session_manager.create_session(title.as_deref()).await {
+                Ok(metadata) => {
+                    // A new session starts a new conversation
+                    session_manager.transcript_mut().replace(Vec::new());
+                    notice::success(format!("Started new session: {}", metadata.title));
+                    println!("   New messages go to {}", metadata.session_id);
+                    println!();
+                }
+                Err(e) => {
+                    error!("Failed to create session: {}", e);
+                    notice::error(format!("Failed to create session: {}", e));
+                }
+            }
+        }
+        commands::Command::ForkSession(session_id) => {
+            match session_manager.fork_session(session_id.as_deref()).await {
+                Ok(metadata) => {
+                    notice::success(format!("Forked into new session: {}", metadata.title));
+                    println!("   {} messages copied; new messages go to {}", 
+                        metadata.message_count, metadata.session_id);
+                    println!();
+                }
+                Err(e) => {
+                    error!("Failed to fork session: {}", e);
+                    notice::error(format!("Failed to fork session: {}", e));
+                }
+            }
+        }
+        commands::Command::ResumeLast => {
+            match session_manager.resume_last(config.session.on_load).await {
+                Ok(Some(session)) => {
+                    notice::success(format!("Resumed session: {}", session.metadata.title));
+                    println!("   {} messages, {} tokens", 
+                        session.messages.len(), session.metadata.total_tokens);
+                    print_session_preview(config, &session);
+                    println!();
+                }
+                Ok(None) => {
+                    notice::warn("No sessions to resume yet");
+                    notice::info("Start a conversation to create a new session!");
+                }
+                Err(e) => {
+                    error!("Failed to resume session: {}", e);
+                    notice::error(format!("Failed to resume session: {}", e));
+                }
+            }
+        }
+        commands::Command::ExportSession { session_id, format, output_file, to_stdout, force } => {
             let session_ref = session_id.as_deref();
-            match session_manager.export_session(session_ref, &format).await {
-                Ok(content) => {
-                    if let Some(file_path) = output_file {
-                        match std::fs::write(&file_path, &content) {
-                            Ok(_) => {
-                                println!("{}✅ Exported to:{} {}", 
-                                    ansi::colors::GREEN, ansi::colors::RESET, file_path);
+            let exported = if format == session::JSON_EXPORT_FORMAT {
+                session_manager.export_session_structured(session_ref).await
+            } else {
+                session_manager.export_session(session_ref, &format).await
+            };
+            match exported {
+                Ok(export) => {
+                    if to_stdout {
+                        println!("{}", export.content());
+                    } else {
+                        // Inline output scrolls away in the alternate screen, so
+                        // default to a fresh temp file the user can open afterwards
+                        let written = match output_file.map(std::path::PathBuf::from) {
+                            Some(file_path) => {
+                                if commands::export_needs_confirmation(&file_path, force) {
+                                    notice::warn(format!("{} already exists.", file_path.display()));
+                                    let answer = prompt::read_line_raw("Overwrite? [y/N]: ").await?;
+                                    if !answer.as_deref().is_some_and(prompt::parse_yes_no) {
+                                        notice::info("Export cancelled; file left unchanged");
+                                        return Ok(false);
+                                    }
+                                }
+                                std::fs::write(&file_path, export.content()).map(|_| file_path)
+                            }
+                            None => {
+                                let id = session_ref.or(session_manager.current_session_id());
+                                commands::create_default_export_file(id, &format).and_then(|(mut file, file_path)| {
+                                    file.write_all(export.content().as_bytes()).map(|_| file_path)
+                                })
+                            }
+                        };
+                        match written {
+                            Ok(file_path) => {
+                                let written = std::path::absolute(&file_path).unwrap_or(file_path);
+                                notice::success(format!("{} to: {}", export.summary(), written.display()));
                             }
                             Err(e) => {
-                                println!("{}Error writing file:{} {}", 
-                                    ansi::colors::RED, ansi::colors::RESET, e);
+                                notice::error(format!("Error writing file: {}", e));
                             }
                         }
-                    } else {
-                        println!("{}", content);
                     }
                 }
                 Err(e) => {
                     error!("Failed to export session: {}", e);
-                    println!("{}Error:{} {}", ansi::colors::RED, ansi::colors::RESET, e);
+                    notice::error(format!("Failed to export session: {}", e));
                 }
             }
         }
         commands::Command::DeleteSession(session_id) => {
             match session_manager.delete_session(&session_id).await {
                 Ok(_) => {
-                    println!("{}✅ Session deleted:{} {}", 
-                        ansi::colors::GREEN, ansi::colors::RESET, session_id);
+                    notice::success(format!("Session deleted: {}", session_id));
                 }
                 Err(e) => {
                     error!("Failed to delete session: {}", e);
-                    println!("{}Error:{} {}", ansi::colors::RED, ansi::colors::RESET, e);
+                    notice::error(format!("Failed to delete session: {}", e));
+                }
+            }
+        }
+        commands::Command::RenameSession { session_id, title } => {
+            match session_manager.rename_session(&session_id, &title).await {
+                Ok(_) => notice::success(format!("Renamed session {} to \"{}\"", session_id, title)),
+                Err(e) => {
+                    error!("Failed to rename session: {}", e);
+                    notice::error(format!("Failed to rename session: {}", e));
+                }
+            }
+        }
+        commands::Command::PinSession(session_id) => {
+            match session_manager.set_pinned(&session_id, true).await {
+                Ok(_) => notice::success(format!("Pinned session: {}", session_id)),
+                Err(e) => {
+                    error!("Failed to pin session: {}", e);
+                    notice::error(format!("Failed to pin session: {}", e));
+                }
+            }
+        }
+        commands::Command::UnpinSession(session_id) => {
+            match session_manager.set_pinned(&session_id, false).await {
+                Ok(_) => notice::success(format!("Unpinned session: {}", session_id)),
+                Err(e) => {
+                    error!("Failed to unpin session: {}", e);
+                    notice::error(format!("Failed to unpin session: {}", e));
+                }
+            }
+        }
+        commands::Command::ListFavorites => {
+            match session_manager.list_sessions(None).await {
+                Ok(sessions) => {
+                    let pinned: Vec<_> = sessions.into_iter().filter(|s| s.pinned).collect();
+                    if pinned.is_empty() {
+                        notice::warn("No pinned sessions.");
+                        println!("Use /pin <session-id> to pin one");
+                    } else {
+                        commands::display_sessions_list(&mut output::StdoutOutput, &pinned, session_manager.now());
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to list sessions: {}", e);
+                    notice::error(format!("Failed to list sessions: {}", e));
+                }
+            }
+        }
+        commands::Command::SetLogLevel(level) => {
+            level.apply();
+            notice::success(format!("Log level set to {}", level.to_filter_str()));
+        }
+        commands::Command::ShowLogLevel => {
+            notice::info(format!("Log level: {}", cli::LogLevel::current().to_filter_str()));
+        }
+        commands::Command::TogglePrivate => {
+            if editor.toggle_mask() {
+                notice::success("Privacy mode on: input is masked until submitted");
+            } else {
+                notice::success("Privacy mode off");
+            }
+        }
+        commands::Command::ListThemes => {
+            commands::display_themes(&mut output::StdoutOutput, ansi::active_theme().name);
+        }
+        commands::Command::SetTheme(name) => {
+            if ansi::set_theme(&name) {
+                config.terminal.theme = ansi::active_theme().name.to_string();
+                notice::success(format!("Theme set to {}", config.terminal.theme));
+            } else {
+                notice::warn(format!("Unknown theme: {}", name));
+                let names: Vec<&str> = ansi::THEMES.iter().map(|t| t.name).collect();
+                println!("Available themes: {}", names.join(", "));
+            }
+        }
+        commands::Command::ListModels => {
+            let result = client.lock().await.list_models().await;
+            match result {
+                Ok(models) => commands::display_models(
+                    &mut output::StdoutOutput,
+                    models.as_deref(),
+                    &config.agent.model,
+                ),
+                Err(e) => {
+                    error!("Failed to list models: {}", e);
+                    notice::error(format!("Failed to list models: {}", e));
                 }
             }
         }
         commands::Command::SessionInfo => {
             commands::display_session_info(
+                &mut output::StdoutOutput,
                 session_manager.current_session_id(),
                 session_manager
             );
         }
+        commands::Command::SetSystemPrompt(prompt) => {
+            match &prompt {
+                Some(text) => notice::success(format!("System prompt set ({} chars)", text.chars().count())),
+                None => notice::success("System prompt cleared"),
+            }
+            query_options.system_prompt = prompt;
+        }
+        commands::Command::SetTemperature(temperature) => {
+            config.agent.temperature = temperature;
+            query_options.temperature = Some(temperature);
+            notice::success(format!("Temperature set to {} for following queries", temperature));
+        }
+        commands::Command::ShowTemperature => {
+            notice::info(format!("Temperature: {}", config.agent.temperature));
+        }
+        commands::Command::SetMaxTokens(max_tokens) => {
+            config.agent.max_tokens = max_tokens;
+            query_options.max_tokens = Some(max_tokens);
+            notice::success(format!("Max tokens set to {} for following queries", max_tokens));
+        }
+        commands::Command::ShowMaxTokens => {
+            notice::info(format!("Max tokens: {}", config.agent.max_tokens));
+        }
+        commands::Command::ShowSystemPrompt => {
+            match query_options.system_prompt.as_deref() {
+                Some(text) => println!("{}System prompt:{} {}", 
+                    ansi::colors::BRIGHT_WHITE, ansi::colors::RESET, text),
+                None => notice::info("No system prompt set (using backend default)"),
+            }
+        }
+        commands::Command::History { limit, search } => {
+            let entries = editor.history_entries(
+                limit.unwrap_or(commands::DEFAULT_HISTORY_LIMIT),
+                search.as_deref(),
+            );
+            commands::display_history(&mut output::StdoutOutput, &entries, search.as_deref(), ansi::content_width(None));
+        }
+        commands::Command::ExportHistory(path) => {
+            match editor.export_history(std::path::Path::new(&path)) {
+                Ok(count) => notice::success(format!("Exported {} history entries to {}", count, path)),
+                Err(e) => notice::error(format!("Failed to export history to {}: {}", path, e)),
+            }
+        }
+        commands::Command::ImportHistory(path) => {
+            match editor.import_history(std::path::Path::new(&path)) {
+                Ok(count) => notice::success(format!("Imported {} new history entries from {}", count, path)),
+                Err(e) => notice::error(format!("Failed to import history from {}: {}", path, e)),
+            }
+        }
+        commands::Command::SafeDir(action) => {
+            let home = dirs::home_dir();
+            let home = home.as_deref();
+            let changed = match action {
+                commands::SafeDirAction::List => {
+                    commands::display_safe_directories(&mut output::StdoutOutput, &config.tools.safe_directories, home);
+                    return Ok(false);
+                }
+                commands::SafeDirAction::Add(path) => {
+                    let expanded = commands::expand_home(&path, home);
+                    if !std::path::Path::new(&expanded).is_dir() {
+                        notice::error(format!("Not a directory: {}", expanded));
+                        return Ok(false);
+                    }
+                    if !commands::add_safe_directory(&mut config.tools.safe_directories, &path, home) {
+                        notice::info(format!("{} is already a safe directory", expanded));
+                        return Ok(false);
+                    }
+                    format!("Added safe directory: {}", expanded)
+                }
+                commands::SafeDirAction::Remove(path) => {
+                    if !commands::remove_safe_directory(&mut config.tools.safe_directories, &path, home) {
+                        notice::warn(format!("{} is not a safe directory", path));
+                        return Ok(false);
+                    }
+                    format!("Removed safe directory: {}", path)
+                }
+            };
+            
+            // Only the allowlist is written back, not this run's CLI overrides or /exec state
+            if let Err(e) = config.save_setting("tools.safe_directories") {
+                notice::error(format!("Failed to save config: {:#}", e));
+            }
+            
+            let expanded: Vec<String> = config.tools.safe_directories.iter()
+                .map(|dir| commands::expand_home(dir, home))
+                .collect();
+            let notification = ipc::message::Notification::context_update_safe_directories(&expanded);
+            if let Err(e) = client.lock().await.send_notification(notification).await {
+                error!("Failed to send tools context update: {}", e);
+                notice::error(format!("Failed to notify backend: {}", e));
+            }
+            notice::success(changed);
+        }
+        commands::Command::Config(action) => match action {
+            commands::ConfigAction::Show => commands::display_config(&mut output::StdoutOutput, config),
+            commands::ConfigAction::Get(key) => match config.get_path(&key) {
+                Ok(value) => commands::display_setting(&mut output::StdoutOutput, &key, &value),
+                Err(e) => notice::error(e),
+            },
+            commands::ConfigAction::Set { key, value } => {
+                if let Err(e) = config.set_path(&key, &value) {
+                    notice::error(e);
+                    return Ok(false);
+                }
+                // Settings read once at startup need applying by hand; the status
+                // line picks up agent.model on its next redraw
+                match key.as_str() {
+                    "terminal.theme" if !ansi::set_theme(&config.terminal.theme) => {
+                        notice::warn(format!("Unknown theme '{}', still using {}", config.terminal.theme, ansi::active_theme().name));
+                    }
+                    "terminal.glyphs" => glyphs::set_mode(config.terminal.glyphs),
+                    "agent.request_timeout_secs" => {
+                        client.lock().await.set_default_timeout(std::time::Duration::from_secs(config.agent.request_timeout_secs));
+                    }
+                    "agent.auto_reconnect" => client.lock().await.set_auto_reconnect(config.agent.auto_reconnect),
+                    "agent.temperature" => query_options.temperature = Some(config.agent.temperature),
+                    "agent.max_tokens" => query_options.max_tokens = Some(config.agent.max_tokens),
+                    "agent.system_prompt" => query_options.system_prompt = config.agent.system_prompt.clone(),
+                    _ => {}
+                }
+
+                // Only this setting is written back, not this run's CLI overrides
+                match config.save_setting(&key) {
+                    Ok(()) => {
+                        let shown = config.get_path(&key).map(|v| v.to_string()).unwrap_or(value);
+                        notice::success(format!("{} = {}", key, shown));
+                    }
+                    Err(e) => notice::error(format!("Changed for this session, but failed to save config: {:#}", e)),
+                }
+            }
+        },
+        commands::Command::ToggleExec(enable) => {
+            if enable && config.safe_mode.enabled {
+                notice::error("Real execution cannot be enabled in safe mode");
+                return Ok(false);
+            }
+            
+            let confirmation = if enable && !config.tools.enable_real_execution {
+                notice::warn("Real execution lets the agent modify files and run commands on this machine.");
+                prompt::read_line_raw(&format!("Type {} to enable: ", commands::EXEC_CONFIRMATION_WORD)).await?
+            } else {
+                None
+            };
+            
+            if !commands::exec_toggle_allowed(enable, config.tools.enable_real_execution, confirmation.as_deref()) {
+                notice::info("Real execution left disabled");
+                return Ok(false);
+            }
+            
+            config.tools.enable_real_execution = enable;
+            let notification = ipc::message::Notification::context_update_tools(enable);
+            if let Err(e) = client.lock().await.send_notification(notification).await {
+                error!("Failed to send tools context update: {}", e);
+                notice::error(format!("Failed to notify backend: {}", e));
+            }
+            
+            if enable {
+                notice::warn("Real tool execution ENABLED");
+            } else {
+                notice::success("Real tool execution disabled (demo mode)");
+            }
+        }
+        commands::Command::Diagnostics => {
+            let client = client.lock().await;
+            commands::display_diagnostics(client.server_info(), client.connection_state(), client.diagnostics());
+        }
+        commands::Command::LastMessage => {
+            match client.lock().await.last_raw_message() {
+                Some(raw) => println!("{}", commands::format_last_message(&raw)),
+                None => notice::info("No messages received from the backend yet"),
+            }
+        }
+        commands::Command::ResetDiagnostics => {
+            client.lock().await.reset_diagnostics();
+            notice::success("Diagnostic counters reset");
+        }
+        commands::Command::Noop => {}
         commands::Command::Help => {
-            commands::display_help();
+            commands::display_help(&mut output::StdoutOutput);
         }
         commands::Command::Exit => {
-            // Exit will be handled by outer loop
+            // Handled by the interactive loop before dispatch
         }
     }
     
-    Ok(())
+    Ok(false)
 }
 
 /// Handle an agent query with concurrent streaming using tokio::select!
+///
+/// With `resume` (the `query_id` of an interrupted response), asks the backend
+/// to continue that response with `agent.continue` instead of sending `query`.
+/// Returns whether the user chose to quit while the response streamed.
+#[allow(clippy::too_many_arguments)]
 async fn handle_agent_query_concurrent(
     client: Arc<Mutex<ipc::client::IpcClient>>,
     query: &str,
+    resume: Option<&str>,
+    query_options: &ipc::message::QueryOptions,
+    config: &config::Config,
+    session_manager: &mut session::SessionManager,
     cancel_tx: &watch::Sender<bool>,
-) -> Result<()> {
+    events: &mut idle::InputEvents,
+) -> Result<bool> {
+    // Over the limit: tell the user instead of sending anything
+    let method = if resume.is_some() { "agent.continue" } else { "agent.query" };
+    if let Err(wait) = client.lock().await.check_rate_limit(method) {
+        notice::warn(format!("Rate limit reached, wait {}s", wait.as_secs_f64().ceil() as u64));
+        return Ok(false);
+    }
+    let mut exit = false;
+    
+    // Make sure the conversation is persisted under a session we know the ID of
+    if resume.is_none() && session::should_auto_create(config.session.auto_create_session, session_manager.current_session_id()) {
+        match session_manager.create_session(None).await {
+            Ok(metadata) => info!("📝 Created session {} for this conversation", metadata.session_id),
+            Err(e) => warn!("Could not create a session before the query: {}", e),
+        }
+    }
+    
     println!();
-    println!("{}🤖 AI:{} ", ansi::colors::BRIGHT_CYAN, ansi::colors::RESET);
+    println!("{}{}{} ", ansi::colors::BRIGHT_CYAN, Glyph::Response.get(), ansi::colors::RESET);
     io::stdout().flush()?;
     
-    // Send query request
-    let request = {
-        let mut client = client.lock().await;
-        ipc::message::Request::agent_query(client.next_request_id(), query.to_string())
+    // The first query after loading a session carries its history as context
+    // (taken before this query is recorded so it isn't sent twice)
+    let mut query_options = query_options.clone();
+    if resume.is_none() {
+        query_options.history = session_manager.activation_context();
+    }
+    if config.agent.thread_queries && client.lock().await.supports_capability("threading") {
+        query_options.parent = session_manager.parent_turn().map(str::to_string);
+    }
+    let query_options = &query_options;
+    
+    // A continuation is another entry in the transcript file, but adds no user message
+    transcript::record(|t| t.begin(if resume.is_some() { "/continue" } else { query }, chrono::Utc::now()));
+    if resume.is_none() {
+        let now = session_manager.now();
+        session_manager.transcript_mut().push_user(query, now);
+    }
+    let session_id = session_manager.current_session_id().map(str::to_string);
+    let session_id = session_id.as_deref();
+    
+    // Only the initial send is retried; a stream that already started is not
+    let retry = if config.agent.retry_queries {
+        error::RetryConfig::for_query()
+    } else {
+        error::RetryConfig { max_attempts: 1, ..error::RetryConfig::for_query() }
     };
     
-    let response = {
+    // Send query request (streamed only if the backend supports it)
+    let (response, streaming) = {
         let mut client = client.lock().await;
-        client.send_request(request).await?
+        let streaming = client.supports_streaming();
+        let response = client.send_request_with_retry(
+            |client| match resume {
+                Some(query_id) => ipc::message::Request::agent_continue(
+                    client.next_request_id(), query_id, query_options, streaming,
+                ),
+                None => ipc::message::Request::agent_query_with_options(
+                    client.next_request_id(), query.to_string(), query_options, streaming,
+                ),
+            },
+            &retry,
+            |attempt, max| notice::warn(format!("Query failed, retrying ({}/{})...", attempt, max)),
+        ).await?;
+        (response, streaming)
     };
     
+    // Only a request the backend took replaces what /continue would resume,
+    // or delivers the activation context
+    if response.result.is_some() {
+        session_manager.set_interrupted(None);
+        if resume.is_none() {
+            session_manager.activation_context_sent();
+        }
+    }
+    
     if let Some(result) = response.result {
-        if let Some(_query_id) = result.get("query_id").and_then(|v| v.as_str()) {
+        let query_id = result.get("query_id").and_then(|v| v.as_str());
+        if !streaming || query_id.is_none() {
+            // Non-streaming backend: the answer is in the response itself
+            let text = commands::inline_response_text(&result);
+            if let Some(turn_id) = ipc::message::turn_id(&result) {
+                session_manager.record_turn(turn_id);
+            }
+            if let Some(query_id) = query_id.filter(|_| ipc::message::is_truncated(&result)) {
+                session_manager.set_interrupted(Some(session::Interrupted { query_id: query_id.to_string(), query: query.to_string() }));
+            }
+            println!("{}\n", text);
+            transcript::record(|t| t.push_text(&text));
+            transcript::record(|t| t.finish(transcript::Outcome::Complete, chrono::Utc::now()));
+            let now = session_manager.now();
+            session_manager.transcript_mut().push_assistant(&text, now);
+            run_response_hook(config, query, None, session_id);
+        } else {
             // Create cancellation receiver
             let mut cancel_rx = cancel_tx.subscribe();
+            let query_id = query_id.unwrap_or_default();
+            let mut streams = streams::ActiveStreams::new();
+            streams.set_dedupe_code_blocks(config.terminal.dedupe_code_blocks);
+            let mut stream_cancelled = streams.start(query_id);
+            // What the stream had received when it was cancelled
+            let mut cancelled_output = None;
             
             // Stream handling loop with concurrent select
             loop {
                 // Use tokio::select! to handle notifications and cancellation concurrently
                 tokio::select! {
-                    // Check for cancellation
+                    // The user cancelled: cancel this query's stream
                     Ok(_) = cancel_rx.changed() => {
                         if *cancel_rx.borrow() {
-                            println!("\n{}Stream cancelled by user{}", ansi::colors::YELLOW, ansi::colors::RESET);
-                            break;
+                            cancelled_output = streams.cancel(query_id);
+                        }
+                    }
+                    
+                    // This query's stream was cancelled
+                    Ok(_) = stream_cancelled.changed() => {
+                        println!();
+                        notice::warn("Stream cancelled by user (/continue resumes it)");
+                        transcript::record(|t| t.finish(transcript::Outcome::Cancelled, chrono::Utc::now()));
+                        if let Some(stream) = cancelled_output.take().filter(|s| !s.text.is_empty()) {
+                            let now = session_manager.now();
+                            session_manager.transcript_mut().push_assistant(&stream.text, now);
+                        }
+                        session_manager.set_interrupted(Some(session::Interrupted {
+                            query_id: query_id.to_string(),
+                            query: query.to_string(),
+                        }));
+                        break;
+                    }
+                    
+                    // Esc or Ctrl+C pressed while streaming
+                    event = events.next_event() => {
+                        match event? {
+                            Event::Key(key_event)
+                                if line_editor::is_stream_cancel_key(key_event.code, key_event.modifiers) =>
+                            {
+                                let _ = cancel_tx.send(true);
+                            }
+                            // Ctrl+D quits (once confirmed, if configured), cancelling the response
+                            Event::Key(key_event)
+                                if key_event.code == KeyCode::Char('d')
+                                    && key_event.modifiers.contains(crossterm::event::KeyModifiers::CONTROL) =>
+                            {
+                                exit = confirm_exit(config, session_manager, true, cancel_tx, events).await?;
+                                if exit {
+                                    let _ = cancel_tx.send(true);
+                                }
+                            }
+                            // Switching sessions mid-response would split it across two
+                            Event::Key(key_event) if session_keys(config).is_some_and(|(previous, next)| {
+                                previous.matches(key_event.code, key_event.modifiers)
+                                    || next.matches(key_event.code, key_event.modifiers)
+                            }) => {
+                                println!();
+                                notice::warn("Can't switch sessions while a response is streaming (Esc cancels it)");
+                            }
+                            // Still tell the backend; the prompt redraw waits for the response to end
+                            Event::Resize(cols, rows) => {
+                                let notification = ipc::message::Notification::context_update_terminal_size(cols, rows);
+                                if let Err(e) = client.lock().await.send_notification(notification).await {
+                                    error!("❌ Failed to send resize notification: {}", e);
+                                }
+                            }
+                            _ => {}
                         }
                     }
                     
@@ -500,18 +1366,63 @@ async fn handle_agent_query_concurrent(
                         client.next_notification().await
                     } => {
                         match notification_result {
+                            Ok(notification) if notification.is_connection_closed() => {
+                                println!();
+                                notice::error("Connection to backend lost");
+                                transcript::record(|t| t.finish(transcript::Outcome::Disconnected, chrono::Utc::now()));
+                                if let Some(stream) = streams.cancel(query_id).filter(|s| !s.text.is_empty()) {
+                                    let now = session_manager.now();
+                                    session_manager.transcript_mut().push_assistant(&stream.text, now);
+                                }
+                                break;
+                            }
                             Ok(notification) => {
+                                let completed = match streams.route(&notification) {
+                                    streams::Routed::Output | streams::Routed::Unscoped => None,
+                                    streams::Routed::Complete(stream) => Some(stream),
+                                    streams::Routed::RepeatedBlock => {
+                                        let omitted = format!("{}(identical block omitted){}\n",
+                                            ansi::colors::BRIGHT_BLACK, ansi::colors::RESET);
+                                        print!("{}", omitted);
+                                        io::stdout().flush()?;
+                                        continue;
+                                    }
+                                    streams::Routed::Dropped => {
+                                        debug!("Dropping {} for an inactive stream", notification.method);
+                                        continue;
+                                    }
+                                };
                                 if let Err(e) = handle_stream_notification(
                                     &notification,
                                     Arc::clone(&client),
+                                    config,
                                     cancel_tx,
+                                    events,
                                 ).await {
                                     error!("Failed to handle notification: {}", e);
                                 }
                                 
                                 // Check if stream is complete
-                                if notification.method == "stream.complete" {
+                                if let Some(stream) = completed {
                                     println!("\n");
+                                    transcript::record(|t| t.finish(transcript::Outcome::Complete, chrono::Utc::now()));
+                                    let now = session_manager.now();
+                                    session_manager.transcript_mut().push_assistant(&stream.text, now);
+                                    let turn_id = notification.params.as_ref().and_then(ipc::message::turn_id);
+                                    session_manager.record_turn(turn_id.unwrap_or(query_id));
+                                    let complete = notification.params_as::<ipc::message::StreamCompleteParams>().unwrap_or_default();
+                                    if let Some(reason) = complete.finish_reason() {
+                                        if let Some(footer) = commands::finish_footer(reason, notice::color_enabled()) {
+                                            println!("{}\n", footer);
+                                        }
+                                        if *reason == ipc::message::FinishReason::Length {
+                                            session_manager.set_interrupted(Some(session::Interrupted {
+                                                query_id: query_id.to_string(),
+                                                query: query.to_string(),
+                                            }));
+                                        }
+                                    }
+                                    run_response_hook(config, query, complete.metadata.total_tokens, session_id);
                                     break;
                                 }
                             }
@@ -524,100 +1435,181 @@ async fn handle_agent_query_concurrent(
                 }
             }
         }
+    } else if let Some(error) = response.error {
+        println!();
+        transcript::record(|t| t.finish(transcript::Outcome::Cancelled, chrono::Utc::now()));
+        notice::error(format!("{} failed: {}", method, error.message));
     }
     
-    Ok(())
+    Ok(exit)
+}
+
+/// The configured previous/next session keys, if both parse
+fn session_keys(config: &config::Config) -> Option<(line_editor::KeyBinding, line_editor::KeyBinding)> {
+    let previous = line_editor::KeyBinding::parse(&config.keybindings.previous_session);
+    let next = line_editor::KeyBinding::parse(&config.keybindings.next_session);
+    previous.zip(next)
+}
+
+/// Print the first question and last answer of a just-loaded session (if enabled)
+fn print_session_preview(config: &config::Config, session: &session::Session) {
+    if !config.session.preview_on_load {
+        return;
+    }
+    // Indent (3) plus marker (2)
+    let width = ansi::content_width(config.terminal.max_content_width).saturating_sub(5);
+    let preview = session.preview(width);
+    for (marker, text) in [(">", preview.first_user), ("<", preview.last_assistant)] {
+        if let Some(text) = text {
+            println!("   {}{} {}{}", ansi::colors::BRIGHT_BLACK, marker, text, ansi::colors::RESET);
+        }
+    }
+}
+
+/// Run the configured `on_response` hook, if any, for a finished response
+fn run_response_hook(config: &config::Config, query: &str, total_tokens: Option<u64>, session_id: Option<&str>) {
+    if let Some(command) = &config.hooks.on_response {
+        hooks::run_on_response(command, hooks::ResponseEvent {
+            query: query.to_string(),
+            total_tokens,
+            session_id: session_id.map(str::to_string),
+        });
+    }
+}
+
+/// Typed params of `notification`, or `None` (logged) if they don't match `T`
+fn typed_params<T: serde::de::DeserializeOwned>(notification: &ipc::message::Notification) -> Option<T> {
+    match notification.params_as() {
+        Ok(params) => Some(params),
+        Err(e) => {
+            warn!("Skipping {} with malformed params: {}", notification.method, e);
+            None
+        }
+    }
 }
 
 /// Handle a single stream notification
 async fn handle_stream_notification(
     notification: &ipc::message::Notification,
     client: Arc<Mutex<ipc::client::IpcClient>>,
+    config: &config::Config,
     cancel_tx: &watch::Sender<bool>,
+    events: &mut idle::InputEvents,
 ) -> Result<()> {
     match notification.method.as_str() {
         "stream.token" => {
-            if let Some(params) = &notification.params {
-                if let Some(content) = params.get("content").and_then(|v| v.as_str()) {
-                    print!("{}", content);
-                    io::stdout().flush()?;
-                }
+            if let Some(params) = typed_params::<ipc::message::StreamTokenParams>(notification) {
+                let content = params.content.as_str();
+                print!("{}", content);
+                io::stdout().flush()?;
+                transcript::record(|t| t.push_text(content));
             }
         }
         "stream.block" => {
-            if let Some(params) = &notification.params {
-                let block_type = params.get("type").and_then(|v| v.as_str()).unwrap_or("text");
-                let content = params.get("content").and_then(|v| v.as_str()).unwrap_or("");
-                let language = params.get("language").and_then(|v| v.as_str()).unwrap_or("text");
+            if let Some(params) = typed_params::<ipc::message::StreamBlockParams>(notification) {
+                let block_type = params.block_type.as_str();
+                let content = params.content.as_str();
+                let language = params.language();
+                let width = ansi::content_width(config.terminal.max_content_width);
                 
-                match block_type {
-                    "code" => {
-                        let formatted = ansi::format_code_block(language, content);
-                        print!("{}", formatted);
-                    }
-                    "diff" => {
-                        let formatted = ansi::format_diff(content);
-                        print!("{}", formatted);
-                    }
-                    _ => {
-                        print!("{}", content);
-                    }
-                }
+                let rendered = match block_type {
+                    "code" => ansi::format_code_block(language, content, width, ansi::active_theme()),
+                    "diff" => ansi::format_diff(content, width, ansi::active_theme()),
+                    _ => content.to_string(),
+                };
+                print!("{}", rendered);
                 io::stdout().flush()?;
+                transcript::record(|t| t.push_block(block_type, language, content));
             }
         }
         "tool.request_approval" => {
             println!("\n");
-            if let Some(params) = &notification.params {
-                let tool_name = params.get("tool_name").and_then(|v| v.as_str()).unwrap_or("unknown");
-                let description = params.get("description").and_then(|v| v.as_str()).unwrap_or("");
-                let risk_level = params.get("risk_level").and_then(|v| v.as_str()).unwrap_or("unknown");
-                let preview = params.get("preview").and_then(|v| v.as_str()).unwrap_or("");
-                let execution_id = params.get("execution_id").and_then(|v| v.as_str()).unwrap_or("");
-                
-                println!("\n{}🔒 Tool Approval Request{}", ansi::colors::YELLOW, ansi::colors::RESET);
-                println!("{}Tool:{} {}", ansi::colors::BRIGHT_WHITE, ansi::colors::RESET, tool_name);
-                println!("{}Description:{} {}", ansi::colors::BRIGHT_WHITE, ansi::colors::RESET, description);
-                println!("{}Risk Level:{} {}{}{}", 
-                    ansi::colors::BRIGHT_WHITE, 
-                    ansi::colors::RESET,
-                    if risk_level == "high" { ansi::colors::RED } else { ansi::colors::YELLOW },
-                    risk_level.to_uppercase(),
-                    ansi::colors::RESET
-                );
-                println!("\n{}Preview:{}", ansi::colors::BRIGHT_WHITE, ansi::colors::RESET);
-                println!("{}", preview);
-                println!("\n{}Approve this action? (y/N):{} ", ansi::colors::BRIGHT_WHITE, ansi::colors::RESET);
-                io::stdout().flush()?;
-                
-                // Wait for user input with timeout
-                let approved = wait_for_approval(cancel_tx).await?;
+            if let Some(params) = typed_params::<ipc::message::ToolApprovalParams>(notification) {
+                let tool_name = params.tool_name.as_str();
+                let description = params.description.as_str();
+                let risk_level = params.risk_level.as_str();
+                let preview = params.preview.as_str();
+                let execution_id = params.execution_id.as_str();
+                let approved = match commands::approval_style(risk_level, config.tools.compact_approvals) {
+                    commands::ApprovalStyle::Compact => {
+                        print!("\n{}{} Approve {}{}{}: {} ({} risk)? (y/N):{} ",
+                            ansi::colors::YELLOW, Glyph::Lock.get(), ansi::colors::BRIGHT_WHITE, tool_name, ansi::colors::YELLOW,
+                            description, risk_level, ansi::colors::RESET);
+                        io::stdout().flush()?;
+                        wait_for_approval(cancel_tx, events).await?
+                    }
+                    commands::ApprovalStyle::Full => {
+                        println!("\n{}{} Tool Approval Request{}", ansi::colors::YELLOW, Glyph::Lock.get(), ansi::colors::RESET);
+                        println!("{}Tool:{} {}", ansi::colors::BRIGHT_WHITE, ansi::colors::RESET, tool_name);
+                        println!("{}Description:{} {}", ansi::colors::BRIGHT_WHITE, ansi::colors::RESET, description);
+                        println!("{}Risk Level:{} {}{}{}", 
+                            ansi::colors::BRIGHT_WHITE, 
+                            ansi::colors::RESET,
+                            if risk_level == "high" { ansi::colors::RED } else { ansi::colors::YELLOW },
+                            risk_level.to_uppercase(),
+                            ansi::colors::RESET
+                        );
+                        println!("\n{}Preview:{}", ansi::colors::BRIGHT_WHITE, ansi::colors::RESET);
+                        println!("{}", preview);
+                        
+                        // Wait for user input with timeout (unless denied outright)
+                        if config.tools.auto_deny_high_risk && risk_level == "high" {
+                            println!();
+                            notice::warn("High-risk tool denied automatically");
+                            false
+                        } else {
+                            println!("\n{}Approve this action? (y/N):{} ", ansi::colors::BRIGHT_WHITE, ansi::colors::RESET);
+                            io::stdout().flush()?;
+                            wait_for_approval(cancel_tx, events).await?
+                        }
+                    }
+                };
                 
-                // Send approval
+                // Send approval (with our timeout so the backend enforces it)
+                let command_timeout = config.tools.command_timeout;
                 let approve_request = {
                     let mut client = client.lock().await;
-                    ipc::message::Request::new(
+                    ipc::message::Request::tool_approve(
                         client.next_request_id(),
-                        "tool.approve",
-                        Some(serde_json::json!({
-                            "execution_id": execution_id,
-                            "approved": approved
-                        }))
+                        execution_id,
+                        approved,
+                        command_timeout,
                     )
                 };
                 
-                let approval_result = {
+                if approved {
+                    println!("{}{} Running (up to {}s)...{}", 
+                        ansi::colors::BRIGHT_BLACK, Glyph::Running.get(), command_timeout, ansi::colors::RESET);
+                    io::stdout().flush()?;
+                }
+                
+                let result_deadline = std::time::Duration::from_secs(command_timeout) + TOOL_RESULT_GRACE;
+                // The result can take the whole command timeout; wait for it
+                // without holding the client so other traffic keeps flowing
+                let pending = {
                     let mut client = client.lock().await;
-                    client.send_request(approve_request).await
+                    client.start_request(approve_request).await
+                };
+                let approval_result = match pending {
+                    Ok(pending) => pending.wait(result_deadline).await,
+                    Err(e) => Err(e),
                 };
                 
                 match approval_result {
+                    Err(ipc::error::IpcError::Timeout(_)) => {
+                        println!();
+                        notice::warn(format!(
+                            "No tool result after {}s - the command may still be running on the backend",
+                            result_deadline.as_secs()
+                        ));
+                    }
                     Ok(response) => {
                         info!("Tool approval response: {:?}", response);
+                        println!();
                         if approved {
-                            println!("\n{}✅ Tool approved and executed{}", ansi::colors::GREEN, ansi::colors::RESET);
+                            notice::success("Tool approved and executed");
                         } else {
-                            println!("\n{}❌ Tool execution denied{}", ansi::colors::RED, ansi::colors::RESET);
+                            notice::error("Tool execution denied");
                         }
                         if let Some(result) = response.result {
                             println!("Result: {}", serde_json::to_string_pretty(&result).unwrap_or_default());
@@ -625,7 +1617,44 @@ async fn handle_stream_notification(
                     }
                     Err(e) => {
                         error!("Tool approval failed: {}", e);
-                        println!("❌ Tool approval failed: {}", e);
+                        notice::error(format!("Tool approval failed: {}", e));
+                    }
+                }
+            }
+        }
+        "context.auth" => {
+            if let Some(params) = typed_params::<ipc::message::ContextAuthParams>(notification) {
+                let auth_id = params.auth_id.as_str();
+                let prompt_text = params.prompt.as_str();
+                
+                println!("\n");
+                println!("{}{} The backend needs a credential{}", ansi::colors::YELLOW, Glyph::Key.get(), ansi::colors::RESET);
+                println!("{}(input is hidden and not saved to history; Esc to cancel){}", 
+                    ansi::colors::BRIGHT_BLACK, ansi::colors::RESET);
+                // The streaming loop may have left the event stream waiting
+                // for input, which would swallow keys meant for the prompt
+                events.release();
+                let secret = prompt::read_secret(&format!("{}: ", prompt_text)).await?;
+                
+                let auth_request = {
+                    let mut client = client.lock().await;
+                    ipc::message::Request::auth_response(
+                        client.next_request_id(),
+                        auth_id,
+                        secret.as_ref().map(|s| s.expose()),
+                    )
+                };
+                drop(secret);
+                
+                let auth_result = {
+                    let mut client = client.lock().await;
+                    client.send_request(auth_request).await
+                };
+                match auth_result {
+                    Ok(_) => info!("Answered auth challenge {}", auth_id),
+                    Err(e) => {
+                        error!("Auth response failed: {}", e);
+                        notice::error(format!("Failed to send credential: {}", e));
                     }
                 }
             }
@@ -642,7 +1671,7 @@ async fn handle_stream_notification(
 }
 
 /// Wait for user approval input (y/N) with timeout
-async fn wait_for_approval(cancel_tx: &watch::Sender<bool>) -> Result<bool> {
+async fn wait_for_approval(cancel_tx: &watch::Sender<bool>, events: &mut idle::InputEvents) -> Result<bool> {
     use crossterm::terminal;
     
     // Enable raw mode temporarily for single-key input
@@ -654,38 +1683,39 @@ async fn wait_for_approval(cancel_tx: &watch::Sender<bool>) -> Result<bool> {
             // Check for cancellation
             Ok(_) = cancel_rx.changed() => {
                 if *cancel_rx.borrow() {
-                    println!("\n{}Approval cancelled{}", ansi::colors::YELLOW, ansi::colors::RESET);
+                    println!();
+                    notice::warn("Approval cancelled");
                     break Ok(false);
                 }
             }
             
-            // Wait for key press with polling
-            _ = tokio::time::sleep(std::time::Duration::from_millis(50)) => {
-                if event::poll(std::time::Duration::from_millis(10))? {
-                    if let Event::Key(key_event) = event::read()? {
-                        match key_event.code {
-                            KeyCode::Char('y') | KeyCode::Char('Y') => {
-                                println!("y");
-                                break Ok(true);
-                            }
-                            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Enter | KeyCode::Esc => {
-                                println!("n");
-                                break Ok(false);
-                            }
-                            KeyCode::Char('c') if key_event.modifiers.contains(crossterm::event::KeyModifiers::CONTROL) => {
-                                let _ = cancel_tx.send(true);
-                                println!("\n{}Cancelled{}", ansi::colors::YELLOW, ansi::colors::RESET);
-                                break Ok(false);
-                            }
-                            _ => {
-                                // Ignore other keys
-                            }
+            // Wait for key press
+            event = events.next_event() => {
+                if let Event::Key(key_event) = event? {
+                    match key_event.code {
+                        KeyCode::Char('y') | KeyCode::Char('Y') => {
+                            println!("y");
+                            break Ok(true);
+                        }
+                        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Enter | KeyCode::Esc => {
+                            println!("n");
+                            break Ok(false);
+                        }
+                        KeyCode::Char('c') if key_event.modifiers.contains(crossterm::event::KeyModifiers::CONTROL) => {
+                            let _ = cancel_tx.send(true);
+                            println!();
+                            notice::warn("Cancelled");
+                            break Ok(false);
+                        }
+                        _ => {
+                            // Ignore other keys
                         }
                     }
                 }
             }
         }
     };
+    events.release();
     
     // Restore raw mode state (should already be in raw mode from main loop)
     // We don't disable it here since we're in the middle of the interactive loop
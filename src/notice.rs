@@ -0,0 +1,128 @@
+// User-facing notices - Leveled, colored messages for the terminal UI
+//
+// Log output goes to stderr and is hidden while raw mode is active, so anything
+// the user needs to see is rendered through these helpers instead of ad-hoc
+// `println!` calls. Each level has a fixed icon and color.
+
+use crate::ansi::colors;
+use crate::glyphs::Glyph;
+use crate::output::{Output, StdoutOutput};
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Global color switch (cleared by `--no-color` or `NO_COLOR`)
+static COLOR_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Enable or disable colored notices
+pub fn set_color_enabled(enabled: bool) {
+    COLOR_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Check whether notices are rendered with color
+pub fn color_enabled() -> bool {
+    COLOR_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Severity of a user-facing notice
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Warn,
+    Error,
+    Success,
+}
+
+impl Level {
+    /// Icon shown before the message (follows the glyph mode)
+    pub fn icon(self) -> &'static str {
+        match self {
+            Level::Info => Glyph::Info,
+            Level::Warn => Glyph::Warn,
+            Level::Error => Glyph::Error,
+            Level::Success => Glyph::Success,
+        }
+        .get()
+    }
+
+    /// ANSI color used for the message
+    pub fn color(self) -> &'static str {
+        match self {
+            Level::Info => colors::CYAN,
+            Level::Warn => colors::YELLOW,
+            Level::Error => colors::RED,
+            Level::Success => colors::GREEN,
+        }
+    }
+}
+
+/// Render a notice to a string without printing it
+pub fn format_notice(level: Level, message: &str, color: bool) -> String {
+    if color {
+        format!("{}{} {}{}", level.color(), level.icon(), message, colors::RESET)
+    } else {
+        format!("{} {}", level.icon(), message)
+    }
+}
+
+/// Write a notice at the given level to `out`
+pub fn write_notice(out: &mut dyn Output, level: Level, message: impl Display) {
+    writeln!(out, "{}", format_notice(level, &message.to_string(), color_enabled()));
+}
+
+/// Print a notice at the given level
+pub fn notice(level: Level, message: impl Display) {
+    write_notice(&mut StdoutOutput, level, message);
+}
+
+/// Print an informational notice
+pub fn info(message: impl Display) {
+    notice(Level::Info, message);
+}
+
+/// Print a warning notice
+pub fn warn(message: impl Display) {
+    notice(Level::Warn, message);
+}
+
+/// Print an error notice
+pub fn error(message: impl Display) {
+    notice(Level::Error, message);
+}
+
+/// Print a success notice
+pub fn success(message: impl Display) {
+    notice(Level::Success, message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_prefixes() {
+        assert!(format_notice(Level::Info, "hi", false).starts_with("ℹ️ "));
+        assert!(format_notice(Level::Warn, "hi", false).starts_with("⚠️ "));
+        assert!(format_notice(Level::Error, "hi", false).starts_with("❌"));
+        assert!(format_notice(Level::Success, "hi", false).starts_with("✅"));
+    }
+
+    #[test]
+    fn test_colored_notice() {
+        let rendered = format_notice(Level::Error, "boom", true);
+        assert!(rendered.starts_with(colors::RED));
+        assert!(rendered.ends_with(colors::RESET));
+        assert!(rendered.contains("boom"));
+
+        let rendered = format_notice(Level::Success, "done", true);
+        assert!(rendered.starts_with(colors::GREEN));
+    }
+
+    #[test]
+    fn test_color_omitted_when_disabled() {
+        for level in [Level::Info, Level::Warn, Level::Error, Level::Success] {
+            let rendered = format_notice(level, "plain", false);
+            assert!(!rendered.contains('\x1b'), "{:?} notice contained escape codes", level);
+            assert!(rendered.ends_with("plain"));
+        }
+    }
+}
@@ -0,0 +1,86 @@
+// Output - Destination for rendered UI text
+//
+// Display functions write through an `Output` instead of calling `println!`
+// directly, so tests can render into a buffer and assert the exact bytes.
+// `write!`/`writeln!` work on any `Output` via the provided `write_fmt`.
+
+use std::fmt;
+use std::io::{self, Write};
+
+/// Sink for rendered text
+pub trait Output {
+    /// Write a chunk of text as-is
+    fn write_str(&mut self, text: &str);
+
+    /// Write formatted text (enables `write!`/`writeln!`)
+    fn write_fmt(&mut self, args: fmt::Arguments<'_>) {
+        match args.as_str() {
+            Some(text) => self.write_str(text),
+            None => self.write_str(&args.to_string()),
+        }
+    }
+
+    /// Make buffered output visible immediately (no-op by default)
+    fn flush(&mut self) {}
+}
+
+/// Writes to the process stdout (the real terminal)
+pub struct StdoutOutput;
+
+impl Output for StdoutOutput {
+    fn write_str(&mut self, text: &str) {
+        // Match println! semantics: rendering errors are not recoverable here
+        let _ = io::stdout().write_all(text.as_bytes());
+    }
+
+    fn flush(&mut self) {
+        let _ = io::stdout().flush();
+    }
+}
+
+/// Collects output in memory, for tests
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct BufferOutput {
+    buf: Vec<u8>,
+}
+
+#[cfg(test)]
+impl BufferOutput {
+    /// Create an empty buffer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Raw bytes written so far
+    pub fn bytes(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Written output as a string (lossy for invalid UTF-8)
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.buf).into_owned()
+    }
+}
+
+#[cfg(test)]
+impl Output for BufferOutput {
+    fn write_str(&mut self, text: &str) {
+        self.buf.extend_from_slice(text.as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_collects_writes() {
+        let mut out = BufferOutput::new();
+        write!(out, "a{}", 1);
+        writeln!(out, " b");
+        writeln!(out);
+        assert_eq!(out.contents(), "a1 b\n\n");
+        assert_eq!(out.bytes(), b"a1 b\n\n");
+    }
+}
@@ -0,0 +1,214 @@
+// Palette - Fuzzy command launcher (Ctrl+P)
+//
+// Lists the slash commands from `commands::COMMANDS`, filtered as you type
+// with a small subsequence scorer. Up/Down move the selection, Enter puts the
+// chosen command in the input buffer and Esc closes the palette unchanged.
+
+use crate::ansi;
+use crate::commands::{CommandSpec, COMMANDS};
+use crate::output::{Output, StdoutOutput};
+use anyhow::Result;
+use crossterm::{
+    cursor, event::{self, Event, KeyCode, KeyModifiers},
+    execute, terminal::{self, ClearType},
+};
+use std::io;
+
+/// Entries shown at once
+const MAX_VISIBLE: usize = 8;
+
+/// Score how well `query` fuzzy-matches `candidate` (higher is better)
+///
+/// Every query character must appear in order (case-insensitive). Matches at
+/// the start of the candidate or of a word, and runs of consecutive matches,
+/// score extra. Returns `None` when `query` is not a subsequence.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<u32> {
+    let candidate: Vec<char> = candidate.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut prev_match: Option<usize> = None;
+
+    for q in query.chars().flat_map(char::to_lowercase) {
+        let idx = pos + candidate[pos..].iter().position(|&c| c == q)?;
+        score += 1;
+        if idx == 0 {
+            score += 8;
+        } else if !candidate[idx - 1].is_alphanumeric() {
+            score += 4;
+        }
+        if prev_match.is_some_and(|prev| prev + 1 == idx) {
+            score += 5;
+        }
+        prev_match = Some(idx);
+        pos = idx + 1;
+    }
+    Some(score)
+}
+
+/// Commands matching `query`, best first (ties keep registry order)
+///
+/// A leading `/` in the query is ignored.
+pub fn filter<'a>(query: &str, commands: &'a [CommandSpec]) -> Vec<&'a CommandSpec> {
+    let query = query.strip_prefix('/').unwrap_or(query);
+    let mut scored: Vec<(u32, &CommandSpec)> = commands
+        .iter()
+        .filter_map(|spec| fuzzy_score(query, spec.name).map(|score| (score, spec)))
+        .collect();
+    scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+    scored.into_iter().map(|(_, spec)| spec).collect()
+}
+
+/// Result of a key press in the palette
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteAction {
+    /// Keep the palette open (and redraw)
+    Continue,
+    /// Close without choosing anything
+    Close,
+    /// Close and put this text in the input buffer
+    Insert(String),
+}
+
+/// Palette state: the filter text and the selected entry
+#[derive(Debug, Default)]
+pub struct Palette {
+    query: String,
+    selected: usize,
+}
+
+impl Palette {
+    /// Commands matching the current filter
+    pub fn items(&self) -> Vec<&'static CommandSpec> {
+        filter(&self.query, COMMANDS)
+    }
+
+    /// Update the palette for a key press
+    pub fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> PaletteAction {
+        match (code, modifiers) {
+            (KeyCode::Esc, _) | (KeyCode::Char('c'), KeyModifiers::CONTROL) => PaletteAction::Close,
+            (KeyCode::Enter, _) => match self.items().get(self.selected) {
+                Some(spec) => PaletteAction::Insert(spec.insert_text()),
+                None => PaletteAction::Close,
+            },
+            (KeyCode::Up, _) | (KeyCode::Char('p'), KeyModifiers::CONTROL) => {
+                self.selected = self.selected.saturating_sub(1);
+                PaletteAction::Continue
+            }
+            (KeyCode::Down, _) | (KeyCode::Char('n'), KeyModifiers::CONTROL) => {
+                let last = self.items().len().saturating_sub(1);
+                self.selected = (self.selected + 1).min(last);
+                PaletteAction::Continue
+            }
+            (KeyCode::Backspace, _) => {
+                self.query.pop();
+                self.selected = 0;
+                PaletteAction::Continue
+            }
+            (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                self.query.push(c);
+                self.selected = 0;
+                PaletteAction::Continue
+            }
+            _ => PaletteAction::Continue,
+        }
+    }
+
+    /// Render the filter line and up to `MAX_VISIBLE` entries
+    fn render(&self, out: &mut dyn Output) {
+        write!(out, "{}palette>{} {}\r\n", ansi::colors::CYAN, ansi::colors::RESET, self.query);
+        let items = self.items();
+        if items.is_empty() {
+            write!(out, "  {}no matching commands{}\r\n", ansi::colors::DIM, ansi::colors::RESET);
+        }
+        // Scroll the window so the selection stays visible
+        let first = self.selected.saturating_sub(MAX_VISIBLE - 1);
+        for (idx, spec) in items.iter().enumerate().skip(first).take(MAX_VISIBLE) {
+            let marker = if idx == self.selected { "›" } else { " " };
+            let name = if idx == self.selected {
+                format!("{}{}/{}{}", ansi::colors::BOLD, ansi::colors::GREEN, spec.name, ansi::colors::RESET)
+            } else {
+                format!("{}/{}{}", ansi::colors::GREEN, spec.name, ansi::colors::RESET)
+            };
+            write!(out, "{} {} {}{}{}\r\n", marker, name, ansi::colors::DIM, spec.description, ansi::colors::RESET);
+        }
+        out.flush();
+    }
+}
+
+/// Show the palette below the cursor until Enter or Esc
+///
+/// Returns the text to insert, if a command was chosen.
+pub fn run() -> Result<Option<String>> {
+    let mut palette = Palette::default();
+
+    // Reserve the rows up front so redraws don't scroll the screen
+    let rows = MAX_VISIBLE + 1;
+    print!("{}", "\r\n".repeat(rows));
+    let top = cursor::position()?.1.saturating_sub(rows as u16);
+
+    let result = loop {
+        execute!(io::stdout(), cursor::MoveTo(0, top), terminal::Clear(ClearType::FromCursorDown))?;
+        palette.render(&mut StdoutOutput);
+
+        let Event::Key(key_event) = event::read()? else {
+            continue;
+        };
+        match palette.handle_key(key_event.code, key_event.modifiers) {
+            PaletteAction::Continue => {}
+            PaletteAction::Close => break None,
+            PaletteAction::Insert(text) => break Some(text),
+        }
+    };
+
+    execute!(io::stdout(), cursor::MoveTo(0, top), terminal::Clear(ClearType::FromCursorDown))?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score_ranking() {
+        assert_eq!(fuzzy_score("", "list"), Some(0));
+        assert!(fuzzy_score("xyz", "list").is_none());
+        // Characters must appear in order
+        assert!(fuzzy_score("tsil", "list").is_none());
+
+        // Prefix beats a scattered match, consecutive beats gapped
+        assert!(fuzzy_score("hi", "history") > fuzzy_score("hi", "this"));
+        assert!(fuzzy_score("ex", "exec") > fuzzy_score("ex", "e_x"));
+        assert_eq!(fuzzy_score("HIS", "history"), fuzzy_score("his", "history"));
+    }
+
+    #[test]
+    fn test_filter_by_query() {
+        let names = |query: &str| filter(query, COMMANDS).iter().map(|c| c.name).collect::<Vec<_>>();
+
+        assert_eq!(names("").len(), COMMANDS.len());
+        assert_eq!(names("res"), ["resume"]);
+        assert_eq!(names("/res"), ["resume"]);
+        let ex = names("ex");
+        assert_eq!(&ex[..3], ["export", "exec", "exit"]);
+        assert!(names("zzz").is_empty());
+    }
+
+    #[test]
+    fn test_palette_keys() {
+        let mut palette = Palette::default();
+        for c in "ex".chars() {
+            assert_eq!(palette.handle_key(KeyCode::Char(c), KeyModifiers::NONE), PaletteAction::Continue);
+        }
+        palette.handle_key(KeyCode::Down, KeyModifiers::NONE);
+        assert_eq!(palette.handle_key(KeyCode::Enter, KeyModifiers::NONE), PaletteAction::Insert("/exec ".to_string()));
+
+        // Typing resets the selection; Esc closes without inserting
+        palette.handle_key(KeyCode::Char('i'), KeyModifiers::NONE);
+        assert_eq!(palette.selected, 0);
+        assert_eq!(palette.handle_key(KeyCode::Esc, KeyModifiers::NONE), PaletteAction::Close);
+
+        // Enter with nothing matching just closes
+        let mut palette = Palette { query: "zzz".to_string(), selected: 0 };
+        assert_eq!(palette.handle_key(KeyCode::Enter, KeyModifiers::NONE), PaletteAction::Close);
+    }
+}
@@ -0,0 +1,243 @@
+// Interactive prompts - Blocking user input kept off the async runtime
+//
+// Reading stdin blocks the calling thread, so prompts that run inside the
+// tokio runtime hand the read to the blocking thread pool instead of stalling
+// the reactor.
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::terminal;
+use crate::output::{Output, StdoutOutput};
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+/// Interpret a line of input as a y/N answer (anything but "y" means no)
+pub fn parse_yes_no(input: &str) -> bool {
+    input.trim().eq_ignore_ascii_case("y")
+}
+
+/// Read a single y/N answer from `reader` on the blocking thread pool
+pub async fn read_yes_no<R>(mut reader: R) -> io::Result<bool>
+where
+    R: BufRead + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || {
+        let mut input = String::new();
+        reader.read_line(&mut input)?;
+        Ok(parse_yes_no(&input))
+    })
+    .await
+    .map_err(io::Error::other)?
+}
+
+/// Read a y/N answer from stdin without blocking the async runtime
+pub async fn read_yes_no_stdin() -> io::Result<bool> {
+    read_yes_no(io::BufReader::new(io::stdin())).await
+}
+
+/// A credential typed by the user
+///
+/// Redacted in `Debug` output and overwritten when dropped, so it never ends
+/// up in logs and doesn't linger in memory longer than needed.
+pub struct Secret(String);
+
+impl Secret {
+    /// Access the secret value (avoid logging or storing the result)
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(<redacted>)")
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        let mut bytes = std::mem::take(&mut self.0).into_bytes();
+        bytes.fill(0);
+        // Keep the wipe from being optimized away as a dead store
+        std::hint::black_box(&bytes);
+    }
+}
+
+/// Read key events into `buf` until Enter (true) or Esc/Ctrl+C (false)
+///
+/// Typed characters are echoed to `echo` when given; secrets pass `None`.
+fn collect_input<F>(mut next_key: F, buf: &mut String, mut echo: Option<&mut dyn Output>) -> io::Result<bool>
+where
+    F: FnMut() -> io::Result<KeyEvent>,
+{
+    loop {
+        let key = next_key()?;
+        match (key.code, key.modifiers) {
+            (KeyCode::Enter, _) => return Ok(true),
+            (KeyCode::Esc, _) => return Ok(false),
+            (KeyCode::Char('c'), m) if m.contains(KeyModifiers::CONTROL) => return Ok(false),
+            (KeyCode::Backspace, _) if !buf.is_empty() => {
+                buf.pop();
+                if let Some(out) = echo.as_deref_mut() {
+                    out.write_str("\x08 \x08");
+                    out.flush();
+                }
+            }
+            (KeyCode::Char(c), m) if !m.contains(KeyModifiers::CONTROL) => {
+                buf.push(c);
+                if let Some(out) = echo.as_deref_mut() {
+                    write!(out, "{}", c);
+                    out.flush();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Assemble a secret from key events without echoing anything
+///
+/// Returns `None` if the user cancels with Esc or Ctrl+C.
+pub fn collect_secret<F>(next_key: F) -> io::Result<Option<Secret>>
+where
+    F: FnMut() -> io::Result<KeyEvent>,
+{
+    // Reserve up front so typing doesn't leave reallocated copies behind
+    let mut secret = Secret(String::with_capacity(256));
+    let submitted = collect_input(next_key, &mut secret.0, None)?;
+    Ok(submitted.then_some(secret))
+}
+
+/// Assemble a line from key events, echoing it to `out`
+///
+/// Returns `None` if the user cancels with Esc or Ctrl+C.
+pub fn collect_line<F>(next_key: F, out: &mut dyn Output) -> io::Result<Option<String>>
+where
+    F: FnMut() -> io::Result<KeyEvent>,
+{
+    let mut line = String::new();
+    let submitted = collect_input(next_key, &mut line, Some(out))?;
+    Ok(submitted.then_some(line))
+}
+
+/// Next key event from the terminal (blocking)
+fn next_terminal_key() -> io::Result<KeyEvent> {
+    loop {
+        if let Event::Key(key) = event::read()? {
+            return Ok(key);
+        }
+    }
+}
+
+/// Run `read` in raw mode on the blocking pool, restoring the caller's mode
+async fn read_raw<T, F>(prompt: &str, read: F) -> io::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> io::Result<T> + Send + 'static,
+{
+    print!("{}", prompt);
+    io::stdout().flush()?;
+
+    // Raw mode disables echo; keep whatever mode the caller was in afterwards
+    let was_raw = terminal::is_raw_mode_enabled()?;
+    if !was_raw {
+        terminal::enable_raw_mode()?;
+    }
+
+    let result = tokio::task::spawn_blocking(read)
+        .await
+        .map_err(io::Error::other);
+
+    if !was_raw {
+        terminal::disable_raw_mode()?;
+    }
+    println!();
+    result?
+}
+
+/// Prompt for a secret on the terminal with echo suppressed
+///
+/// The value is read directly from key events, so it never passes through the
+/// line editor or its history.
+pub async fn read_secret(prompt: &str) -> io::Result<Option<Secret>> {
+    read_raw(prompt, || collect_secret(next_terminal_key)).await
+}
+
+/// Prompt for a line of visible input while the terminal is in raw mode
+///
+/// Line-buffered stdin reads don't work in raw mode (Enter sends `\r`), so
+/// this reads key events instead. `None` means the user cancelled.
+pub async fn read_line_raw(prompt: &str) -> io::Result<Option<String>> {
+    read_raw(prompt, || collect_line(next_terminal_key, &mut StdoutOutput)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn keys(events: Vec<KeyEvent>) -> impl FnMut() -> io::Result<KeyEvent> {
+        let mut events = events.into_iter();
+        move || events.next().ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_collect_secret_from_injected_keys() {
+        let events = vec![
+            key(KeyCode::Char('s')),
+            key(KeyCode::Char('k')),
+            key(KeyCode::Char('x')),
+            key(KeyCode::Backspace),
+            KeyEvent::new(KeyCode::Char('-'), KeyModifiers::SHIFT),
+            key(KeyCode::Char('1')),
+            key(KeyCode::Enter),
+        ];
+        let secret = collect_secret(keys(events)).unwrap().unwrap();
+        assert_eq!(secret.expose(), "sk-1");
+        assert_eq!(format!("{:?}", secret), "Secret(<redacted>)");
+    }
+
+    #[test]
+    fn test_collect_line_echoes() {
+        let events = vec![
+            key(KeyCode::Char('o')),
+            key(KeyCode::Char('k')),
+            key(KeyCode::Char('x')),
+            key(KeyCode::Backspace),
+            key(KeyCode::Enter),
+        ];
+        let mut out = crate::output::BufferOutput::new();
+        let line = collect_line(keys(events), &mut out).unwrap();
+        assert_eq!(line.as_deref(), Some("ok"));
+        assert_eq!(out.contents(), "okx\x08 \x08");
+    }
+
+    #[test]
+    fn test_collect_secret_cancel() {
+        let events = vec![key(KeyCode::Char('a')), key(KeyCode::Esc)];
+        assert!(collect_secret(keys(events)).unwrap().is_none());
+
+        let events = vec![KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)];
+        assert!(collect_secret(keys(events)).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_parse_yes_no() {
+        assert!(parse_yes_no("y"));
+        assert!(parse_yes_no("Y\n"));
+        assert!(!parse_yes_no("n"));
+        assert!(!parse_yes_no(""));
+        assert!(!parse_yes_no("yes please"));
+    }
+
+    #[tokio::test]
+    async fn test_read_yes_no_from_injected_input() {
+        assert!(read_yes_no(Cursor::new("y\n")).await.unwrap());
+        assert!(!read_yes_no(Cursor::new("N\n")).await.unwrap());
+        // EOF without input is treated as "no"
+        assert!(!read_yes_no(Cursor::new("")).await.unwrap());
+    }
+}
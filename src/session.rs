@@ -3,12 +3,18 @@
 // This module provides session management functionality on the Rust frontend,
 // coordinating with the Python backend's SessionManager via IPC messages.
 
+use crate::ansi;
+use crate::clock::{Clock, SystemClock};
+use crate::config::LoadMode;
+use crate::ipc::client::METHOD_NOT_FOUND;
+use crate::ipc::message::ContextMessage;
 use crate::ipc::{IpcClient, IpcError, Request};
+use crate::tokenizer::{HeuristicTokenizer, Tokenizer};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
@@ -25,16 +31,27 @@ pub enum MessageRole {
     System,
 }
 
+impl MessageRole {
+    /// Wire name of the role (matches the serde form)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageRole::User => "user",
+            MessageRole::Assistant => "assistant",
+            MessageRole::System => "system",
+        }
+    }
+}
+
 /// A single message in a session
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
     pub role: MessageRole,
     pub content: String,
     pub timestamp: DateTime<Utc>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub token_count: Option<usize>,
+    /// Sorted so serialized messages (e.g. JSON exports) are deterministic
     #[serde(default)]
-    pub metadata: HashMap<String, String>,
+    pub metadata: BTreeMap<String, String>,
 }
 
 /// Session metadata summary
@@ -46,6 +63,9 @@ pub struct SessionMetadata {
     pub updated_at: DateTime<Utc>,
     pub message_count: usize,
     pub total_tokens: usize,
+    /// Pinned sessions are listed first and shown by `/fav`
+    #[serde(default)]
+    pub pinned: bool,
 }
 
 /// Full session with messages
@@ -55,26 +75,255 @@ pub struct Session {
     pub messages: Vec<Message>,
 }
 
+/// Format name of the client-built JSON export
+pub const JSON_EXPORT_FORMAT: &str = "json";
+
+/// A `session.search` result: the session and the text that matched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSearchHit {
+    #[serde(flatten)]
+    pub metadata: SessionMetadata,
+    /// Matching excerpt, absent for title-only matches
+    #[serde(default)]
+    pub snippet: Option<String>,
+}
+
+/// First question and last answer of a session, each cut to one line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionPreview {
+    pub first_user: Option<String>,
+    pub last_assistant: Option<String>,
+}
+
+impl Session {
+    /// Pretty-printed JSON of the metadata and every message
+    ///
+    /// Messages always carry `role`, `content`, `timestamp` (RFC 3339),
+    /// `token_count` (null when unknown) and `metadata`.
+    pub fn to_json(&self) -> Result<String, IpcError> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| IpcError::ParseError(format!("Failed to serialize session: {}", e)))
+    }
+
+    /// Preview of the conversation with each line at most `max_cols` wide
+    ///
+    /// Whitespace (including newlines) is collapsed so every snippet is one line.
+    pub fn preview(&self, max_cols: usize) -> SessionPreview {
+        let snippet = |m: &Message| {
+            let flat = m.content.split_whitespace().collect::<Vec<_>>().join(" ");
+            ansi::truncate_display(&flat, max_cols)
+        };
+        SessionPreview {
+            first_user: self.messages.iter().find(|m| m.role == MessageRole::User).map(snippet),
+            last_assistant: self.messages.iter().rev().find(|m| m.role == MessageRole::Assistant).map(snippet),
+        }
+    }
+}
+
+/// Exported session content plus what the backend reported about it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportResult {
+    pub content: String,
+    /// Format actually used (may differ from the one requested)
+    pub format: String,
+    pub byte_count: usize,
+    /// Number of exported messages, when the backend reports it
+    pub message_count: Option<usize>,
+}
+
+impl ExportResult {
+    /// Parse a `session.export` result; missing metadata is derived from the content
+    pub fn from_result(result: &serde_json::Value, requested_format: &str) -> Result<Self, IpcError> {
+        let Some(content) = result.get("content").and_then(|v| v.as_str()) else {
+            let reason = result.get("error").and_then(|v| v.as_str()).unwrap_or("missing content field");
+            return Err(IpcError::ParseError(format!("Export failed: {}", reason)));
+        };
+        let format = result.get("format").and_then(|v| v.as_str()).unwrap_or(requested_format);
+        let byte_count = result.get("byte_count").and_then(|v| v.as_u64());
+        let message_count = result.get("message_count").and_then(|v| v.as_u64());
+
+        Ok(Self {
+            content: content.to_string(),
+            format: format.to_string(),
+            byte_count: byte_count.map_or(content.len(), |n| n as usize),
+            message_count: message_count.map(|n| n as usize),
+        })
+    }
+
+    /// Export `session` as JSON (see `Session::to_json`)
+    pub fn from_session(session: &Session) -> Result<Self, IpcError> {
+        let content = session.to_json()?;
+        Ok(Self {
+            byte_count: content.len(),
+            format: JSON_EXPORT_FORMAT.to_string(),
+            message_count: Some(session.messages.len()),
+            content,
+        })
+    }
+
+    /// Just the exported text
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// One-line description, e.g. "Exported 42 messages (18 KB) as markdown"
+    pub fn summary(&self) -> String {
+        let size = format_size(self.byte_count);
+        match self.message_count {
+            Some(1) => format!("Exported 1 message ({}) as {}", size, self.format),
+            Some(n) => format!("Exported {} messages ({}) as {}", n, size, self.format),
+            None => format!("Exported {} as {}", size, self.format),
+        }
+    }
+}
+
+/// Human-readable byte size (B below 1 KB, then KB/MB rounded)
+fn format_size(bytes: usize) -> String {
+    const KB: usize = 1024;
+    const MB: usize = 1024 * KB;
+    match bytes {
+        b if b < KB => format!("{} B", b),
+        b if b < MB => format!("{} KB", (b + KB / 2) / KB),
+        b => format!("{:.1} MB", b as f64 / MB as f64),
+    }
+}
+
+/// Metadata key marking a token count as a tokenizer estimate
+pub const TOKEN_ESTIMATE_KEY: &str = "token_count_estimated";
+
+/// Client-side record of the current conversation, with local token counts
+pub struct Transcript {
+    /// Clean source text, used for copy, export and backend context
+    messages: Vec<Message>,
+    tokenizer: Arc<dyn Tokenizer>,
+}
+
+impl Transcript {
+    /// Create an empty transcript counting tokens with `tokenizer`
+    pub fn new(tokenizer: Arc<dyn Tokenizer>) -> Self {
+        Self { messages: Vec::new(), tokenizer }
+    }
+
+    /// Record a user message
+    pub fn push_user(&mut self, content: &str, timestamp: DateTime<Utc>) -> &Message {
+        self.push(MessageRole::User, content, timestamp)
+    }
+
+    /// Record an assistant response
+    pub fn push_assistant(&mut self, content: &str, timestamp: DateTime<Utc>) -> &Message {
+        self.push(MessageRole::Assistant, content, timestamp)
+    }
+
+    fn push(&mut self, role: MessageRole, content: &str, timestamp: DateTime<Utc>) -> &Message {
+        let mut message = Message {
+            role,
+            // Display colors must never reach stored or exported text
+            content: ansi::strip_ansi(content),
+            timestamp,
+            token_count: None,
+            metadata: BTreeMap::new(),
+        };
+        self.count_tokens(&mut message);
+        self.messages.push(message);
+        self.messages.last().unwrap()
+    }
+
+    /// Replace the transcript with loaded messages, counting any without a token count
+    pub fn replace(&mut self, messages: Vec<Message>) {
+        self.messages.clear();
+        self.append(messages);
+    }
+
+    /// Add loaded messages after the current ones, counting any without a token count
+    pub fn append(&mut self, mut messages: Vec<Message>) {
+        for message in messages.iter_mut().filter(|m| m.token_count.is_none()) {
+            self.count_tokens(message);
+        }
+        self.messages.extend(messages);
+    }
+
+    fn count_tokens(&self, message: &mut Message) {
+        message.token_count = Some(self.tokenizer.count(&message.content));
+        if self.tokenizer.is_estimate() {
+            message.metadata.insert(TOKEN_ESTIMATE_KEY.to_string(), "true".to_string());
+        }
+    }
+
+    /// Messages recorded so far, oldest first
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    /// Running token total across all messages
+    pub fn total_tokens(&self) -> usize {
+        self.messages.iter().filter_map(|m| m.token_count).sum()
+    }
+
+    /// Whether any count in the total is a tokenizer estimate
+    pub fn is_estimate(&self) -> bool {
+        self.messages.iter().any(|m| m.metadata.contains_key(TOKEN_ESTIMATE_KEY))
+    }
+}
+
 /// Session manager client - handles session operations via IPC
 pub struct SessionManager {
     ipc_client: Arc<Mutex<IpcClient>>,
     current_session_id: Option<String>,
     sessions_cache: HashMap<String, SessionMetadata>,
     request_counter: u64,
+    clock: Arc<dyn Clock>,
+    transcript: Transcript,
+    /// A session was activated and its history hasn't been sent to the agent yet
+    pending_context: bool,
+    /// ID of the last completed turn, sent as the parent of a follow-up query
+    last_turn_id: Option<String>,
+    /// The last response, if it was cancelled or cut off and `/continue` can resume it
+    interrupted: Option<Interrupted>,
+    /// Sessions newest first, as of the first quick switch (kept stable while cycling)
+    recent_sessions: Vec<SessionMetadata>,
+    /// Position in `recent_sessions` of the session last switched to
+    recent_cursor: Option<usize>,
 }
 
 impl SessionManager {
     /// Create a new session manager with IPC client
     pub fn new(ipc_client: Arc<Mutex<IpcClient>>) -> Self {
+        Self::with_clock(ipc_client, Arc::new(SystemClock))
+    }
+
+    /// Create a session manager with a custom clock (for deterministic tests)
+    pub fn with_clock(ipc_client: Arc<Mutex<IpcClient>>, clock: Arc<dyn Clock>) -> Self {
         info!("📝 Session manager created with IPC client");
         Self {
             ipc_client,
             current_session_id: None,
             sessions_cache: HashMap::new(),
             request_counter: SESSION_MANAGER_ID_MIN - 1, // Start at 9999 so first ID is 10000
+            clock,
+            transcript: Transcript::new(Arc::new(HeuristicTokenizer)),
+            pending_context: false,
+            last_turn_id: None,
+            interrupted: None,
+            recent_sessions: Vec::new(),
+            recent_cursor: None,
         }
     }
 
+    /// Conversation recorded on the client side
+    pub fn transcript(&self) -> &Transcript {
+        &self.transcript
+    }
+
+    /// Mutable access for recording new messages
+    pub fn transcript_mut(&mut self) -> &mut Transcript {
+        &mut self.transcript
+    }
+
+    /// Current time according to the manager's clock
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
     /// Get next request ID for IPC calls (SessionManager uses IDs >= 10000)
     fn next_request_id(&mut self) -> u64 {
         self.request_counter += 1;
@@ -94,14 +343,22 @@ impl SessionManager {
     pub async fn list_sessions(&mut self, limit: Option<usize>) -> Result<Vec<SessionMetadata>, IpcError> {
         debug!("📋 Listing sessions (limit: {:?})", limit);
 
-        let request_id = self.next_request_id();
-
         let params = if let Some(limit) = limit {
             serde_json::json!({ "limit": limit })
         } else {
             serde_json::json!({})
         };
+        self.request_session_list(params).await
+    }
 
+    /// List the `limit` most recently updated sessions, ignoring pins
+    pub async fn list_recent_sessions(&mut self, limit: usize) -> Result<Vec<SessionMetadata>, IpcError> {
+        debug!("📋 Listing {} most recent sessions", limit);
+        self.request_session_list(serde_json::json!({ "limit": limit, "sort": "updated_at" })).await
+    }
+
+    async fn request_session_list(&mut self, params: serde_json::Value) -> Result<Vec<SessionMetadata>, IpcError> {
+        let request_id = self.next_request_id();
         let request = Request::new(request_id, "session.list", Some(params));
         let response = {
             let mut client = self.ipc_client.lock().await;
@@ -130,10 +387,74 @@ impl SessionManager {
         Ok(sessions)
     }
 
+    /// Search sessions by title and content via `session.search`
+    ///
+    /// Cached sessions whose title contains `query` ignoring case are added
+    /// after the backend's hits, so a case-sensitive backend still finds them.
+    /// Without `session.search` on the backend, only titles are matched.
+    pub async fn search_sessions(&mut self, query: &str, limit: Option<usize>) -> Result<Vec<SessionSearchHit>, IpcError> {
+        debug!("🔍 Searching sessions for {:?} (limit: {:?})", query, limit);
+
+        let request_id = self.next_request_id();
+
+        let mut params = serde_json::json!({ "query": query });
+        if let Some(limit) = limit {
+            params["limit"] = limit.into();
+        }
+
+        let request = Request::new(request_id, "session.search", Some(params));
+        let response = {
+            let mut client = self.ipc_client.lock().await;
+            client.send_request(request).await?
+        };
+
+        let mut hits = match response.error {
+            Some(error) if error.code == METHOD_NOT_FOUND => {
+                debug!("Backend does not support session.search; matching titles locally");
+                self.list_sessions(None).await?;
+                Vec::new()
+            }
+            Some(error) => return Err(IpcError::RpcError { code: error.code, message: error.message }),
+            None => {
+                let result = response.result
+                    .ok_or_else(|| IpcError::ParseError("No result in response".to_string()))?;
+
+                let sessions_data = result.get("sessions")
+                    .ok_or_else(|| IpcError::ParseError("No 'sessions' field".to_string()))?;
+
+                let hits: Vec<SessionSearchHit> = serde_json::from_value(sessions_data.clone())
+                    .map_err(|e| IpcError::ParseError(format!("Failed to parse search results: {}", e)))?;
+
+                // Update cache
+                for hit in &hits {
+                    self.sessions_cache.insert(hit.metadata.session_id.clone(), hit.metadata.clone());
+                }
+                hits
+            }
+        };
+
+        add_title_matches(&mut hits, self.sessions_cache.values(), query, limit);
+
+        info!("🔍 {} sessions match {:?}", hits.len(), query);
+        Ok(hits)
+    }
+
     /// Load a specific session from the backend
+    ///
+    /// Makes it the current session but leaves the transcript alone; use
+    /// `load_and_activate` to continue the conversation from it.
     pub async fn load_session(&mut self, session_id: &str) -> Result<Session, IpcError> {
         info!("📂 Loading session: {}", session_id);
 
+        let session = self.fetch_session(session_id).await?;
+        self.current_session_id = Some(session.metadata.session_id.clone());
+
+        info!("📂 Loaded session with {} messages", session.messages.len());
+        Ok(session)
+    }
+
+    /// Fetch a session's messages via `session.load`, with cached or derived metadata
+    async fn fetch_session(&mut self, session_id: &str) -> Result<Session, IpcError> {
         let request_id = self.next_request_id();
 
         let params = serde_json::json!({ "session_id": session_id });
@@ -163,29 +484,133 @@ impl SessionManager {
             .map_err(|e| IpcError::ParseError(format!("Failed to parse messages: {}", e)))?;
 
         // Get or create metadata
-        let metadata = if let Some(cached) = self.sessions_cache.get(&session_id_str) {
-            cached.clone()
-        } else {
-            // Create basic metadata from the loaded data
-            SessionMetadata {
-                session_id: session_id_str.clone(),
-                title: format!("Session {}", &session_id_str[..8]),
-                created_at: messages.first().map(|m| m.timestamp).unwrap_or_else(Utc::now),
-                updated_at: messages.last().map(|m| m.timestamp).unwrap_or_else(Utc::now),
-                message_count: messages.len(),
-                total_tokens: messages.iter().filter_map(|m| m.token_count).sum(),
-            }
+        let metadata = match self.sessions_cache.get(&session_id_str) {
+            Some(cached) => cached.clone(),
+            None => self.fallback_metadata(&session_id_str, &messages),
         };
 
-        self.current_session_id = Some(session_id_str.clone());
+        Ok(Session { metadata, messages })
+    }
 
-        let session = Session { metadata, messages };
-        info!("📂 Loaded session with {} messages", session.messages.len());
+    /// Load a session and make its messages the context for following queries
+    pub async fn load_and_activate(&mut self, session_id: &str, mode: LoadMode) -> Result<Session, IpcError> {
+        let session = self.load_session(session_id).await?;
+        self.activate(&session.messages, mode);
         Ok(session)
     }
 
-    /// Export a session to markdown format
-    pub async fn export_session(&mut self, session_id: Option<&str>, format: &str) -> Result<String, IpcError> {
+    /// Feed loaded messages into the transcript and queue them as agent context
+    fn activate(&mut self, messages: &[Message], mode: LoadMode) {
+        match mode {
+            LoadMode::Replace => self.transcript.replace(messages.to_vec()),
+            LoadMode::Append => self.transcript.append(messages.to_vec()),
+        }
+        self.pending_context = true;
+        self.last_turn_id = None;
+        self.interrupted = None;
+    }
+
+    /// Remember the ID of a completed turn for threading the next query
+    pub fn record_turn(&mut self, turn_id: &str) {
+        self.last_turn_id = Some(turn_id.to_string());
+    }
+
+    /// Parent for the next query: the last turn completed in this session
+    pub fn parent_turn(&self) -> Option<&str> {
+        self.last_turn_id.as_deref()
+    }
+
+    /// Remember how the last response ended: `Some` if it stopped early, `None` if it finished
+    pub fn set_interrupted(&mut self, interrupted: Option<Interrupted>) {
+        self.interrupted = interrupted;
+    }
+
+    /// The last response, if `/continue` can resume it
+    pub fn interrupted(&self) -> Option<&Interrupted> {
+        self.interrupted.as_ref()
+    }
+
+    /// History to send with the next query after a session was activated
+    ///
+    /// Returns the whole transcript until `activation_context_sent` is called
+    /// (once the backend accepted a query carrying it), and nothing otherwise.
+    pub fn activation_context(&self) -> Vec<ContextMessage> {
+        if !self.pending_context {
+            return Vec::new();
+        }
+        self.transcript
+            .messages()
+            .iter()
+            .map(|m| ContextMessage { role: m.role.as_str().to_string(), content: m.content.clone() })
+            .collect()
+    }
+
+    /// Record that the backend has the activation context, so it isn't sent again
+    pub fn activation_context_sent(&mut self) {
+        self.pending_context = false;
+    }
+
+    /// Load (and activate) the previous or next session in recency order
+    ///
+    /// The order is snapshotted on first use so loading (which may bump a
+    /// session's `updated_at`) doesn't reshuffle it; it is refreshed when the
+    /// current session isn't in it (e.g. one created since). Returns `None`
+    /// when there is no other session to switch to.
+    pub async fn cycle_session(&mut self, direction: SessionCycle, mode: LoadMode) -> Result<Option<Session>, IpcError> {
+        let current = self.current_session_id.clone();
+        let stale = current.as_deref().is_some_and(|id| !self.recent_sessions.iter().any(|s| s.session_id == id));
+        if self.recent_sessions.is_empty() || stale {
+            self.recent_sessions = recency_order(&self.list_sessions(None).await?);
+            self.recent_cursor = None;
+        }
+
+        // Follow the current session if it moved (e.g. loaded with /load)
+        let cursor = current
+            .as_deref()
+            .and_then(|id| self.recent_sessions.iter().position(|s| s.session_id == id))
+            .or(self.recent_cursor);
+        let Some(index) = cycle_index(self.recent_sessions.len(), cursor, direction) else {
+            return Ok(None);
+        };
+        let target = self.recent_sessions[index].session_id.clone();
+        if current.as_deref() == Some(target.as_str()) {
+            return Ok(None);
+        }
+
+        let session = self.load_and_activate(&target, mode).await?;
+        self.recent_cursor = Some(index);
+        Ok(Some(session))
+    }
+
+    /// Load (and activate) the most recently updated session, if there is one
+    pub async fn resume_last(&mut self, mode: LoadMode) -> Result<Option<Session>, IpcError> {
+        // Plain session.list puts pinned sessions first. The backend sorts
+        // newest first when asked, but don't rely on it for the pick
+        let sessions = self.list_recent_sessions(1).await?;
+        let Some(latest) = most_recent(&sessions).map(|s| s.session_id.clone()) else {
+            return Ok(None);
+        };
+        self.load_and_activate(&latest, mode).await.map(Some)
+    }
+
+    /// Build basic metadata from loaded messages when none is cached
+    ///
+    /// Sessions without messages are stamped with the clock's current time.
+    fn fallback_metadata(&self, session_id: &str, messages: &[Message]) -> SessionMetadata {
+        let now = self.clock.now();
+        SessionMetadata {
+            session_id: session_id.to_string(),
+            title: format!("Session {}", &session_id[..8.min(session_id.len())]),
+            created_at: messages.first().map(|m| m.timestamp).unwrap_or(now),
+            updated_at: messages.last().map(|m| m.timestamp).unwrap_or(now),
+            message_count: messages.len(),
+            total_tokens: messages.iter().filter_map(|m| m.token_count).sum(),
+            pinned: false,
+        }
+    }
+
+    /// Export a session via `session.export` (JSON goes through `export_session_structured`)
+    pub async fn export_session(&mut self, session_id: Option<&str>, format: &str) -> Result<ExportResult, IpcError> {
         debug!("📤 Exporting session: {:?} as {}", session_id, format);
 
         let request_id = self.next_request_id();
@@ -212,12 +637,27 @@ impl SessionManager {
         let result = response.result
             .ok_or_else(|| IpcError::ParseError("No result in response".to_string()))?;
 
-        let content = result.get("content")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| IpcError::ParseError("Missing content field".to_string()))?;
+        let export = ExportResult::from_result(&result, format)?;
+        info!("📤 Exported session ({} bytes)", export.byte_count);
+        Ok(export)
+    }
+
+    /// Load a session (current by default) and export it as pretty JSON
+    ///
+    /// Built here rather than by `session.export` so the schema doesn't depend
+    /// on the backend. Unlike `load_session`, this doesn't switch sessions.
+    pub async fn export_session_structured(&mut self, session_id: Option<&str>) -> Result<ExportResult, IpcError> {
+        let target = self.export_target(session_id)?;
+        let export = ExportResult::from_session(&self.fetch_session(&target).await?)?;
+        info!("📤 Exported session as JSON ({} bytes)", export.byte_count);
+        Ok(export)
+    }
 
-        info!("📤 Exported session ({} bytes)", content.len());
-        Ok(content.to_string())
+    /// Session to export: the given one, else the current one
+    fn export_target(&self, session_id: Option<&str>) -> Result<String, IpcError> {
+        session_id.or(self.current_session_id.as_deref())
+            .map(str::to_string)
+            .ok_or_else(|| IpcError::ParseError("No session to export; pass a session ID".to_string()))
     }
 
     /// Delete a session
@@ -249,6 +689,164 @@ impl SessionManager {
         Ok(())
     }
 
+    /// Pin or unpin a session via `session.update`
+    pub async fn set_pinned(&mut self, session_id: &str, pinned: bool) -> Result<(), IpcError> {
+        info!("📌 Setting pinned={} on session: {}", pinned, session_id);
+
+        let request_id = self.next_request_id();
+        let params = serde_json::json!({ "session_id": session_id, "pinned": pinned });
+        let request = Request::new(request_id, "session.update", Some(params));
+        let response = {
+            let mut client = self.ipc_client.lock().await;
+            client.send_request(request).await?
+        };
+
+        if let Some(error) = response.error {
+            return Err(IpcError::RpcError { code: error.code, message: error.message });
+        }
+
+        let result = response.result
+            .ok_or_else(|| IpcError::ParseError("No result in response".to_string()))?;
+
+        if let Some(reason) = result.get("error").and_then(|v| v.as_str()) {
+            return Err(IpcError::ParseError(format!("Update failed: {}", reason)));
+        }
+
+        if let Some(metadata) = self.sessions_cache.get_mut(session_id) {
+            metadata.pinned = pinned;
+        }
+
+        Ok(())
+    }
+
+    /// Retitle a session via `session.rename`
+    pub async fn rename_session(&mut self, session_id: &str, new_title: &str) -> Result<(), IpcError> {
+        info!("✏️  Renaming session {} to {:?}", session_id, new_title);
+
+        let request_id = self.next_request_id();
+        let params = serde_json::json!({ "session_id": session_id, "title": new_title });
+        let request = Request::new(request_id, "session.rename", Some(params));
+        let response = {
+            let mut client = self.ipc_client.lock().await;
+            client.send_request(request).await?
+        };
+
+        if let Some(error) = response.error {
+            return Err(IpcError::RpcError { code: error.code, message: error.message });
+        }
+
+        let result = response.result
+            .ok_or_else(|| IpcError::ParseError("No result in response".to_string()))?;
+
+        if let Some(reason) = result.get("error").and_then(|v| v.as_str()) {
+            return Err(IpcError::ParseError(format!("Rename failed: {}", reason)));
+        }
+
+        if let Some(metadata) = self.sessions_cache.get_mut(session_id) {
+            metadata.title = new_title.to_string();
+        }
+
+        Ok(())
+    }
+
+    /// Start an empty session on the backend (optionally titled) and make it the current one
+    ///
+    /// Leaves the transcript alone; callers starting a new conversation clear it.
+    pub async fn create_session(&mut self, title: Option<&str>) -> Result<SessionMetadata, IpcError> {
+        info!("📝 Creating new session");
+
+        let request_id = self.next_request_id();
+        let params = match title {
+            Some(title) => serde_json::json!({ "title": title }),
+            None => serde_json::json!({}),
+        };
+        let request = Request::new(request_id, "session.new", Some(params));
+        let response = {
+            let mut client = self.ipc_client.lock().await;
+            client.send_request(request).await?
+        };
+
+        if let Some(error) = response.error {
+            return Err(IpcError::RpcError { code: error.code, message: error.message });
+        }
+
+        let result = response.result
+            .ok_or_else(|| IpcError::ParseError("No result in response".to_string()))?;
+        self.activate_new_session(&result, title)
+    }
+
+    /// Record the session created by a `session.new` result as current
+    ///
+    /// The backend's title wins; `requested_title` covers backends that don't echo it.
+    fn activate_new_session(&mut self, result: &serde_json::Value, requested_title: Option<&str>) -> Result<SessionMetadata, IpcError> {
+        let Some(session_id) = result.get("session_id").and_then(|v| v.as_str()) else {
+            let reason = result.get("error").and_then(|v| v.as_str()).unwrap_or("missing session_id");
+            return Err(IpcError::ParseError(format!("Session creation failed: {}", reason)));
+        };
+
+        let mut metadata = self.fallback_metadata(session_id, &[]);
+        if let Some(title) = result.get("title").and_then(|v| v.as_str()).or(requested_title) {
+            metadata.title = title.to_string();
+        }
+
+        self.sessions_cache.insert(session_id.to_string(), metadata.clone());
+        self.current_session_id = Some(session_id.to_string());
+        self.last_turn_id = None;
+        self.interrupted = None;
+        Ok(metadata)
+    }
+
+    /// Copy a session (the current one by default) into a new session and switch to it
+    ///
+    /// Forking another session switches to its messages, as loading it would.
+    pub async fn fork_session(&mut self, session_id: Option<&str>) -> Result<SessionMetadata, IpcError> {
+        let forks_current = forks_current(session_id, self.current_session_id.as_deref());
+        let source = session_id.or(self.current_session_id.as_deref()).map(str::to_string);
+        info!("🍴 Forking session: {:?}", source);
+
+        let request_id = self.next_request_id();
+        let request = Request::new(request_id, "session.fork", Some(fork_params(source.as_deref())));
+        let response = {
+            let mut client = self.ipc_client.lock().await;
+            client.send_request(request).await?
+        };
+
+        if let Some(error) = response.error {
+            return Err(IpcError::RpcError { code: error.code, message: error.message });
+        }
+
+        let result = response.result
+            .ok_or_else(|| IpcError::ParseError("No result in response".to_string()))?;
+
+        let Some(fork_id) = result.get("session_id").and_then(|v| v.as_str()) else {
+            let reason = result.get("error").and_then(|v| v.as_str()).unwrap_or("missing session_id");
+            return Err(IpcError::ParseError(format!("Fork failed: {}", reason)));
+        };
+
+        let fork_id = fork_id.to_string();
+        let mut metadata = if forks_current {
+            // The fork starts with the same messages, so the transcript carries
+            // over; turns and /continue belong to the source session
+            self.last_turn_id = None;
+            self.interrupted = None;
+            self.fallback_metadata(&fork_id, self.transcript.messages())
+        } else {
+            self.load_and_activate(&fork_id, LoadMode::Replace).await?.metadata
+        };
+        if let Some(title) = result.get("title").and_then(|v| v.as_str()) {
+            metadata.title = title.to_string();
+        }
+        if let Some(count) = result.get("message_count").and_then(|v| v.as_u64()) {
+            metadata.message_count = count as usize;
+        }
+
+        self.sessions_cache.insert(fork_id.clone(), metadata.clone());
+        self.current_session_id = Some(fork_id.clone());
+
+        info!("🍴 Switched to forked session: {}", fork_id);
+        Ok(metadata)
+    }
+
     /// Get the current session ID
     pub fn current_session_id(&self) -> Option<&str> {
         self.current_session_id.as_deref()
@@ -266,9 +864,245 @@ impl SessionManager {
     }
 }
 
+/// Whether a session should be created before sending a query
+pub fn should_auto_create(auto_create_session: bool, current_session_id: Option<&str>) -> bool {
+    auto_create_session && current_session_id.is_none()
+}
+
+/// Pick the session with the latest `updated_at`
+pub fn most_recent(sessions: &[SessionMetadata]) -> Option<&SessionMetadata> {
+    sessions.iter().max_by_key(|s| s.updated_at)
+}
+
+/// Append `known` sessions whose title contains `query` (ignoring case) and
+/// that aren't among `hits` yet, most recently updated first
+///
+/// `limit` caps the combined list.
+fn add_title_matches<'a>(
+    hits: &mut Vec<SessionSearchHit>,
+    known: impl IntoIterator<Item = &'a SessionMetadata>,
+    query: &str,
+    limit: Option<usize>,
+) {
+    let needle = query.to_lowercase();
+    let mut extra: Vec<&SessionMetadata> = known.into_iter()
+        .filter(|s| s.title.to_lowercase().contains(&needle))
+        .filter(|s| !hits.iter().any(|h| h.metadata.session_id == s.session_id))
+        .collect();
+    extra.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+
+    hits.extend(extra.into_iter().map(|s| SessionSearchHit { metadata: s.clone(), snippet: None }));
+    if let Some(limit) = limit {
+        hits.truncate(limit);
+    }
+}
+
+/// A response that stopped before the model was done (cancelled, or cut off at `max_tokens`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interrupted {
+    /// `query_id` of the stream, which `agent.continue` resumes
+    pub query_id: String,
+    /// The query it answers
+    pub query: String,
+}
+
+/// Direction to move through sessions in recency order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionCycle {
+    /// Toward more recently updated sessions
+    Previous,
+    /// Toward older sessions
+    Next,
+}
+
+/// Sessions ordered newest first by `updated_at`
+pub fn recency_order(sessions: &[SessionMetadata]) -> Vec<SessionMetadata> {
+    let mut ordered = sessions.to_vec();
+    ordered.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+    ordered
+}
+
+/// Index to move to from `cursor` in a list of `len` sessions, wrapping at both ends
+///
+/// Without a cursor `Next` starts at the newest session and `Previous` at the oldest.
+pub fn cycle_index(len: usize, cursor: Option<usize>, direction: SessionCycle) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    Some(match (cursor.filter(|&i| i < len), direction) {
+        (None, SessionCycle::Next) => 0,
+        (None, SessionCycle::Previous) => len - 1,
+        (Some(i), SessionCycle::Next) => (i + 1) % len,
+        (Some(i), SessionCycle::Previous) => (i + len - 1) % len,
+    })
+}
+
+/// Whether forking `requested` copies the `current` session (the default)
+fn forks_current(requested: Option<&str>, current: Option<&str>) -> bool {
+    requested.is_none() || requested == current
+}
+
+/// Params for `session.fork`; without a source the backend forks its active session
+fn fork_params(source: Option<&str>) -> serde_json::Value {
+    match source {
+        Some(id) => serde_json::json!({ "session_id": id }),
+        None => serde_json::json!({}),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ipc::message::QueryOptions;
+
+    #[test]
+    fn test_export_result_with_metadata() {
+        let result = serde_json::json!({
+            "status": "success",
+            "format": "markdown",
+            "content": "# Session",
+            "byte_count": 18 * 1024,
+            "message_count": 42
+        });
+        let export = ExportResult::from_result(&result, "json").unwrap();
+        assert_eq!(export.content(), "# Session");
+        assert_eq!(export.format, "markdown");
+        assert_eq!(export.byte_count, 18 * 1024);
+        assert_eq!(export.message_count, Some(42));
+        assert_eq!(export.summary(), "Exported 42 messages (18 KB) as markdown");
+    }
+
+    #[test]
+    fn test_export_result_content_only() {
+        let result = serde_json::json!({ "content": "hello" });
+        let export = ExportResult::from_result(&result, "markdown").unwrap();
+        assert_eq!(export.format, "markdown");
+        assert_eq!(export.byte_count, 5);
+        assert_eq!(export.message_count, None);
+        assert_eq!(export.summary(), "Exported 5 B as markdown");
+
+        let failed = serde_json::json!({ "status": "error", "error": "Unsupported format: pdf" });
+        let err = ExportResult::from_result(&failed, "pdf").unwrap_err();
+        assert!(err.to_string().contains("Unsupported format: pdf"));
+    }
+
+    #[test]
+    fn test_structured_json_export() {
+        use chrono::TimeZone;
+
+        let timestamp = Utc.with_ymd_and_hms(2025, 10, 4, 9, 30, 0).unwrap();
+        let mut answer = Message {
+            role: MessageRole::Assistant,
+            content: "ls -la".into(),
+            timestamp,
+            token_count: Some(4),
+            metadata: BTreeMap::new(),
+        };
+        answer.metadata.insert("model".into(), "mock".into());
+        let session = Session {
+            metadata: SessionMetadata {
+                session_id: "abc123".into(),
+                title: "Files".into(),
+                created_at: timestamp,
+                updated_at: timestamp,
+                message_count: 2,
+                total_tokens: 4,
+                pinned: false,
+            },
+            messages: vec![Message { timestamp, ..message(MessageRole::User, "list files") }, answer],
+        };
+
+        let export = ExportResult::from_session(&session).unwrap();
+        assert_eq!(export.format, JSON_EXPORT_FORMAT);
+        assert_eq!(export.message_count, Some(2));
+        assert!(export.summary().starts_with("Exported 2 messages"), "{}", export.summary());
+        assert!(export.content().contains('\n'), "not pretty-printed");
+
+        let parsed: serde_json::Value = serde_json::from_str(export.content()).unwrap();
+        assert_eq!(parsed["metadata"]["session_id"], "abc123");
+        assert_eq!(parsed["metadata"]["created_at"], "2025-10-04T09:30:00Z");
+        assert_eq!(parsed["messages"][0], serde_json::json!({
+            "role": "user",
+            "content": "list files",
+            "timestamp": "2025-10-04T09:30:00Z",
+            "token_count": null,
+            "metadata": {},
+        }));
+        assert_eq!(parsed["messages"][1]["token_count"], 4);
+        assert_eq!(parsed["messages"][1]["metadata"], serde_json::json!({ "model": "mock" }));
+
+        // Round-trips through the same types
+        let restored: Session = serde_json::from_str(export.content()).unwrap();
+        assert_eq!(restored.messages[1].metadata.get("model").map(String::as_str), Some("mock"));
+    }
+
+    fn message(role: MessageRole, content: &str) -> Message {
+        Message { role, content: content.into(), timestamp: Utc::now(), token_count: None, metadata: BTreeMap::new() }
+    }
+
+    #[test]
+    fn test_activation_replaces_or_appends() {
+        let loaded = [message(MessageRole::User, "list files"), message(MessageRole::Assistant, "ls -la")];
+
+        let mut manager = SessionManager::new(Arc::new(Mutex::new(IpcClient::new())));
+        manager.transcript_mut().push_user("earlier question", Utc::now());
+        manager.activate(&loaded, LoadMode::Replace);
+        let contents: Vec<&str> = manager.transcript().messages().iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["list files", "ls -la"]);
+        assert!(manager.transcript().messages().iter().all(|m| m.token_count.is_some()));
+
+        let mut manager = SessionManager::new(Arc::new(Mutex::new(IpcClient::new())));
+        manager.transcript_mut().push_user("earlier question", Utc::now());
+        manager.activate(&loaded, LoadMode::Append);
+        let contents: Vec<&str> = manager.transcript().messages().iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["earlier question", "list files", "ls -la"]);
+    }
+
+    #[test]
+    fn test_activation_context_sent_once() {
+        let mut manager = SessionManager::new(Arc::new(Mutex::new(IpcClient::new())));
+        assert!(manager.activation_context().is_empty());
+
+        manager.activate(&[message(MessageRole::User, "hi"), message(MessageRole::Assistant, "hello")], LoadMode::Replace);
+        let context = manager.activation_context();
+        assert_eq!(context, [
+            ContextMessage { role: "user".to_string(), content: "hi".to_string() },
+            ContextMessage { role: "assistant".to_string(), content: "hello".to_string() },
+        ]);
+        // Until a query carrying it is accepted, the context is offered again
+        assert_eq!(manager.activation_context(), context);
+        // Later queries rely on the backend session instead
+        manager.activation_context_sent();
+        assert!(manager.activation_context().is_empty());
+    }
+
+    #[test]
+    fn test_follow_up_query_carries_parent_turn() {
+        let mut manager = SessionManager::new(Arc::new(Mutex::new(IpcClient::new())));
+        assert_eq!(manager.parent_turn(), None);
+
+        let complete = serde_json::json!({"query_id": "q1", "message_id": "m1", "status": "success"});
+        manager.record_turn(crate::ipc::message::turn_id(&complete).unwrap());
+        let options = QueryOptions { parent: manager.parent_turn().map(str::to_string), ..Default::default() };
+        let params = Request::agent_query_with_options(2, "and then?", &options, true).params.unwrap();
+        assert_eq!(params["parent"], "m1");
+
+        // A different conversation starts a new thread
+        manager.activate(&[message(MessageRole::User, "hi")], LoadMode::Replace);
+        assert_eq!(manager.parent_turn(), None);
+    }
+
+    #[test]
+    fn test_interrupted_cleared_on_switch() {
+        let mut manager = SessionManager::new(Arc::new(Mutex::new(IpcClient::new())));
+        assert_eq!(manager.interrupted(), None);
+        let interrupted = Interrupted { query_id: "q1".to_string(), query: "explain".to_string() };
+        manager.set_interrupted(Some(interrupted.clone()));
+        assert_eq!(manager.interrupted(), Some(&interrupted));
+
+        manager.activate(&[message(MessageRole::User, "hi")], LoadMode::Replace);
+        assert_eq!(manager.interrupted(), None);
+    }
 
     // Note: SessionManager tests require a mock IpcClient
     // These tests are disabled until we implement a mock
@@ -283,6 +1117,65 @@ mod tests {
         assert_eq!(role, MessageRole::Assistant);
     }
 
+    #[test]
+    fn test_session_preview() {
+        let metadata = SessionMetadata {
+            session_id: "s1".to_string(),
+            title: "Files".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            message_count: 5,
+            total_tokens: 0,
+            pinned: false,
+        };
+        let session = Session {
+            metadata: metadata.clone(),
+            messages: vec![
+                message(MessageRole::System, "be brief"),
+                message(MessageRole::User, "how do I\n  list files?"),
+                message(MessageRole::Assistant, "Use ls"),
+                message(MessageRole::User, "and hidden ones?"),
+                message(MessageRole::Assistant, "Use ls -a to include dotfiles in the listing"),
+            ],
+        };
+        let preview = session.preview(20);
+        assert_eq!(preview.first_user.as_deref(), Some("how do I list files?"));
+        assert_eq!(preview.last_assistant.as_deref(), Some("Use ls -a to includ…"));
+
+        let empty = Session { metadata, messages: vec![message(MessageRole::User, "hi")] };
+        assert_eq!(empty.preview(20), SessionPreview { first_user: Some("hi".to_string()), last_assistant: None });
+    }
+
+    #[test]
+    fn test_cycle_in_recency_order() {
+        use chrono::TimeZone;
+        let at = |minute: u32| Utc.with_ymd_and_hms(2025, 10, 4, 12, minute, 0).unwrap();
+        let session = |id: &str, minute: u32| SessionMetadata {
+            session_id: id.to_string(),
+            title: id.to_string(),
+            created_at: at(0),
+            updated_at: at(minute),
+            message_count: 1,
+            total_tokens: 0,
+            pinned: false,
+        };
+        let ordered = recency_order(&[session("b", 20), session("c", 10), session("a", 30)]);
+        let ids: Vec<&str> = ordered.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, ["a", "b", "c"]);
+
+        // Next goes to older sessions, Previous to newer, wrapping at both ends
+        assert_eq!(cycle_index(3, None, SessionCycle::Next), Some(0));
+        assert_eq!(cycle_index(3, None, SessionCycle::Previous), Some(2));
+        assert_eq!(cycle_index(3, Some(0), SessionCycle::Next), Some(1));
+        assert_eq!(cycle_index(3, Some(2), SessionCycle::Next), Some(0));
+        assert_eq!(cycle_index(3, Some(1), SessionCycle::Previous), Some(0));
+        assert_eq!(cycle_index(3, Some(0), SessionCycle::Previous), Some(2));
+        // A cursor past the end (the list shrank) starts over
+        assert_eq!(cycle_index(2, Some(5), SessionCycle::Next), Some(0));
+        assert_eq!(cycle_index(0, None, SessionCycle::Next), None);
+        assert_eq!(cycle_index(1, Some(0), SessionCycle::Next), Some(0));
+    }
+
     #[test]
     fn test_message_creation() {
         let msg = Message {
@@ -290,7 +1183,7 @@ mod tests {
             content: "Hello!".to_string(),
             timestamp: Utc::now(),
             token_count: Some(2),
-            metadata: HashMap::new(),
+            metadata: BTreeMap::new(),
         };
 
         assert_eq!(msg.role, MessageRole::User);
@@ -307,6 +1200,7 @@ mod tests {
             updated_at: Utc::now(),
             message_count: 5,
             total_tokens: 100,
+            pinned: false,
         };
 
         assert_eq!(metadata.session_id, "test-123");
@@ -314,6 +1208,214 @@ mod tests {
         assert_eq!(metadata.total_tokens, 100);
     }
 
+    #[test]
+    fn test_fallback_metadata_uses_clock() {
+        use crate::clock::MockClock;
+        use chrono::TimeZone;
+
+        let pinned = Utc.with_ymd_and_hms(2025, 10, 4, 12, 0, 0).unwrap();
+        let client = Arc::new(Mutex::new(IpcClient::new()));
+        let manager = SessionManager::with_clock(client, Arc::new(MockClock::new(pinned)));
+        assert_eq!(manager.now(), pinned);
+
+        // No messages: timestamps come from the clock
+        let metadata = manager.fallback_metadata("abc", &[]);
+        assert_eq!(metadata.created_at, pinned);
+        assert_eq!(metadata.updated_at, pinned);
+        assert_eq!(metadata.title, "Session abc");
+
+        // With messages: timestamps come from the first/last message
+        let first = pinned - chrono::Duration::hours(2);
+        let last = pinned - chrono::Duration::minutes(5);
+        let messages: Vec<Message> = [first, last].iter().map(|&timestamp| Message {
+            role: MessageRole::User,
+            content: "hi".to_string(),
+            timestamp,
+            token_count: Some(3),
+            metadata: BTreeMap::new(),
+        }).collect();
+        let metadata = manager.fallback_metadata("0123456789", &messages);
+        assert_eq!(metadata.created_at, first);
+        assert_eq!(metadata.updated_at, last);
+        assert_eq!(metadata.total_tokens, 6);
+        assert_eq!(metadata.title, "Session 01234567");
+        assert_eq!(crate::clock::humanize_since(metadata.updated_at, manager.now()), "5m ago");
+    }
+
+    #[test]
+    fn test_fork_params() {
+        assert_eq!(fork_params(Some("abc-123")), serde_json::json!({ "session_id": "abc-123" }));
+        assert_eq!(fork_params(None), serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_forks_current() {
+        assert!(forks_current(None, Some("abc")));
+        assert!(forks_current(None, None));
+        assert!(forks_current(Some("abc"), Some("abc")));
+        assert!(!forks_current(Some("def"), Some("abc")));
+        assert!(!forks_current(Some("def"), None));
+    }
+
+    #[test]
+    fn test_should_auto_create() {
+        assert!(should_auto_create(true, None));
+        assert!(!should_auto_create(true, Some("2025-10-04_120000")));
+        assert!(!should_auto_create(false, None));
+        assert!(!should_auto_create(false, Some("2025-10-04_120000")));
+    }
+
+    #[test]
+    fn test_new_session_becomes_current() {
+        let client = Arc::new(Mutex::new(IpcClient::new()));
+        let mut manager = SessionManager::new(client);
+        assert_eq!(manager.current_session_id(), None);
+
+        let result = serde_json::json!({"status": "success", "session_id": "2025-10-04_120000", "title": null});
+        let metadata = manager.activate_new_session(&result, None).unwrap();
+        assert_eq!(metadata.session_id, "2025-10-04_120000");
+        assert_eq!(metadata.message_count, 0);
+        assert_eq!(manager.current_session_id(), Some("2025-10-04_120000"));
+        assert!(manager.get_cached_metadata("2025-10-04_120000").is_some());
+        assert!(!should_auto_create(true, manager.current_session_id()));
+
+        // A backend-side failure leaves the current session alone
+        let failed = serde_json::json!({"status": "error", "error": "disk full"});
+        let err = manager.activate_new_session(&failed, None).unwrap_err();
+        assert!(err.to_string().contains("disk full"));
+        assert_eq!(manager.current_session_id(), Some("2025-10-04_120000"));
+    }
+
+    #[test]
+    fn test_new_session_title() {
+        let client = Arc::new(Mutex::new(IpcClient::new()));
+        let mut manager = SessionManager::new(client);
+
+        // A backend that doesn't echo the title still gets the requested one
+        let result = serde_json::json!({"status": "success", "session_id": "s1"});
+        assert_eq!(manager.activate_new_session(&result, Some("Refactor plan")).unwrap().title, "Refactor plan");
+        assert_eq!(manager.get_cached_metadata("s1").unwrap().title, "Refactor plan");
+
+        // The backend's own title wins
+        let result = serde_json::json!({"status": "success", "session_id": "s2", "title": "Refactor plan (2)"});
+        assert_eq!(manager.activate_new_session(&result, Some("Refactor plan")).unwrap().title, "Refactor plan (2)");
+        assert_eq!(manager.current_session_id(), Some("s2"));
+    }
+
+    #[test]
+    fn test_most_recent_session() {
+        use chrono::TimeZone;
+
+        let metadata = |id: &str, hour: u32| SessionMetadata {
+            session_id: id.to_string(),
+            title: id.to_string(),
+            created_at: Utc.with_ymd_and_hms(2025, 10, 1, 0, 0, 0).unwrap(),
+            updated_at: Utc.with_ymd_and_hms(2025, 10, 4, hour, 0, 0).unwrap(),
+            message_count: 1,
+            total_tokens: 0,
+            pinned: false,
+        };
+
+        let sessions = vec![metadata("older", 9), metadata("newest", 17), metadata("middle", 12)];
+        assert_eq!(most_recent(&sessions).unwrap().session_id, "newest");
+        assert!(most_recent(&[]).is_none());
+    }
+
+    #[test]
+    fn test_search_hits_and_title_fallback() {
+        use chrono::TimeZone;
+
+        let metadata = |id: &str, title: &str, hour: u32| SessionMetadata {
+            session_id: id.to_string(),
+            title: title.to_string(),
+            created_at: Utc.with_ymd_and_hms(2025, 10, 1, 0, 0, 0).unwrap(),
+            updated_at: Utc.with_ymd_and_hms(2025, 10, 4, hour, 0, 0).unwrap(),
+            message_count: 1,
+            total_tokens: 0,
+            pinned: false,
+        };
+
+        // Backend hits carry the flattened metadata plus the matched snippet
+        let mut hits: Vec<SessionSearchHit> = serde_json::from_value(serde_json::json!([{
+            "session_id": "a", "title": "parser notes",
+            "created_at": "2025-10-01T00:00:00Z", "updated_at": "2025-10-04T09:00:00Z",
+            "message_count": 4, "total_tokens": 120, "snippet": "the Parser drops comments"
+        }])).unwrap();
+        assert_eq!(hits[0].metadata.message_count, 4);
+        assert_eq!(hits[0].snippet.as_deref(), Some("the Parser drops comments"));
+
+        // Case-insensitive title matches the backend missed follow, newest first, without duplicates
+        let known = [
+            metadata("a", "parser notes", 9),
+            metadata("b", "Parser rewrite", 10),
+            metadata("c", "PARSER bugs", 15),
+            metadata("d", "lexer", 16),
+        ];
+        add_title_matches(&mut hits, &known, "parser", None);
+        let ids: Vec<_> = hits.iter().map(|h| h.metadata.session_id.as_str()).collect();
+        assert_eq!(ids, ["a", "c", "b"]);
+        assert!(hits[1].snippet.is_none());
+
+        add_title_matches(&mut hits, &known, "Parser", Some(2));
+        assert_eq!(hits.len(), 2);
+    }
+
+    /// Tokenizer with exact word counts, to check non-estimate handling
+    struct WordTokenizer;
+
+    impl Tokenizer for WordTokenizer {
+        fn count(&self, text: &str) -> usize {
+            text.split_whitespace().count()
+        }
+    }
+
+    #[test]
+    fn test_transcript_token_counts() {
+        let now = Utc::now();
+        let mut transcript = Transcript::new(Arc::new(WordTokenizer));
+        assert_eq!(transcript.push_user("list all files", now).token_count, Some(3));
+        assert_eq!(transcript.push_assistant("Use ls -la", now).token_count, Some(3));
+        transcript.push_user("thanks", now);
+
+        let per_message: usize = transcript.messages().iter().map(|m| m.token_count.unwrap()).sum();
+        assert_eq!(transcript.total_tokens(), per_message);
+        assert_eq!(transcript.total_tokens(), 7);
+        assert!(!transcript.is_estimate());
+        assert_eq!(transcript.messages()[1].role, MessageRole::Assistant);
+        assert_eq!(transcript.messages()[1].content, "Use ls -la");
+    }
+
+    #[test]
+    fn test_transcript_strips_display_colors() {
+        let now = Utc::now();
+        let mut transcript = Transcript::new(Arc::new(WordTokenizer));
+        let display = format!("Run {}ls -la{}", crate::ansi::colors::GREEN, crate::ansi::colors::RESET);
+
+        // Escapes that slip into the content are stripped from the stored copy
+        transcript.push_assistant(&display, now);
+        assert_eq!(transcript.messages()[0].content, "Run ls -la");
+    }
+
+    #[test]
+    fn test_transcript_heuristic_counts_are_marked() {
+        let now = Utc::now();
+        let mut transcript = Transcript::new(Arc::new(HeuristicTokenizer));
+        let message = transcript.push_user("hello world!", now);
+        assert_eq!(message.token_count, Some(3));
+        assert_eq!(message.metadata.get(TOKEN_ESTIMATE_KEY).map(String::as_str), Some("true"));
+        assert!(transcript.is_estimate());
+
+        // Loaded messages keep backend counts and only missing ones are estimated
+        let loaded = vec![
+            Message { role: MessageRole::User, content: "hi".into(), timestamp: now, token_count: Some(10), metadata: BTreeMap::new() },
+            Message { role: MessageRole::Assistant, content: "hello".into(), timestamp: now, token_count: None, metadata: BTreeMap::new() },
+        ];
+        transcript.replace(loaded);
+        assert_eq!(transcript.messages()[0].token_count, Some(10));
+        assert_eq!(transcript.messages()[1].token_count, Some(2));
+        assert_eq!(transcript.total_tokens(), 12);
+    }
+
     // Disabled: requires IpcClient
     // #[test]
     // fn test_clear_cache() { ... }
@@ -0,0 +1,254 @@
+// Streams - Route streaming notifications to the query they belong to
+//
+// The backend tags `stream.token`, `stream.block` and `stream.complete` with
+// the `query_id` returned by `agent.query`. Each active query gets its own
+// output accumulator and cancellation token, so one stream can be cancelled
+// or finish without touching another, and late notifications from a stream
+// that already ended are dropped instead of leaking into the next response.
+
+use crate::ipc::message::Notification;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use tokio::sync::watch;
+
+/// State of one streaming query
+#[derive(Debug)]
+pub struct ActiveStream {
+    /// Response source text received so far (no escape codes)
+    pub text: String,
+    /// Hash of the code block just received, while nothing else has followed it
+    last_code_block: Option<u64>,
+    cancel: watch::Sender<bool>,
+}
+
+impl ActiveStream {
+    /// Whether `notification` is a code block identical to the one right before it
+    ///
+    /// Remembers each code block for the next call; any other visible output
+    /// (text, another kind of block) breaks the run. Whitespace-only tokens
+    /// between two blocks don't.
+    fn repeats_last_code_block(&mut self, notification: &Notification) -> bool {
+        let params = notification.params.as_ref();
+        let is_code = notification.method == "stream.block"
+            && params.and_then(|p| p.get("type")).and_then(|v| v.as_str()) == Some("code");
+        let content = notification.stream_content().unwrap_or_default();
+        if !is_code {
+            if !content.trim().is_empty() {
+                self.last_code_block = None;
+            }
+            return false;
+        }
+
+        let language = params.and_then(|p| p.get("language")).and_then(|v| v.as_str());
+        let hash = code_block_hash(language, content);
+        self.last_code_block.replace(hash) == Some(hash)
+    }
+}
+
+/// Content hash of a code block (language included)
+fn code_block_hash(language: Option<&str>, content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    language.unwrap_or("text").hash(&mut hasher);
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Where a notification belongs
+#[derive(Debug)]
+pub enum Routed {
+    /// Output for an active stream (already added to its text)
+    Output,
+    /// A code block identical to the one just before it (not added to the text)
+    RepeatedBlock,
+    /// The stream finished; its final state is handed back
+    Complete(ActiveStream),
+    /// Not tied to a query (tool approvals, auth challenges)
+    Unscoped,
+    /// For a stream that was cancelled, already finished, or never started here
+    Dropped,
+}
+
+/// Active streams keyed by `query_id`
+#[derive(Debug, Default)]
+pub struct ActiveStreams {
+    streams: HashMap<String, ActiveStream>,
+    /// Route immediately-repeated code blocks as `RepeatedBlock`
+    dedupe_code_blocks: bool,
+}
+
+impl ActiveStreams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report code blocks that repeat the previous one instead of routing them as output
+    pub fn set_dedupe_code_blocks(&mut self, enabled: bool) {
+        self.dedupe_code_blocks = enabled;
+    }
+
+    /// Track a new stream; the receiver fires when it is cancelled
+    pub fn start(&mut self, query_id: &str) -> watch::Receiver<bool> {
+        let (cancel, cancelled) = watch::channel(false);
+        self.streams.insert(query_id.to_string(), ActiveStream {
+            text: String::new(),
+            last_code_block: None,
+            cancel,
+        });
+        cancelled
+    }
+
+    /// Number of streams still running
+    #[allow(dead_code)] // For the status line once queries can overlap
+    pub fn len(&self) -> usize {
+        self.streams.len()
+    }
+
+    /// Cancel one stream and stop tracking it, returning what it had received
+    pub fn cancel(&mut self, query_id: &str) -> Option<ActiveStream> {
+        let stream = self.streams.remove(query_id)?;
+        let _ = stream.cancel.send(true);
+        Some(stream)
+    }
+
+    /// Route a notification to its stream by `query_id`
+    ///
+    /// Stream notifications without a `query_id` (older backends) go to the
+    /// only active stream, if there is exactly one.
+    pub fn route(&mut self, notification: &Notification) -> Routed {
+        if !notification.method.starts_with("stream.") {
+            return Routed::Unscoped;
+        }
+
+        let query_id = notification.params.as_ref()
+            .and_then(|p| p.get("query_id"))
+            .and_then(|v| v.as_str());
+        let query_id = match query_id {
+            Some(id) => id.to_string(),
+            None if self.streams.len() == 1 => self.streams.keys().next().cloned().unwrap_or_default(),
+            None => return Routed::Dropped,
+        };
+
+        if notification.method == "stream.complete" {
+            return match self.streams.remove(&query_id) {
+                Some(stream) => Routed::Complete(stream),
+                None => Routed::Dropped,
+            };
+        }
+
+        match self.streams.get_mut(&query_id) {
+            Some(stream) => {
+                if stream.repeats_last_code_block(notification) && self.dedupe_code_blocks {
+                    return Routed::RepeatedBlock;
+                }
+                if let Some(content) = notification.stream_content() {
+                    stream.text.push_str(content);
+                }
+                Routed::Output
+            }
+            None => Routed::Dropped,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn token(query_id: &str, content: &str) -> Notification {
+        Notification::new("stream.token", Some(json!({ "query_id": query_id, "content": content })))
+    }
+
+    fn code_block(query_id: &str, content: &str) -> Notification {
+        Notification::new("stream.block", Some(json!({
+            "query_id": query_id, "type": "code", "language": "rust", "content": content,
+        })))
+    }
+
+    fn complete(query_id: &str) -> Notification {
+        Notification::new("stream.complete", Some(json!({ "query_id": query_id, "status": "success" })))
+    }
+
+    #[test]
+    fn test_routes_by_query_id() {
+        let mut streams = ActiveStreams::new();
+        streams.start("a");
+        streams.start("b");
+
+        assert!(matches!(streams.route(&token("a", "Hel")), Routed::Output));
+        assert!(matches!(streams.route(&token("b", "Wor")), Routed::Output));
+        assert!(matches!(streams.route(&token("a", "lo")), Routed::Output));
+        assert!(matches!(streams.route(&token("zzz", "?")), Routed::Dropped));
+
+        let Routed::Complete(a) = streams.route(&complete("a")) else { panic!("expected a to complete") };
+        assert_eq!(a.text, "Hello");
+        assert_eq!(streams.len(), 1);
+
+        // Late output for a finished stream doesn't reach anyone
+        assert!(matches!(streams.route(&token("a", "late")), Routed::Dropped));
+
+        let Routed::Complete(b) = streams.route(&complete("b")) else { panic!("expected b to complete") };
+        assert_eq!(b.text, "Wor");
+
+        let approval = Notification::new("tool.request_approval", Some(json!({ "execution_id": "x" })));
+        assert!(matches!(streams.route(&approval), Routed::Unscoped));
+    }
+
+    #[test]
+    fn test_independent_cancellation() {
+        let mut streams = ActiveStreams::new();
+        let a_cancelled = streams.start("a");
+        let b_cancelled = streams.start("b");
+        streams.route(&token("a", "partial"));
+        let a = streams.cancel("a").unwrap();
+        assert_eq!(a.text, "partial");
+        assert!(*a_cancelled.borrow());
+        assert!(!*b_cancelled.borrow());
+
+        assert!(matches!(streams.route(&token("a", "more")), Routed::Dropped));
+        assert!(matches!(streams.route(&token("b", "still here")), Routed::Output));
+        assert!(streams.cancel("a").is_none());
+    }
+
+    #[test]
+    fn test_repeated_code_block_suppressed() {
+        let mut streams = ActiveStreams::new();
+        streams.set_dedupe_code_blocks(true);
+        streams.start("a");
+
+        // Two identical blocks: the second is suppressed, even across a newline token
+        assert!(matches!(streams.route(&code_block("a", "fn main() {}")), Routed::Output));
+        assert!(matches!(streams.route(&token("a", "\n")), Routed::Output));
+        assert!(matches!(streams.route(&code_block("a", "fn main() {}")), Routed::RepeatedBlock));
+
+        // Two different blocks both come through
+        assert!(matches!(streams.route(&code_block("a", "let x = 1;")), Routed::Output));
+        assert!(matches!(streams.route(&code_block("a", "let x = 2;")), Routed::Output));
+
+        // Text in between means the repeat isn't immediate
+        assert!(matches!(streams.route(&token("a", "Again:")), Routed::Output));
+        assert!(matches!(streams.route(&code_block("a", "let x = 2;")), Routed::Output));
+
+        let Routed::Complete(a) = streams.route(&complete("a")) else { panic!("expected a to complete") };
+        assert_eq!(a.text, "fn main() {}\nlet x = 1;let x = 2;Again:let x = 2;");
+
+        // Off by default
+        let mut streams = ActiveStreams::new();
+        streams.start("b");
+        assert!(matches!(streams.route(&code_block("b", "same")), Routed::Output));
+        assert!(matches!(streams.route(&code_block("b", "same")), Routed::Output));
+    }
+
+    #[test]
+    fn test_untagged_output_goes_to_sole_stream() {
+        let mut streams = ActiveStreams::new();
+        streams.start("only");
+        let untagged = Notification::new("stream.token", Some(json!({ "content": "hi" })));
+        assert!(matches!(streams.route(&untagged), Routed::Output));
+
+        streams.start("second");
+        assert!(matches!(streams.route(&untagged), Routed::Dropped));
+        let Some(only) = streams.cancel("only") else { panic!("expected stream") };
+        assert_eq!(only.text, "hi");
+    }
+}
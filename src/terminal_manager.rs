@@ -10,6 +10,10 @@ use crossterm::{
     style::{Color, Print, ResetColor, SetForegroundColor},
     terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use crate::ansi;
+use crate::config::{CursorStyle, GlyphMode};
+use crate::glyphs::{self, Glyph};
+use crate::line_editor::RenderedInput;
 use std::io::{self, Write};
 
 /// Status information for display
@@ -17,6 +21,156 @@ pub struct StatusInfo {
     pub connection_state: String,
     pub model: String,
     pub session_id: Option<String>,
+    /// Backend version, shown only when it is incompatible with ours
+    pub backend_version_mismatch: Option<String>,
+    /// Whether tools really execute (vs demo mode)
+    pub real_execution: bool,
+    /// Whether safe mode is active
+    pub safe_mode: bool,
+    /// Running token total of the conversation (hidden when zero)
+    pub tokens: usize,
+    /// Whether `tokens` is a local estimate
+    pub tokens_estimated: bool,
+    /// Whether input is masked (privacy mode)
+    pub private: bool,
+}
+
+/// What needs repainting after the terminal is resized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeRedraw {
+    /// Same size as before (repeated event): nothing to do
+    Nothing,
+    /// Only the height changed: repaint the status line and move the prompt
+    StatusAndPrompt,
+    /// The width changed: the status line and input, laid out for the old
+    /// width, must be cleared and drawn again
+    Reflow,
+}
+
+/// Decide how much to redraw when the size goes from `old` to `new` (cols, rows)
+///
+/// With no known previous size everything is reflowed.
+pub fn resize_redraw(old: Option<(u16, u16)>, new: (u16, u16)) -> ResizeRedraw {
+    match old {
+        Some(old) if old == new => ResizeRedraw::Nothing,
+        Some((old_cols, _)) if old_cols == new.0 => ResizeRedraw::StatusAndPrompt,
+        _ => ResizeRedraw::Reflow,
+    }
+}
+
+/// The `│`-separated parts of the status line, connection state first
+pub fn status_segments(status: &StatusInfo, mode: GlyphMode) -> Vec<String> {
+    let mut parts = vec![format!("● {}", status.connection_state)];
+    
+    // Safe mode banner, ahead of everything else
+    if status.safe_mode {
+        parts.push(format!("{} SAFE MODE", Glyph::Safe.in_mode(mode)));
+    }
+    
+    // Model
+    parts.push(format!("{} {}", Glyph::Ai.in_mode(mode), status.model));
+    
+    // Session ID (short form)
+    if let Some(session_id) = &status.session_id {
+        let short_id = &session_id[..8.min(session_id.len())];
+        parts.push(format!("{} {}", Glyph::Session.in_mode(mode), short_id));
+    }
+    
+    // Running token total
+    if status.tokens > 0 {
+        let approx = if status.tokens_estimated { "~" } else { "" };
+        parts.push(format!("{} {}{} tok", Glyph::Tokens.in_mode(mode), approx, status.tokens));
+    }
+    
+    if status.private {
+        parts.push(format!("{} PRIVATE", Glyph::Lock.in_mode(mode)));
+    }
+    
+    // Real execution warning
+    if status.real_execution {
+        parts.push(format!("{} REAL EXEC", Glyph::RealExec.in_mode(mode)));
+    }
+    
+    // Version mismatch warning
+    if let Some(backend_version) = &status.backend_version_mismatch {
+        parts.push(format!("{} backend v{}", Glyph::Caution.in_mode(mode), backend_version));
+    }
+    
+    parts
+}
+
+/// Compact safety flags for the prompt, or `None` when neither is on
+///
+/// Inline there is no status line, but safe mode and real execution must
+/// stay visible, so these ride along in front of the prompt instead.
+pub fn safety_badge(status: &StatusInfo, mode: GlyphMode) -> Option<String> {
+    let mut flags = Vec::new();
+    if status.safe_mode {
+        flags.push(format!("{} SAFE", Glyph::Safe.in_mode(mode)));
+    }
+    if status.real_execution {
+        flags.push(format!("{} EXEC", Glyph::RealExec.in_mode(mode)));
+    }
+    (!flags.is_empty()).then(|| flags.join(" "))
+}
+
+/// Where the UI lives on screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenLayout {
+    /// Alternate screen: status line pinned to the top, prompt on the bottom rows
+    AlternateScreen,
+    /// Normal screen: output flows into scrollback and nothing is pinned
+    Inline,
+}
+
+impl ScreenLayout {
+    /// Layout for the `use_alternate_screen` setting
+    pub fn from_config(use_alternate_screen: bool) -> Self {
+        if use_alternate_screen {
+            ScreenLayout::AlternateScreen
+        } else {
+            ScreenLayout::Inline
+        }
+    }
+}
+
+/// Row to draw the prompt on, given the terminal height and the cursor's row
+///
+/// The alternate screen reserves the last two rows; inline the prompt stays
+/// wherever output left the cursor.
+pub fn prompt_row(layout: ScreenLayout, rows: u16, cursor_row: u16) -> u16 {
+    match layout {
+        ScreenLayout::AlternateScreen => rows.saturating_sub(2),
+        ScreenLayout::Inline => cursor_row,
+    }
+}
+
+/// Row the first input line goes on, for an input `height` lines tall
+///
+/// In the alternate screen the last line sits on the prompt row and earlier
+/// ones grow upward. Inline the input starts where it was last drawn: the
+/// cursor is on line `drawn_cursor_row` of it.
+pub fn input_top_row(layout: ScreenLayout, rows: u16, cursor_row: u16, height: u16, drawn_cursor_row: u16) -> u16 {
+    match layout {
+        ScreenLayout::AlternateScreen => prompt_row(layout, rows, cursor_row).saturating_sub(height.saturating_sub(1)),
+        ScreenLayout::Inline => cursor_row.saturating_sub(drawn_cursor_row),
+    }
+}
+
+/// DECSCUSR sequence that resets the cursor to the terminal's default shape
+const CURSOR_STYLE_RESET: &str = "\x1b[0 q";
+
+/// DECSCUSR sequence selecting `style` (`None` leaves the cursor alone)
+pub fn cursor_style_escape(style: CursorStyle) -> Option<&'static str> {
+    match style {
+        CursorStyle::Default => None,
+        CursorStyle::BlinkingBlock => Some("\x1b[1 q"),
+        CursorStyle::Block => Some("\x1b[2 q"),
+        CursorStyle::BlinkingUnderline => Some("\x1b[3 q"),
+        CursorStyle::Underline => Some("\x1b[4 q"),
+        CursorStyle::BlinkingBeam => Some("\x1b[5 q"),
+        CursorStyle::Beam => Some("\x1b[6 q"),
+    }
 }
 
 /// Manages terminal state and provides control operations
@@ -24,6 +178,19 @@ pub struct TerminalManager {
     raw_mode_enabled: bool,
     alternate_screen_enabled: bool,
     status_info: Option<StatusInfo>,
+    /// Whether `resume` should re-enter the alternate screen
+    resume_alternate_screen: bool,
+    /// Last status line drawn (with terminal width), to skip identical repaints
+    last_status_rendered: Option<String>,
+    /// Terminal size as of the last resize handled (cols, rows)
+    size: Option<(u16, u16)>,
+    /// Cursor shape applied while in the alternate screen
+    cursor_style: CursorStyle,
+    layout: ScreenLayout,
+    /// Lines in the input last drawn by `draw_input`
+    input_rows: u16,
+    /// Line of that input the cursor was left on
+    input_cursor_row: u16,
 }
 
 impl TerminalManager {
@@ -35,23 +202,74 @@ impl TerminalManager {
             raw_mode_enabled: true,
             alternate_screen_enabled: false,
             status_info: None,
+            resume_alternate_screen: false,
+            last_status_rendered: None,
+            size: terminal::size().ok(),
+            cursor_style: CursorStyle::Default,
+            layout: ScreenLayout::AlternateScreen,
+            input_rows: 1,
+            input_cursor_row: 0,
         })
     }
     
-    /// Enable alternate screen buffer for clean UX
+    /// Choose between the alternate screen and inline rendering
+    pub fn set_layout(&mut self, layout: ScreenLayout) {
+        self.layout = layout;
+    }
+    
+    /// Whether output is rendered inline on the normal screen
+    pub fn is_inline(&self) -> bool {
+        self.layout == ScreenLayout::Inline
+    }
+    
+    /// Set up the screen for the chosen layout
+    ///
+    /// The alternate screen is entered and cleared; inline nothing on screen is
+    /// touched apart from the cursor style.
+    pub fn enter_layout(&mut self) -> Result<()> {
+        match self.layout {
+            ScreenLayout::AlternateScreen => {
+                self.enter_alternate_screen()?;
+                self.clear_screen()?;
+            }
+            ScreenLayout::Inline => self.apply_cursor_style()?,
+        }
+        Ok(())
+    }
+    
+    /// Write the configured cursor style, if any
+    fn apply_cursor_style(&self) -> Result<()> {
+        if let Some(escape) = cursor_style_escape(self.cursor_style) {
+            execute!(io::stdout(), Print(escape))?;
+        }
+        Ok(())
+    }
+    
+    /// Cursor shape to use from the next `enter_alternate_screen`
+    pub fn set_cursor_style(&mut self, style: CursorStyle) {
+        self.cursor_style = style;
+    }
+    
+    /// Enable alternate screen buffer for clean UX (and apply the cursor style)
     pub fn enter_alternate_screen(&mut self) -> Result<()> {
         if !self.alternate_screen_enabled {
             execute!(io::stdout(), EnterAlternateScreen)?;
+            self.apply_cursor_style()?;
             self.alternate_screen_enabled = true;
+            self.invalidate_status();
         }
         Ok(())
     }
     
-    /// Leave alternate screen buffer and restore original screen
+    /// Leave alternate screen buffer and restore original screen (and cursor)
     pub fn leave_alternate_screen(&mut self) -> Result<()> {
         if self.alternate_screen_enabled {
+            if cursor_style_escape(self.cursor_style).is_some() {
+                execute!(io::stdout(), Print(CURSOR_STYLE_RESET))?;
+            }
             execute!(io::stdout(), LeaveAlternateScreen)?;
             self.alternate_screen_enabled = false;
+            self.invalidate_status();
         }
         Ok(())
     }
@@ -63,31 +281,91 @@ impl TerminalManager {
             terminal::Clear(ClearType::All),
             cursor::MoveTo(0, 0)
         )?;
+        self.invalidate_status();
+        self.input_rows = 1;
+        self.input_cursor_row = 0;
+        Ok(())
+    }
+    
+    /// Clear the status line and its separator, leaving the output below alone
+    ///
+    /// After a width change they may have been cut off or wrapped onto the
+    /// next row; the next `draw_status_line` paints them at the new width.
+    pub fn clear_status_area(&mut self) -> Result<()> {
+        self.clear_status_area_to(&mut io::stdout())
+    }
+    
+    fn clear_status_area_to<W: Write>(&mut self, out: &mut W) -> Result<()> {
+        self.invalidate_status();
+        if self.is_inline() {
+            return Ok(());
+        }
+        execute!(
+            out,
+            cursor::SavePosition,
+            cursor::MoveTo(0, 0),
+            terminal::Clear(ClearType::CurrentLine),
+            cursor::MoveTo(0, 1),
+            terminal::Clear(ClearType::CurrentLine),
+            cursor::RestorePosition
+        )?;
         Ok(())
     }
     
+    /// Force the next `draw_status_line` to repaint (e.g. after output scrolled)
+    pub fn invalidate_status(&mut self) {
+        self.last_status_rendered = None;
+    }
+    
+    /// Record a new terminal size and return what must be redrawn for it
+    ///
+    /// Anything other than `Nothing` also forces the status line to repaint.
+    /// Inline, earlier output belongs to the scrollback and is never reflowed.
+    pub fn handle_resize(&mut self, cols: u16, rows: u16) -> ResizeRedraw {
+        let redraw = match resize_redraw(self.size.replace((cols, rows)), (cols, rows)) {
+            ResizeRedraw::Reflow if self.is_inline() => ResizeRedraw::StatusAndPrompt,
+            redraw => redraw,
+        };
+        if redraw != ResizeRedraw::Nothing {
+            self.invalidate_status();
+        }
+        redraw
+    }
+    
     /// Update status information
     pub fn set_status(&mut self, status: StatusInfo) {
         self.status_info = Some(status);
     }
     
-    /// Draw status line at the top of the screen
-    pub fn draw_status_line(&self) -> Result<()> {
+    /// Safety flags to show in the prompt, when inline hides the status line
+    pub fn prompt_badge(&self) -> Option<String> {
+        if !self.is_inline() {
+            return None;
+        }
+        safety_badge(self.status_info.as_ref()?, glyphs::mode())
+    }
+    
+    /// Draw status line at the top of the screen (skipped if unchanged)
+    pub fn draw_status_line(&mut self) -> Result<()> {
+        let (cols, _) = terminal::size()?;
+        self.draw_status_line_to(&mut io::stdout(), cols, cursor::position)?;
+        Ok(())
+    }
+    
+    /// Draw the status line to `out`, returning whether anything was rendered
+    ///
+    /// The cursor position is only queried when a repaint is actually needed.
+    fn draw_status_line_to<W: Write>(
+        &mut self,
+        out: &mut W,
+        cols: u16,
+        cursor_position: impl FnOnce() -> io::Result<(u16, u16)>,
+    ) -> Result<bool> {
+        // Inline there is no reserved row, and row 0 is someone else's output
+        if self.is_inline() {
+            return Ok(false);
+        }
         if let Some(status) = &self.status_info {
-            let (cols, _) = terminal::size()?;
-            
-            // Save cursor position
-            let current_pos = cursor::position()?;
-            
-            // Move to top line
-            execute!(io::stdout(), cursor::MoveTo(0, 0))?;
-            
-            // Clear the line
-            execute!(io::stdout(), terminal::Clear(ClearType::CurrentLine))?;
-            
-            // Build status line
-            let mut status_parts = Vec::new();
-            
             // Connection state with color
             let conn_color = match status.connection_state.as_str() {
                 "Connected" => Color::Green,
@@ -96,50 +374,55 @@ impl TerminalManager {
                 "Failed" | "Disconnected" => Color::Red,
                 _ => Color::White,
             };
-            status_parts.push(format!("● {}", status.connection_state));
+            let status_parts = status_segments(status, glyphs::mode());
             
-            // Model
-            status_parts.push(format!("🤖 {}", status.model));
+            let status_line = status_parts.join("  │  ");
             
-            // Session ID (short form)
-            if let Some(session_id) = &status.session_id {
-                let short_id = &session_id[..8.min(session_id.len())];
-                status_parts.push(format!("📝 {}", short_id));
+            // Skip the repaint if nothing visible changed
+            let rendered = format!("{}:{}", cols, status_line);
+            if self.last_status_rendered.as_deref() == Some(rendered.as_str()) {
+                return Ok(false);
             }
             
-            let status_line = status_parts.join("  │  ");
+            // Save cursor position
+            let current_pos = cursor_position()?;
+            
+            // Move to top line and clear it
+            queue!(out, cursor::MoveTo(0, 0), terminal::Clear(ClearType::CurrentLine))?;
             
-            // Truncate if too long
+            // Truncate to the terminal width (by display columns, never mid-character)
             let max_len = (cols as usize).saturating_sub(4);
-            let display_status = if status_line.len() > max_len {
-                format!("{}...", &status_line[..max_len.saturating_sub(3)])
-            } else {
-                status_line
-            };
+            let display_status = ansi::truncate_display(&status_line, max_len);
             
-            // Print with color
+            // Print with the connection state colored
+            let (conn_part, rest) = match display_status.strip_prefix(status_parts[0].as_str()) {
+                Some(rest) => (status_parts[0].as_str(), rest),
+                None => (display_status.as_str(), ""),
+            };
             queue!(
-                io::stdout(),
+                out,
                 SetForegroundColor(Color::DarkGrey),
                 Print(" "),
                 SetForegroundColor(conn_color),
-                Print(&status_parts[0]),
+                Print(conn_part),
                 ResetColor
             )?;
             
-            if status_parts.len() > 1 {
+            if let Some(rest) = rest.strip_prefix("  │  ") {
                 queue!(
-                    io::stdout(),
+                    out,
                     SetForegroundColor(Color::DarkGrey),
                     Print("  │  "),
                     ResetColor,
-                    Print(&status_parts[1..].join("  │  "))
+                    Print(rest)
                 )?;
+            } else if !rest.is_empty() {
+                queue!(out, Print(rest))?;
             }
             
             // Draw separator line
             queue!(
-                io::stdout(),
+                out,
                 cursor::MoveTo(0, 1),
                 SetForegroundColor(Color::DarkGrey),
                 Print("─".repeat(cols as usize)),
@@ -147,31 +430,23 @@ impl TerminalManager {
             )?;
             
             // Restore cursor position (adjust for status line)
-            execute!(io::stdout(), cursor::MoveTo(current_pos.0, current_pos.1.max(2)))?;
-            io::stdout().flush()?;
+            execute!(out, cursor::MoveTo(current_pos.0, current_pos.1.max(2)))?;
+            out.flush()?;
+            
+            self.last_status_rendered = Some(rendered);
+            return Ok(true);
         }
-        Ok(())
+        Ok(false)
     }
     
     /// Get terminal size (cols, rows)
-    #[allow(dead_code)]
     pub fn size(&self) -> Result<(u16, u16)> {
         Ok(terminal::size()?)
     }
     
-    /// Move cursor to start of current line and clear it
-    pub fn clear_current_line(&mut self) -> Result<()> {
-        execute!(
-            io::stdout(),
-            cursor::MoveToColumn(0),
-            terminal::Clear(ClearType::CurrentLine)
-        )?;
-        Ok(())
-    }
-    
     /// Clear streaming area (below status line, above prompt)
+    #[allow(dead_code)] // Reserved for streaming area management
     pub fn clear_streaming_area(&self) -> Result<()> {
-        let (cols, rows) = terminal::size()?;
         let current_pos = cursor::position()?;
         
         // Clear from line 2 (below status) to current position
@@ -189,16 +464,56 @@ impl TerminalManager {
     }
     
     /// Move to streaming area (below status line)
+    #[allow(dead_code)] // Reserved for streaming area management
     pub fn move_to_streaming_area(&self) -> Result<()> {
         execute!(io::stdout(), cursor::MoveTo(0, 3))?; // Line 3 (0=status, 1=separator, 2=blank)
         Ok(())
     }
     
-    /// Move to prompt area (bottom of screen)
-    pub fn move_to_prompt_area(&self) -> Result<()> {
+    /// Draw the input at the prompt area and place the cursor in it
+    ///
+    /// Rows left over from a taller input drawn before are cleared. The lines
+    /// should already be wrapped to the terminal width (see `LineEditor::render`).
+    pub fn draw_input(&mut self, input: &RenderedInput) -> Result<()> {
         let (_, rows) = terminal::size()?;
-        // Reserve last 2 lines for prompt
-        execute!(io::stdout(), cursor::MoveTo(0, rows.saturating_sub(2)))?;
+        let (_, cursor_row) = cursor::position()?;
+        let height = input.lines.len().max(1) as u16;
+        let top = input_top_row(self.layout, rows, cursor_row, height, self.input_cursor_row);
+        let previous_top = input_top_row(self.layout, rows, cursor_row, self.input_rows, self.input_cursor_row);
+        
+        let mut out = io::stdout();
+        queue!(
+            out,
+            cursor::MoveTo(0, top.min(previous_top)),
+            terminal::Clear(ClearType::FromCursorDown),
+            cursor::MoveTo(0, top)
+        )?;
+        for (i, line) in input.lines.iter().enumerate() {
+            if i > 0 {
+                queue!(out, Print("\r\n"))?;
+            }
+            queue!(out, Print(line))?;
+        }
+        
+        // Inline the lines may have scrolled the screen up to fit the last one
+        let top = top.min(rows.saturating_sub(height));
+        execute!(out, cursor::MoveTo(input.cursor_col as u16, top + input.cursor_row as u16))?;
+        self.input_rows = height;
+        self.input_cursor_row = input.cursor_row as u16;
+        Ok(())
+    }
+    
+    /// Move to the last line of the input and forget it was drawn
+    ///
+    /// Call before printing anything after the input, so the output starts
+    /// below all of it rather than over the lines under the cursor.
+    pub fn leave_input(&mut self) -> Result<()> {
+        let below = self.input_rows.saturating_sub(self.input_cursor_row + 1);
+        if below > 0 {
+            execute!(io::stdout(), cursor::MoveDown(below))?;
+        }
+        self.input_rows = 1;
+        self.input_cursor_row = 0;
         Ok(())
     }
     
@@ -210,12 +525,36 @@ impl TerminalManager {
         }
         
         if self.raw_mode_enabled {
+            // Inline the cursor style isn't undone by leaving the alternate screen
+            if self.is_inline() && cursor_style_escape(self.cursor_style).is_some() {
+                execute!(io::stdout(), Print(CURSOR_STYLE_RESET))?;
+            }
             terminal::disable_raw_mode()?;
             self.raw_mode_enabled = false;
         }
         Ok(())
     }
     
+    /// Temporarily hand the terminal back to a child process (e.g. $EDITOR)
+    pub fn suspend(&mut self) -> Result<()> {
+        self.resume_alternate_screen = self.alternate_screen_enabled;
+        self.restore()
+    }
+    
+    /// Re-acquire the terminal after `suspend`
+    pub fn resume(&mut self) -> Result<()> {
+        if !self.raw_mode_enabled {
+            terminal::enable_raw_mode()?;
+            self.raw_mode_enabled = true;
+        }
+        if self.resume_alternate_screen {
+            self.enter_alternate_screen()?;
+        } else if self.is_inline() {
+            self.apply_cursor_style()?;
+        }
+        Ok(())
+    }
+    
     /// Check if raw mode is enabled
     #[allow(dead_code)]
     pub fn is_raw_mode(&self) -> bool {
@@ -247,6 +586,198 @@ mod tests {
         // Drop will restore automatically
     }
     
+    /// Manager that never touched the real terminal (safe to drop in tests)
+    fn detached_manager() -> TerminalManager {
+        TerminalManager {
+            raw_mode_enabled: false,
+            alternate_screen_enabled: false,
+            status_info: None,
+            resume_alternate_screen: false,
+            last_status_rendered: None,
+            size: Some((80, 24)),
+            cursor_style: CursorStyle::Default,
+            layout: ScreenLayout::AlternateScreen,
+            input_rows: 1,
+            input_cursor_row: 0,
+        }
+    }
+    
+    fn status(model: &str) -> StatusInfo {
+        StatusInfo {
+            connection_state: "Connected".to_string(),
+            model: model.to_string(),
+            session_id: None,
+            backend_version_mismatch: None,
+            real_execution: false,
+            safe_mode: false,
+            tokens: 0,
+            tokens_estimated: false,
+            private: false,
+        }
+    }
+    
+    #[test]
+    fn test_identical_status_renders_once() {
+        let mut manager = detached_manager();
+        let mut out = Vec::new();
+        let mut renders = 0;
+        
+        for _ in 0..2 {
+            manager.set_status(status("gpt-4"));
+            if manager.draw_status_line_to(&mut out, 80, || Ok((0, 5))).unwrap() {
+                renders += 1;
+            }
+        }
+        assert_eq!(renders, 1);
+        let written = out.len();
+        assert!(written > 0);
+        
+        // A changed status repaints
+        manager.set_status(status("claude"));
+        assert!(manager.draw_status_line_to(&mut out, 80, || Ok((0, 5))).unwrap());
+        
+        // So does a width change or an explicit invalidation
+        assert!(manager.draw_status_line_to(&mut out, 100, || Ok((0, 5))).unwrap());
+        assert!(!manager.draw_status_line_to(&mut out, 100, || Ok((0, 5))).unwrap());
+        manager.invalidate_status();
+        assert!(manager.draw_status_line_to(&mut out, 100, || Ok((0, 5))).unwrap());
+    }
+    
+    #[test]
+    fn test_resize_redraw() {
+        assert_eq!(resize_redraw(Some((80, 24)), (80, 24)), ResizeRedraw::Nothing);
+        assert_eq!(resize_redraw(Some((80, 24)), (80, 40)), ResizeRedraw::StatusAndPrompt);
+        assert_eq!(resize_redraw(Some((80, 24)), (120, 24)), ResizeRedraw::Reflow);
+        assert_eq!(resize_redraw(Some((80, 24)), (60, 30)), ResizeRedraw::Reflow);
+        assert_eq!(resize_redraw(None, (80, 24)), ResizeRedraw::Reflow);
+        
+        // The manager remembers the size, so a repeated event is a no-op
+        let mut manager = detached_manager();
+        let mut out = Vec::new();
+        manager.set_status(status("gpt-4"));
+        assert!(manager.draw_status_line_to(&mut out, 80, || Ok((0, 5))).unwrap());
+        assert_eq!(manager.handle_resize(100, 24), ResizeRedraw::Reflow);
+        assert_eq!(manager.handle_resize(100, 24), ResizeRedraw::Nothing);
+        assert_eq!(manager.handle_resize(100, 30), ResizeRedraw::StatusAndPrompt);
+        // Redrawing after a resize repaints even at an unchanged width
+        assert!(manager.draw_status_line_to(&mut out, 80, || Ok((0, 5))).unwrap());
+        
+        // A reflow clears only the status rows, and inline there are none
+        let mut cleared = Vec::new();
+        manager.clear_status_area_to(&mut cleared).unwrap();
+        assert!(!String::from_utf8_lossy(&cleared).contains("\x1b[2J"));
+        assert!(!cleared.is_empty());
+        manager.set_layout(ScreenLayout::Inline);
+        cleared.clear();
+        manager.clear_status_area_to(&mut cleared).unwrap();
+        assert!(cleared.is_empty());
+    }
+    
+    #[test]
+    fn test_multi_line_input_top_row() {
+        // The alternate screen grows the input upward from the prompt row
+        assert_eq!(input_top_row(ScreenLayout::AlternateScreen, 24, 7, 1, 0), 22);
+        assert_eq!(input_top_row(ScreenLayout::AlternateScreen, 24, 7, 3, 1), 20);
+        assert_eq!(input_top_row(ScreenLayout::AlternateScreen, 2, 0, 5, 0), 0);
+        
+        // Inline it stays where it was drawn, whichever line the cursor is on
+        assert_eq!(input_top_row(ScreenLayout::Inline, 24, 7, 3, 0), 7);
+        assert_eq!(input_top_row(ScreenLayout::Inline, 24, 9, 3, 2), 7);
+    }
+    
+    #[test]
+    fn test_inline_layout() {
+        assert_eq!(ScreenLayout::from_config(true), ScreenLayout::AlternateScreen);
+        assert_eq!(ScreenLayout::from_config(false), ScreenLayout::Inline);
+        
+        // The alternate screen pins the prompt near the bottom; inline it follows the output
+        assert_eq!(prompt_row(ScreenLayout::AlternateScreen, 24, 7), 22);
+        assert_eq!(prompt_row(ScreenLayout::Inline, 24, 7), 7);
+        assert_eq!(prompt_row(ScreenLayout::AlternateScreen, 1, 0), 0);
+        
+        // Inline there is no pinned status line and nothing is reflowed
+        let mut manager = detached_manager();
+        manager.set_layout(ScreenLayout::Inline);
+        manager.set_status(status("mock"));
+        let mut out = Vec::new();
+        assert!(!manager.draw_status_line_to(&mut out, 80, || Ok((0, 5))).unwrap());
+        assert!(out.is_empty());
+        assert_eq!(manager.handle_resize(120, 24), ResizeRedraw::StatusAndPrompt);
+    }
+    
+    #[test]
+    fn test_cursor_style_escape() {
+        assert_eq!(cursor_style_escape(CursorStyle::Default), None);
+        assert_eq!(cursor_style_escape(CursorStyle::BlinkingBlock), Some("\x1b[1 q"));
+        assert_eq!(cursor_style_escape(CursorStyle::Block), Some("\x1b[2 q"));
+        assert_eq!(cursor_style_escape(CursorStyle::BlinkingUnderline), Some("\x1b[3 q"));
+        assert_eq!(cursor_style_escape(CursorStyle::Underline), Some("\x1b[4 q"));
+        assert_eq!(cursor_style_escape(CursorStyle::BlinkingBeam), Some("\x1b[5 q"));
+        assert_eq!(cursor_style_escape(CursorStyle::Beam), Some("\x1b[6 q"));
+    }
+    
+    #[test]
+    fn test_status_glyph_widths() {
+        let info = StatusInfo {
+            session_id: Some("2025-10-04_120000".to_string()),
+            private: true,
+            ..status("mock")
+        };
+        
+        let emoji = status_segments(&info, GlyphMode::Emoji).join("  │  ");
+        assert_eq!(emoji, "● Connected  │  🤖 mock  │  📝 2025-10-  │  🔒 PRIVATE");
+        // Each emoji takes two columns
+        assert_eq!(ansi::display_width(&emoji), emoji.chars().count() + 3);
+        
+        let ascii = status_segments(&info, GlyphMode::Ascii).join("  │  ");
+        assert_eq!(ascii, "● Connected  │  AI: mock  │  [S] 2025-10-  │  [LOCK] PRIVATE");
+        assert_eq!(ansi::display_width(&ascii), ascii.chars().count());
+        
+        // Either way the drawn line fits the terminal
+        for line in [&emoji, &ascii] {
+            for cols in [20, 30, 45] {
+                let max = cols - 4;
+                assert!(ansi::display_width(&ansi::truncate_display(line, max)) <= max);
+            }
+        }
+    }
+    
+    #[test]
+    fn test_safe_mode_banner() {
+        let mut manager = detached_manager();
+        let mut out = Vec::new();
+        manager.set_status(StatusInfo { safe_mode: true, ..status("mock") });
+        manager.draw_status_line_to(&mut out, 80, || Ok((0, 5))).unwrap();
+        assert!(String::from_utf8_lossy(&out).contains("SAFE MODE"));
+    }
+    
+    #[test]
+    fn test_prompt_badge_only_inline() {
+        let info = StatusInfo { safe_mode: true, real_execution: true, ..status("mock") };
+        assert_eq!(safety_badge(&info, GlyphMode::Ascii).as_deref(), Some("[SAFE] SAFE [!] EXEC"));
+        assert_eq!(safety_badge(&status("mock"), GlyphMode::Ascii), None);
+        
+        let mut manager = detached_manager();
+        manager.set_status(info);
+        assert_eq!(manager.prompt_badge(), None);
+        manager.set_layout(ScreenLayout::Inline);
+        assert!(manager.prompt_badge().is_some_and(|badge| badge.contains("SAFE") && badge.contains("EXEC")));
+    }
+    
+    #[test]
+    fn test_token_total_shown() {
+        let mut manager = detached_manager();
+        let mut out = Vec::new();
+        manager.set_status(status("mock"));
+        manager.draw_status_line_to(&mut out, 80, || Ok((0, 5))).unwrap();
+        assert!(!String::from_utf8_lossy(&out).contains("tok"));
+        
+        out.clear();
+        manager.set_status(StatusInfo { tokens: 42, tokens_estimated: true, ..status("mock") });
+        manager.draw_status_line_to(&mut out, 80, || Ok((0, 5))).unwrap();
+        assert!(String::from_utf8_lossy(&out).contains("~42 tok"));
+    }
+    
     #[test]
     #[ignore] // Skip in CI - requires TTY
     fn test_terminal_size() {
@@ -0,0 +1,50 @@
+// Tokenizer - Client-side token counting
+//
+// The backend reports exact token usage only when a response completes. To
+// show running totals without waiting on it, messages are counted locally
+// through a `Tokenizer`. The default is a heuristic estimate; a model-specific
+// tokenizer can be plugged in where exact counts matter.
+
+/// Counts tokens in a piece of text
+pub trait Tokenizer: Send + Sync {
+    /// Number of tokens in `text`
+    fn count(&self, text: &str) -> usize;
+
+    /// Whether counts are estimates rather than exact model token counts
+    fn is_estimate(&self) -> bool {
+        false
+    }
+}
+
+/// Rough estimate of about four characters per token
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HeuristicTokenizer;
+
+/// Average characters per token for English text and code
+const CHARS_PER_TOKEN: usize = 4;
+
+impl Tokenizer for HeuristicTokenizer {
+    fn count(&self, text: &str) -> usize {
+        text.chars().count().div_ceil(CHARS_PER_TOKEN)
+    }
+
+    fn is_estimate(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heuristic_count() {
+        let tokenizer = HeuristicTokenizer;
+        assert_eq!(tokenizer.count(""), 0);
+        assert_eq!(tokenizer.count("hi"), 1);
+        assert_eq!(tokenizer.count("hello world!"), 3);
+        // Characters, not bytes
+        assert_eq!(tokenizer.count("日本語です"), 2);
+        assert!(tokenizer.is_estimate());
+    }
+}
@@ -0,0 +1,190 @@
+// Transcript - Append streamed responses to a markdown file as they arrive
+//
+// Enabled with `--transcript <file>` or `terminal.transcript_file`. Each query
+// becomes one markdown entry: a header with the query and start time, the
+// response text as it streams, and a footer written on completion. The entry
+// is flushed when the response ends so the file is always readable.
+//
+// The active writer is process-wide (like the notice color switch) so the
+// streaming handlers can record without threading it through every call.
+
+use chrono::{DateTime, Utc};
+use log::warn;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Writer for the active transcript file, if one was opened
+static ACTIVE: Mutex<Option<TranscriptWriter<BufWriter<File>>>> = Mutex::new(None);
+
+/// How a recorded response ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Complete,
+    Cancelled,
+    /// The backend connection closed mid-response
+    Disconnected,
+}
+
+/// Appends query/response entries to a markdown sink
+pub struct TranscriptWriter<W: Write> {
+    out: W,
+    /// An entry has been started and not yet finished
+    in_entry: bool,
+}
+
+impl TranscriptWriter<BufWriter<File>> {
+    /// Open `path` for appending, creating it if needed
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> TranscriptWriter<W> {
+    /// Write entries to `out`
+    pub fn new(out: W) -> Self {
+        Self { out, in_entry: false }
+    }
+
+    /// Start an entry for `query`; an unfinished previous entry is closed first
+    pub fn begin(&mut self, query: &str, at: DateTime<Utc>) -> io::Result<()> {
+        if self.in_entry {
+            self.finish(Outcome::Cancelled, at)?;
+        }
+        writeln!(self.out, "## {}", at.format("%Y-%m-%d %H:%M:%S UTC"))?;
+        writeln!(self.out)?;
+        for line in query.lines() {
+            writeln!(self.out, "> {}", line)?;
+        }
+        writeln!(self.out)?;
+        self.in_entry = true;
+        Ok(())
+    }
+
+    /// Append streamed response text
+    pub fn push_text(&mut self, text: &str) -> io::Result<()> {
+        if self.in_entry {
+            self.out.write_all(text.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Append a structured block (`code` and `diff` become fenced blocks)
+    pub fn push_block(&mut self, block_type: &str, language: &str, content: &str) -> io::Result<()> {
+        if !self.in_entry {
+            return Ok(());
+        }
+        let fence = match block_type {
+            "code" => Some(language),
+            "diff" => Some("diff"),
+            _ => None,
+        };
+        match fence {
+            Some(lang) => {
+                write!(self.out, "\n```{}\n{}", lang, content)?;
+                if !content.ends_with('\n') {
+                    writeln!(self.out)?;
+                }
+                writeln!(self.out, "```")?;
+            }
+            None => self.out.write_all(content.as_bytes())?,
+        }
+        Ok(())
+    }
+
+    /// Close the current entry and flush it to the sink
+    pub fn finish(&mut self, outcome: Outcome, at: DateTime<Utc>) -> io::Result<()> {
+        if !std::mem::take(&mut self.in_entry) {
+            return Ok(());
+        }
+        let status = match outcome {
+            Outcome::Complete => "Completed",
+            Outcome::Cancelled => "Cancelled",
+            Outcome::Disconnected => "Connection lost",
+        };
+        writeln!(self.out)?;
+        writeln!(self.out)?;
+        writeln!(self.out, "_{} at {}_", status, at.format("%Y-%m-%d %H:%M:%S UTC"))?;
+        writeln!(self.out)?;
+        writeln!(self.out, "---")?;
+        writeln!(self.out)?;
+        self.out.flush()
+    }
+}
+
+/// Open the process-wide transcript, replacing any previous one
+pub fn open_global(path: &Path) -> io::Result<()> {
+    let writer = TranscriptWriter::open(path)?;
+    *ACTIVE.lock().unwrap_or_else(|e| e.into_inner()) = Some(writer);
+    Ok(())
+}
+
+/// Run `f` against the process-wide transcript, if one is open
+///
+/// Write errors are logged and otherwise ignored so they never disrupt the UI.
+pub fn record(f: impl FnOnce(&mut TranscriptWriter<BufWriter<File>>) -> io::Result<()>) {
+    let mut active = ACTIVE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(writer) = active.as_mut() {
+        if let Err(e) = f(writer) {
+            warn!("Failed to write transcript: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 10, 4, 12, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_entry_markdown() {
+        let mut writer = TranscriptWriter::new(Vec::new());
+        writer.begin("list files\nin src", at(0)).unwrap();
+        writer.push_text("Here you ").unwrap();
+        writer.push_text("go:").unwrap();
+        writer.push_block("code", "bash", "ls src").unwrap();
+        writer.finish(Outcome::Complete, at(1)).unwrap();
+
+        let expected = "## 2025-10-04 12:00:00 UTC\n\
+                        \n\
+                        > list files\n\
+                        > in src\n\
+                        \n\
+                        Here you go:\n\
+                        ```bash\n\
+                        ls src\n\
+                        ```\n\
+                        \n\
+                        \n\
+                        _Completed at 2025-10-04 12:01:00 UTC_\n\
+                        \n\
+                        ---\n\
+                        \n";
+        assert_eq!(String::from_utf8(writer.out).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_unfinished_entry_is_closed_as_cancelled() {
+        let mut writer = TranscriptWriter::new(Vec::new());
+        writer.begin("first", at(0)).unwrap();
+        writer.push_text("partial").unwrap();
+        writer.begin("second", at(2)).unwrap();
+        writer.finish(Outcome::Complete, at(3)).unwrap();
+
+        let text = String::from_utf8(writer.out).unwrap();
+        assert!(text.contains("partial\n\n_Cancelled at 2025-10-04 12:02:00 UTC_"));
+        assert!(text.contains("> second"));
+
+        // Output outside an entry is dropped and finishing twice is harmless
+        let mut writer = TranscriptWriter::new(Vec::new());
+        writer.push_text("stray").unwrap();
+        writer.finish(Outcome::Complete, at(0)).unwrap();
+        assert!(writer.out.is_empty());
+    }
+}
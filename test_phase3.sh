@@ -22,7 +22,7 @@ else
     RUNTIME_DIR="/tmp"
 fi
 
-SOCKET_PATH="$RUNTIME_DIR/openagent-terminal-test.sock"
+SOCKET_PATH="$RUNTIME_DIR/openagent-terminal.sock"
 
 echo "📁 Runtime directory: $RUNTIME_DIR"
 echo "🔌 Socket path: $SOCKET_PATH"
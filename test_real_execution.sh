@@ -17,7 +17,7 @@ NC='\033[0m' # No Color
 
 # Get runtime directory
 RUNTIME_DIR="${XDG_RUNTIME_DIR:-/tmp}"
-SOCKET_PATH="$RUNTIME_DIR/openagent-terminal-test.sock"
+SOCKET_PATH="$RUNTIME_DIR/openagent-terminal.sock"
 
 echo "📁 Runtime directory: $RUNTIME_DIR"
 echo "🔌 Socket path: $SOCKET_PATH"
@@ -194,7 +194,7 @@ import os
 
 async def test_session_commands():
     runtime_dir = os.environ.get("XDG_RUNTIME_DIR", "/tmp")
-    socket_path = f"{runtime_dir}/openagent-terminal-test.sock"
+    socket_path = f"{runtime_dir}/openagent-terminal.sock"
     
     if not os.path.exists(socket_path):
         print("⚠️  Socket not found, skipping IPC tests")
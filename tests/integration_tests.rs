@@ -12,17 +12,15 @@ async fn test_streaming_cancellation() {
     let (cancel_tx, mut cancel_rx) = watch::channel(false);
     
     // Simulate a streaming loop
-    let mut received_items = Vec::new();
-    let mut loop_exited_cleanly = false;
-    
     tokio::spawn(async move {
-        loop {
+        let mut received_items = Vec::new();
+        
+        let loop_exited_cleanly = loop {
             tokio::select! {
                 // Check for cancellation
                 Ok(_) = cancel_rx.changed() => {
                     if *cancel_rx.borrow() {
-                        loop_exited_cleanly = true;
-                        break;
+                        break true;
                     }
                 }
                 // Simulate receiving stream items
@@ -33,7 +31,7 @@ async fn test_streaming_cancellation() {
                     }
                 }
             }
-        }
+        };
         
         // Verify clean exit
         assert!(loop_exited_cleanly);
@@ -298,7 +296,7 @@ fn test_ansi_nesting() {
 /// Test ANSI reset codes
 #[test]
 fn test_ansi_reset() {
-    let colored = format!("\x1b[31mRed\x1b[0m Normal");
+    let colored = "\x1b[31mRed\x1b[0m Normal".to_string();
     assert!(colored.starts_with("\x1b[31m"));
     assert!(colored.contains("\x1b[0m"));
 }